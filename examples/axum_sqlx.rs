@@ -0,0 +1,369 @@
+//! A full-stack example that wires the session store into an [axum](https://crates.io/crates/axum) application
+//! with a [sqlx](https://crates.io/crates/sqlx) connector, CSRF protection and a sweeper for expired sessions.
+//!
+//! Run it against PostgreSQL with
+//! `DATABASE_URL=postgres://localhost/sessions cargo run --example axum_sqlx --features postgres-store,sqlite-store`.
+//! The test suite executes the same application against an in-memory SQLite database.
+
+use axum::extract::State;
+use axum::http::header::{COOKIE, SET_COOKIE};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::{AppendHeaders, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use chrono::{Duration, Utc};
+use rand::distributions::{Alphanumeric, DistString};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::sync::Arc;
+use tracing::{debug, error};
+use typed_session::{
+    check_csrf, CookieValue, CsrfDecision, CsrfTokenState, PostgresStore, Session,
+    SessionRenewalStrategy, SessionStore, SessionStoreConnector,
+};
+
+/// The header that carries the CSRF token in both directions.
+const CSRF_HEADER: &str = "x-csrf-token";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionData {
+    visits: u64,
+    csrf_token: String,
+}
+
+struct AppState<SessionStoreConnection> {
+    store: SessionStore<SessionData, SessionStoreConnection>,
+    connection: SessionStoreConnection,
+}
+
+type SharedState<SessionStoreConnection> = Arc<AppState<SessionStoreConnection>>;
+
+fn app<SessionStoreConnection>(connection: SessionStoreConnection) -> Router
+where
+    SessionStoreConnection: SessionStoreConnector<SessionData> + Send + Sync + Clone + 'static,
+    SessionStoreConnection::Error: Send,
+{
+    let store = SessionStore::new(SessionRenewalStrategy::AutomaticRenewal {
+        time_to_live: Duration::hours(24),
+        maximum_remaining_time_to_live_for_renewal: Duration::hours(20),
+    });
+    Router::new()
+        .route("/", get(visit))
+        .route("/logout", post(logout))
+        .with_state(Arc::new(AppState { store, connection }))
+}
+
+/// Count the visits of the client and hand out the CSRF token of its session.
+async fn visit<SessionStoreConnection>(
+    State(state): State<SharedState<SessionStoreConnection>>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode>
+where
+    SessionStoreConnection: SessionStoreConnector<SessionData> + Send + Sync + Clone,
+    SessionStoreConnection::Error: Send,
+{
+    let mut connection = state.connection.clone();
+    let mut session = load_session(&state, &headers, &mut connection).await?;
+    let data = session.data_mut();
+    data.visits += 1;
+    if data.csrf_token.is_empty() {
+        data.csrf_token = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    }
+    let body = data.visits.to_string();
+    let csrf_token = data.csrf_token.clone();
+
+    let set_cookie = store_session(&state, session, &mut connection).await?;
+    Ok((
+        AppendHeaders(set_cookie.map(|value| (SET_COOKIE, value))),
+        [(CSRF_HEADER, csrf_token)],
+        body,
+    )
+        .into_response())
+}
+
+/// Delete the session of the client, if the request carries its CSRF token.
+async fn logout<SessionStoreConnection>(
+    State(state): State<SharedState<SessionStoreConnection>>,
+    method: Method,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode>
+where
+    SessionStoreConnection: SessionStoreConnector<SessionData> + Send + Sync + Clone,
+    SessionStoreConnection::Error: Send,
+{
+    let mut connection = state.connection.clone();
+    let mut session = load_session(&state, &headers, &mut connection).await?;
+    let token_state = match headers.get(CSRF_HEADER) {
+        None => CsrfTokenState::Missing,
+        Some(token)
+            if !session.data().csrf_token.is_empty()
+                && token.as_bytes() == session.data().csrf_token.as_bytes() =>
+        {
+            CsrfTokenState::Valid
+        }
+        Some(_) => CsrfTokenState::Invalid,
+    };
+    if check_csrf(method.as_str(), token_state) == CsrfDecision::Reject {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    session.delete();
+    let set_cookie = store_session(&state, session, &mut connection).await?;
+    Ok((
+        StatusCode::NO_CONTENT,
+        AppendHeaders(set_cookie.map(|value| (SET_COOKIE, value))),
+    )
+        .into_response())
+}
+
+/// Load the session of the client, or create a new one if the client has none or sent a malformed cookie.
+async fn load_session<SessionStoreConnection>(
+    state: &AppState<SessionStoreConnection>,
+    headers: &HeaderMap,
+    connection: &mut SessionStoreConnection,
+) -> Result<Session<SessionData>, StatusCode>
+where
+    SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+{
+    let cookie_name = &state.store.cookie_settings().name;
+    let cookie_value = headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .flat_map(|header| header.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| name == cookie_name)
+        .map(|(_, value)| CookieValue::new(value));
+
+    let Some(cookie_value) = cookie_value else {
+        return Ok(Session::new());
+    };
+    match state.store.load_session(&cookie_value, connection).await {
+        Ok(session) => Ok(session.unwrap_or_else(Session::new)),
+        Err(error) if error.is_malformed_cookie() => {
+            debug!("Ignoring a malformed session cookie: {error:?}");
+            Ok(Session::new())
+        }
+        Err(error) => Err(internal_server_error(error)),
+    }
+}
+
+/// Store the session and return the `Set-Cookie` header for the client, if its cookie changed.
+async fn store_session<SessionStoreConnection>(
+    state: &AppState<SessionStoreConnection>,
+    session: Session<SessionData>,
+    connection: &mut SessionStoreConnection,
+) -> Result<Option<HeaderValue>, StatusCode>
+where
+    SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+{
+    let command = state
+        .store
+        .store_session(session, connection)
+        .await
+        .map_err(internal_server_error)?;
+    command
+        .set_cookie_header()
+        .map(HeaderValue::try_from)
+        .transpose()
+        .map_err(internal_server_error)
+}
+
+fn internal_server_error(error: impl Debug) -> StatusCode {
+    error!("Internal server error: {error:?}");
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// Periodically delete expired sessions, since the connector does not do this by itself.
+fn spawn_sweeper<SessionStoreConnection>(
+    mut connection: SessionStoreConnection,
+    period: std::time::Duration,
+) -> tokio::task::JoinHandle<()>
+where
+    SessionStoreConnection: SessionStoreConnector<SessionData> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(error) = connection.delete_expired_sessions(Utc::now()).await {
+                error!("Could not delete expired sessions: {error:?}");
+            }
+        }
+    })
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let database_url = std::env::var("DATABASE_URL")?;
+    let connection = PostgresStore::new(sqlx::PgPool::connect(&database_url).await?);
+    connection.migrate().await?;
+    spawn_sweeper(connection.clone(), std::time::Duration::from_secs(60));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+    axum::serve(listener, app(connection)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+    use typed_session::{
+        SessionExpiry, SessionId, SessionMetadata, SqliteStore, WriteSessionResult,
+    };
+
+    async fn sqlite_connection() -> SqliteStore<SessionData> {
+        // Every connection to an in-memory database opens a new database, hence use a single connection.
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let connection = SqliteStore::new(pool);
+        connection.migrate().await.unwrap();
+        connection
+    }
+
+    async fn request(app: &Router, request: Request<Body>) -> (StatusCode, HeaderMap, String) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, headers, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn cookie(headers: &HeaderMap) -> String {
+        let set_cookie = headers.get(SET_COOKIE).unwrap().to_str().unwrap();
+        set_cookie.split(';').next().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_example() {
+        let mut connection = sqlite_connection().await;
+        let app = app(connection.clone());
+
+        let (status, headers, body) =
+            request(&app, Request::get("/").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "1");
+        let csrf_token = headers.get(CSRF_HEADER).unwrap().clone();
+
+        let (status, headers, body) = request(
+            &app,
+            Request::get("/")
+                .header(COOKIE, cookie(&headers))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "2");
+        assert_eq!(headers.get(CSRF_HEADER), Some(&csrf_token));
+        let cookie = cookie(&headers);
+
+        let (status, _, _) = request(
+            &app,
+            Request::post("/logout")
+                .header(COOKIE, &cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, headers, _) = request(
+            &app,
+            Request::post("/logout")
+                .header(COOKIE, &cookie)
+                .header(CSRF_HEADER, csrf_token)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(headers
+            .get(SET_COOKIE)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("Max-Age=0"));
+
+        let (_, _, body) = request(
+            &app,
+            Request::get("/")
+                .header(COOKIE, &cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(body, "1");
+        assert_eq!(
+            connection
+                .delete_expired_sessions(Utc::now())
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    /// Ensure that clients with malformed cookies get a new session instead of an error.
+    #[tokio::test]
+    async fn test_malformed_cookie() {
+        let app = app(sqlite_connection().await);
+        let (status, headers, body) = request(
+            &app,
+            Request::get("/")
+                .header(COOKIE, "session=malformed")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "1");
+        assert!(headers.get(SET_COOKIE).is_some());
+    }
+
+    /// Ensure that the sweeper deletes expired sessions, and only those.
+    #[tokio::test]
+    async fn test_sweeper() {
+        let mut connection = sqlite_connection().await;
+        for (cookie, expiry) in [
+            ("expired", Utc::now() - Duration::hours(1)),
+            ("valid", Utc::now() + Duration::hours(1)),
+        ] {
+            let result = connection
+                .create_session(
+                    &SessionId::from_cookie_value(&CookieValue::new(cookie)),
+                    &SessionExpiry::DateTime(expiry),
+                    &SessionMetadata::default(),
+                    &SessionData::default(),
+                )
+                .await
+                .unwrap();
+            assert!(matches!(result, WriteSessionResult::Ok(())));
+        }
+        assert_eq!(connection.count_sessions().await.unwrap(), 2);
+
+        let sweeper = spawn_sweeper(connection.clone(), std::time::Duration::from_millis(10));
+        for _ in 0..100 {
+            if connection.count_sessions().await.unwrap() == 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        sweeper.abort();
+        assert_eq!(connection.count_sessions().await.unwrap(), 1);
+        assert!(connection
+            .is_session_valid(
+                &SessionId::from_cookie_value(&CookieValue::new("valid")),
+                Utc::now()
+            )
+            .await
+            .unwrap());
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{
+    CookieValue, DefaultSessionCookieGenerator, Session, SessionCookieGenerator, SessionStore,
+    SessionStoreConnector,
+};
+use actix_web::body::MessageBody;
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::ErrorInternalServerError;
+use actix_web::http::header::{HeaderValue, COOKIE, SET_COOKIE};
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+use std::fmt::{Debug, Formatter};
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use tracing::{debug, error};
+
+/// An [actix-web](https://crates.io/crates/actix-web) middleware that manages the session of each request,
+/// available under the feature flag `actix`.
+///
+/// This works like the [`SessionLayer`](crate::SessionLayer) for tower:
+/// the session is loaded before the request is handled, or created with default data if there is none,
+/// and stored afterwards, setting the `Set-Cookie` header of the response as required.
+///
+/// Handlers receive the session through the [`Session`] extractor.
+/// To modify the session, the handler inserts it into the extensions of the response.
+/// If the response carries no session, the session is stored as it was loaded, such that it is still renewed.
+///
+/// The connection is cloned for each request, so it should be a cheap handle like a connection pool.
+/// Malformed session cookies are treated like cookies of missing sessions, such that the new session overwrites them.
+/// If loading or storing the session fails otherwise, the error is logged and an internal server error is returned.
+///
+/// # Example
+///
+/// ```rust
+/// # use actix_web::{web, App, HttpMessage, HttpResponse};
+/// # use typed_session::{MemoryStore, Session, SessionMiddleware, SessionRenewalStrategy, SessionStore};
+/// async fn visit(mut session: Session<u64>) -> HttpResponse {
+///     *session.data_mut() += 1;
+///     let mut response = HttpResponse::Ok().body(session.data().to_string());
+///     response.extensions_mut().insert(session);
+///     response
+/// }
+///
+/// let store: SessionStore<u64, _> = SessionStore::new(SessionRenewalStrategy::Ignore);
+/// let connection = MemoryStore::new();
+/// let app = App::new()
+///     .wrap(SessionMiddleware::new(store, connection))
+///     .route("/", web::get().to(visit));
+/// ```
+pub struct SessionMiddleware<
+    SessionData,
+    SessionStoreConnection,
+    CookieGenerator = DefaultSessionCookieGenerator,
+> {
+    store: Rc<SessionStore<SessionData, SessionStoreConnection, CookieGenerator>>,
+    connection: SessionStoreConnection,
+}
+
+/// The service created by a [`SessionMiddleware`].
+pub struct SessionMiddlewareService<
+    Inner,
+    SessionData,
+    SessionStoreConnection,
+    CookieGenerator = DefaultSessionCookieGenerator,
+> {
+    inner: Rc<Inner>,
+    store: Rc<SessionStore<SessionData, SessionStoreConnection, CookieGenerator>>,
+    connection: SessionStoreConnection,
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator>
+    SessionMiddleware<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Create a new middleware that manages sessions with the given session store and connection.
+    pub fn new(
+        store: SessionStore<SessionData, SessionStoreConnection, CookieGenerator>,
+        connection: SessionStoreConnection,
+    ) -> Self {
+        Self {
+            store: Rc::new(store),
+            connection,
+        }
+    }
+}
+
+impl<Inner, ResponseBody, SessionData, SessionStoreConnection, CookieGenerator>
+    Transform<Inner, ServiceRequest>
+    for SessionMiddleware<SessionData, SessionStoreConnection, CookieGenerator>
+where
+    Inner: Service<ServiceRequest, Response = ServiceResponse<ResponseBody>, Error = actix_web::Error>
+        + 'static,
+    ResponseBody: MessageBody + 'static,
+    SessionData: Debug + Default + Clone + 'static,
+    SessionStoreConnection: SessionStoreConnector<SessionData> + Send + Clone + 'static,
+    CookieGenerator: SessionCookieGenerator + 'static,
+{
+    type Response = ServiceResponse<ResponseBody>;
+    type Error = actix_web::Error;
+    type Transform =
+        SessionMiddlewareService<Inner, SessionData, SessionStoreConnection, CookieGenerator>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, inner: Inner) -> Self::Future {
+        ready(Ok(SessionMiddlewareService {
+            inner: Rc::new(inner),
+            store: self.store.clone(),
+            connection: self.connection.clone(),
+        }))
+    }
+}
+
+impl<Inner, ResponseBody, SessionData, SessionStoreConnection, CookieGenerator>
+    Service<ServiceRequest>
+    for SessionMiddlewareService<Inner, SessionData, SessionStoreConnection, CookieGenerator>
+where
+    Inner: Service<ServiceRequest, Response = ServiceResponse<ResponseBody>, Error = actix_web::Error>
+        + 'static,
+    ResponseBody: MessageBody + 'static,
+    SessionData: Debug + Default + Clone + 'static,
+    SessionStoreConnection: SessionStoreConnector<SessionData> + Send + Clone + 'static,
+    CookieGenerator: SessionCookieGenerator + 'static,
+{
+    type Response = ServiceResponse<ResponseBody>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(inner);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let inner = self.inner.clone();
+        let store = self.store.clone();
+        let mut connection = self.connection.clone();
+
+        Box::pin(async move {
+            let cookie_value = CookieValue::from_cookie_headers(
+                request
+                    .headers()
+                    .get_all(COOKIE)
+                    .filter_map(|header| header.to_str().ok()),
+                &store.cookie_settings().name,
+            );
+            let session = match cookie_value {
+                Some(cookie_value) => {
+                    match store.load_session(&cookie_value, &mut connection).await {
+                        Ok(session) => session.unwrap_or_else(Session::new),
+                        Err(error) if error.is_malformed_cookie() => {
+                            debug!("Ignoring a malformed session cookie: {error:?}");
+                            Session::new()
+                        }
+                        Err(error) => {
+                            error!("Could not load session: {error:?}");
+                            return Err(ErrorInternalServerError("could not load session"));
+                        }
+                    }
+                }
+                None => Session::new(),
+            };
+            request.extensions_mut().insert(session.clone());
+
+            let mut response = inner.call(request).await?;
+
+            let session = response
+                .response_mut()
+                .extensions_mut()
+                .remove::<Session<SessionData>>()
+                .unwrap_or(session);
+            let command = store
+                .store_session(session, &mut connection)
+                .await
+                .map_err(|error| {
+                    error!("Could not store session: {error:?}");
+                    ErrorInternalServerError("could not store session")
+                })?;
+            if let Some(set_cookie) = command.set_cookie_header() {
+                let set_cookie = HeaderValue::try_from(set_cookie).map_err(|error| {
+                    error!("Could not encode session cookie: {error:?}");
+                    ErrorInternalServerError("could not encode session cookie")
+                })?;
+                response.headers_mut().append(SET_COOKIE, set_cookie);
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Extracts the session loaded by the [`SessionMiddleware`].
+///
+/// The extracted session is a copy, so changes must be passed back to the middleware
+/// by inserting the session into the extensions of the response.
+/// Extraction fails with an internal server error if the middleware is not installed.
+impl<SessionData: Clone + 'static> FromRequest for Session<SessionData> {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(request.extensions().get::<Self>().cloned().ok_or_else(|| {
+            error!("Extracted a session without the session middleware");
+            ErrorInternalServerError("session middleware is not installed")
+        }))
+    }
+}
+
+impl<SessionData, SessionStoreConnection: Clone, CookieGenerator> Clone
+    for SessionMiddleware<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            connection: self.connection.clone(),
+        }
+    }
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator> Debug
+    for SessionMiddleware<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionMiddleware").finish_non_exhaustive()
+    }
+}
+
+impl<Inner, SessionData, SessionStoreConnection, CookieGenerator> Debug
+    for SessionMiddlewareService<Inner, SessionData, SessionStoreConnection, CookieGenerator>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionMiddlewareService")
+            .finish_non_exhaustive()
+    }
+}
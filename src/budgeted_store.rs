@@ -0,0 +1,221 @@
+use crate::{
+    ConnectorDescription, Error, RequestContext, Session, SessionExpiry, SessionId,
+    SessionMetadata, SessionStoreConnector, WriteSessionResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+/// A session store connector that caps the number of operations performed on behalf of a single request.
+///
+/// The cap is taken from [`RequestContext::operation_budget`].
+/// All operations between consecutive calls of [`set_request_context`](SessionStoreConnector::set_request_context)
+/// with the same [request id](RequestContext::request_id) share one budget, e.g. loading and storing the session
+/// of one request.
+/// Once the budget is spent, each further operation fails with [`Error::OperationBudgetExceeded`].
+/// Operations without a request context, or with a request context without budget, are not limited.
+///
+/// This protects the backend from pathological loops in middleware, and makes the cost of a request predictable.
+#[derive(Debug, Clone)]
+pub struct BudgetedStore<SessionStoreConnection> {
+    inner: SessionStoreConnection,
+    request_id: Option<String>,
+    budget: Option<u32>,
+    spent: u32,
+}
+
+impl<SessionStoreConnection> BudgetedStore<SessionStoreConnection> {
+    /// Wrap the given connector.
+    pub fn new(inner: SessionStoreConnection) -> Self {
+        Self {
+            inner,
+            request_id: None,
+            budget: None,
+            spent: 0,
+        }
+    }
+
+    /// Returns the wrapped connector.
+    pub fn inner(&self) -> &SessionStoreConnection {
+        &self.inner
+    }
+
+    /// Returns the wrapped connector, consuming this wrapper.
+    pub fn into_inner(self) -> SessionStoreConnection {
+        self.inner
+    }
+
+    /// The number of operations performed on behalf of the current or most recent request.
+    pub fn spent_operations(&self) -> u32 {
+        self.spent
+    }
+
+    fn spend<InnerError>(&mut self, operation: &str) -> Result<(), Error<InnerError>> {
+        if let Some(budget) = self.budget {
+            if self.spent >= budget {
+                warn!("Refused to {operation}, because the request already spent its budget of {budget} session store operations");
+                return Err(Error::OperationBudgetExceeded { budget });
+            }
+            self.spent += 1;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+    > SessionStoreConnector<SessionData> for BudgetedStore<SessionStoreConnection>
+{
+    type Error = SessionStoreConnection::Error;
+
+    fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        self.inner.maximum_retries_on_id_collision()
+    }
+
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        self.inner.is_transient_error(error)
+    }
+
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::wrapping("BudgetedStore", [self.inner.describe()])
+    }
+
+    fn enforces_expiry(&self) -> bool {
+        self.inner.enforces_expiry()
+    }
+
+    fn accepts_session_id(&self, new_id: &SessionId, previous_id: Option<&SessionId>) -> bool {
+        self.inner.accepts_session_id(new_id, previous_id)
+    }
+
+    async fn create_session(
+        &mut self,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        self.spend("create session")?;
+        self.inner
+            .create_session(current_id, expiry, metadata, data)
+            .await
+    }
+
+    async fn read_session(
+        &mut self,
+        id: SessionId,
+    ) -> Result<Option<Session<SessionData>>, Error<Self::Error>> {
+        self.spend("read session")?;
+        self.inner.read_session(id).await
+    }
+
+    async fn update_session(
+        &mut self,
+        current_id: &SessionId,
+        previous_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        self.spend("update session")?;
+        self.inner
+            .update_session(current_id, previous_id, expiry, metadata, data)
+            .await
+    }
+
+    async fn delete_session(&mut self, id: &SessionId) -> Result<(), Error<Self::Error>> {
+        self.spend("delete session")?;
+        self.inner.delete_session(id).await
+    }
+
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.spend("delete sessions")?;
+        self.inner.delete_sessions(ids).await
+    }
+
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        self.spend("create tombstone")?;
+        self.inner.create_tombstone(id, expiry).await
+    }
+
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.spend("check tombstone")?;
+        self.inner.is_tombstone(id, now).await
+    }
+
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.spend("check session validity")?;
+        self.inner.is_session_valid(id, now).await
+    }
+
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        self.spend("read clock")?;
+        self.inner.now().await
+    }
+
+    fn set_request_context(&mut self, context: Option<&RequestContext>) {
+        if let Some(context) = context {
+            if context.request_id.is_none() || context.request_id != self.request_id {
+                self.spent = 0;
+            }
+            self.request_id = context.request_id.clone();
+            self.budget = context.operation_budget;
+        } else {
+            self.budget = None;
+        }
+        self.inner.set_request_context(context);
+    }
+
+    async fn read_sessions(
+        &mut self,
+        ids: &[SessionId],
+    ) -> Result<Vec<Option<Session<SessionData>>>, Error<Self::Error>>
+    where
+        SessionData: Send,
+    {
+        self.spend("read sessions")?;
+        self.inner.read_sessions(ids).await
+    }
+
+    async fn preload_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.spend("preload sessions")?;
+        self.inner.preload_sessions(ids).await
+    }
+
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        self.spend("count sessions")?;
+        self.inner.count_sessions().await
+    }
+
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        self.spend("delete expired sessions")?;
+        self.inner.delete_expired_sessions(before).await
+    }
+
+    async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
+        self.spend("clear")?;
+        self.inner.clear().await
+    }
+
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        self.spend("clear namespace")?;
+        self.inner.clear_namespace(namespace).await
+    }
+}
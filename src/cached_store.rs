@@ -0,0 +1,511 @@
+use crate::{
+    ConnectorDescription, Error, RequestContext, Session, SessionExpiry, SessionId,
+    SessionMetadata, SessionStoreConnector, Spawner, WriteSessionResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// A session store connector that caches read sessions in-process, in front of another connector.
+///
+/// The cache holds at most `capacity` sessions and evicts the least recently used one when it is full.
+/// Cached sessions are served for at most `time_to_live` after they were read from the inner connector.
+/// All writes go to the inner connector, and invalidate the cached copies of the sessions they affect.
+///
+/// The cache is shared between all clones of this connector, so a clone can be handed to each request.
+/// However, it is not shared between processes: a session that is updated or deleted through another process
+/// may still be served from the cache until its time-to-live ends.
+/// Since updating a session changes its id, this means that a replaced cookie may stay valid for up to `time_to_live`
+/// on other processes, so choose it accordingly.
+///
+/// For read-heavy workloads, this removes the backend from the hot path of most requests.
+/// [`SessionStore::preload`](crate::SessionStore::preload) fills the cache ahead of time.
+/// To also remove the backend from the hot path when cached sessions reach their time-to-live,
+/// see [`set_stale_while_revalidate`](CachedStore::set_stale_while_revalidate).
+pub struct CachedStore<SessionData, SessionStoreConnection> {
+    inner: SessionStoreConnection,
+    cache: Arc<Mutex<SessionCache<SessionData>>>,
+    stale_while_revalidate: Option<StaleWhileRevalidate>,
+}
+
+struct SessionCache<SessionData> {
+    capacity: usize,
+    time_to_live: Duration,
+    entries: HashMap<SessionId, CacheEntry<SessionData>>,
+    /// The ids of all entries, ordered by their last use.
+    recency: BTreeMap<u64, SessionId>,
+    clock: u64,
+    /// The ids of all entries that are being refreshed in the background.
+    refreshing: HashSet<SessionId>,
+    /// The number of invalidations so far, such that reads and refreshes that raced with a write are discarded.
+    invalidations: u64,
+}
+
+struct StaleWhileRevalidate {
+    max_staleness: Duration,
+    refresh: Arc<dyn Fn(SessionId, u64) + Send + Sync>,
+}
+
+struct CacheEntry<SessionData> {
+    session: Session<SessionData>,
+    cached_at: Instant,
+    last_used: u64,
+}
+
+impl<SessionData, SessionStoreConnection> CachedStore<SessionData, SessionStoreConnection> {
+    /// Wrap the given connector with a cache of at most `capacity` sessions,
+    /// each of which is cached for at most `time_to_live`.
+    pub fn new(inner: SessionStoreConnection, capacity: usize, time_to_live: Duration) -> Self {
+        Self {
+            inner,
+            cache: Arc::new(Mutex::new(SessionCache {
+                capacity,
+                time_to_live,
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                clock: 0,
+                refreshing: HashSet::new(),
+                invalidations: 0,
+            })),
+            stale_while_revalidate: None,
+        }
+    }
+
+    /// Returns the wrapped connector.
+    pub fn inner(&self) -> &SessionStoreConnection {
+        &self.inner
+    }
+
+    /// Returns the wrapped connector, consuming this wrapper.
+    pub fn into_inner(self) -> SessionStoreConnection {
+        self.inner
+    }
+
+    /// Returns the number of sessions in the cache, including those whose time-to-live has ended.
+    pub fn cached_sessions(&self) -> usize {
+        self.cache.lock().unwrap().entries.len()
+    }
+
+    /// Removes all sessions from the cache.
+    pub fn invalidate_all(&self) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.entries.clear();
+        cache.recency.clear();
+        cache.invalidations += 1;
+    }
+
+    /// Remove the given sessions from the cache, and discard all reads that are in flight.
+    ///
+    /// Writes call this both before and after they reach the wrapped connector,
+    /// since reads that started while a write was in flight may have read the previous state.
+    fn invalidate(&self, ids: &[&SessionId]) {
+        let mut cache = self.cache.lock().unwrap();
+        for id in ids {
+            cache.remove(id);
+        }
+        cache.invalidations += 1;
+    }
+
+    fn max_staleness(&self) -> Option<Duration> {
+        self.stale_while_revalidate
+            .as_ref()
+            .map(|stale_while_revalidate| stale_while_revalidate.max_staleness)
+    }
+
+    /// Refresh the given session in the background, discarding the result if the cache was invalidated
+    /// since it had `invalidations` invalidations.
+    fn refresh(&self, id: SessionId, invalidations: u64) {
+        if let Some(stale_while_revalidate) = &self.stale_while_revalidate {
+            (stale_while_revalidate.refresh)(id, invalidations);
+        }
+    }
+}
+
+impl<
+        SessionData: Clone + Send + Sync + 'static,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Clone + Send + Sync + 'static,
+    > CachedStore<SessionData, SessionStoreConnection>
+{
+    /// Serve cached sessions for up to `max_staleness` after their time-to-live ended,
+    /// and refresh them from the wrapped connector in the background with the given spawner.
+    ///
+    /// This trades strict freshness for tail latency: a read of a stale session returns immediately, and only
+    /// the first read after the time-to-live ended triggers a refresh. Once the refresh completes,
+    /// the cache holds the current version of the session for another `time_to_live`,
+    /// or drops the session if it no longer exists.
+    /// If the cache was invalidated by a write while the refresh was running, its result is discarded
+    /// together with the stale session, such that the next read goes to the wrapped connector.
+    /// Sessions that are staler than `max_staleness` are read from the wrapped connector as usual.
+    /// Hence, a session that was updated or deleted through another process may be served for up to
+    /// `time_to_live + max_staleness`.
+    ///
+    /// Refreshes use a clone of the wrapped connector as it is when this is called, without request context.
+    /// Failed refreshes are logged, and the stale session is kept until `max_staleness` ends.
+    pub fn set_stale_while_revalidate(
+        &mut self,
+        max_staleness: Duration,
+        spawner: impl Spawner + 'static,
+    ) {
+        let inner = self.inner.clone();
+        let cache = self.cache.clone();
+        let refresh = move |id: SessionId, invalidations: u64| {
+            let mut inner = inner.clone();
+            let cache = cache.clone();
+            spawner.spawn(Box::pin(async move {
+                let result = inner.read_session(id.clone()).await;
+                let mut cache = cache.lock().unwrap();
+                match result {
+                    Ok(session) => cache.finish_refresh(id, invalidations, session),
+                    Err(error) => {
+                        warn!("Could not refresh a stale cached session: {error:?}");
+                        cache.refreshing.remove(&id);
+                    }
+                }
+            }));
+        };
+        self.stale_while_revalidate = Some(StaleWhileRevalidate {
+            max_staleness,
+            refresh: Arc::new(refresh),
+        });
+    }
+}
+
+impl<SessionData> SessionCache<SessionData> {
+    fn remove(&mut self, id: &SessionId) {
+        if let Some(entry) = self.entries.remove(id) {
+            self.recency.remove(&entry.last_used);
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+impl<SessionData: Clone> SessionCache<SessionData> {
+    /// Returns the cached session with the given id, and if it needs to be refreshed in the background,
+    /// the number of invalidations so far.
+    ///
+    /// Sessions whose time-to-live ended are only returned if they are at most `max_staleness` stale,
+    /// and then need to be refreshed unless a refresh is already running.
+    fn get(
+        &mut self,
+        id: &SessionId,
+        max_staleness: Option<Duration>,
+    ) -> Option<(Session<SessionData>, Option<u64>)> {
+        let age = self.entries.get(id)?.cached_at.elapsed();
+        let stale = age >= self.time_to_live;
+        if stale
+            && max_staleness
+                .is_none_or(|max_staleness| age >= self.time_to_live.saturating_add(max_staleness))
+        {
+            self.remove(id);
+            return None;
+        }
+        let refresh = (stale && self.refreshing.insert(id.clone())).then_some(self.invalidations);
+
+        let now = self.tick();
+        let entry = self.entries.get_mut(id)?;
+        let previous = std::mem::replace(&mut entry.last_used, now);
+        let session = entry.session.clone();
+        self.recency.remove(&previous);
+        self.recency.insert(now, id.clone());
+        Some((session, refresh))
+    }
+
+    /// Store the result of a background refresh.
+    ///
+    /// If the cache was invalidated since the refresh started, the result may be outdated,
+    /// so the stale session is dropped instead, and the next read goes to the wrapped connector.
+    fn finish_refresh(
+        &mut self,
+        id: SessionId,
+        invalidations: u64,
+        session: Option<Session<SessionData>>,
+    ) {
+        self.refreshing.remove(&id);
+        match session {
+            Some(session) if invalidations == self.invalidations => self.insert(id, session),
+            _ => self.remove(&id),
+        }
+    }
+
+    /// Store a session that was read from the wrapped connector, unless the cache was invalidated since the read started.
+    /// Otherwise, a read that raced with e.g. a deletion could cache the deleted session.
+    fn insert_read(&mut self, id: SessionId, invalidations: u64, session: Session<SessionData>) {
+        if invalidations == self.invalidations {
+            self.insert(id, session);
+        }
+    }
+
+    fn insert(&mut self, id: SessionId, session: Session<SessionData>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.remove(&id);
+        while self.entries.len() >= self.capacity {
+            let Some((_, evicted)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&evicted);
+        }
+
+        let now = self.tick();
+        self.recency.insert(now, id.clone());
+        self.entries.insert(
+            id,
+            CacheEntry {
+                session,
+                cached_at: Instant::now(),
+                last_used: now,
+            },
+        );
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Clone + Send + Sync,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+    > SessionStoreConnector<SessionData> for CachedStore<SessionData, SessionStoreConnection>
+{
+    type Error = SessionStoreConnection::Error;
+
+    fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        self.inner.maximum_retries_on_id_collision()
+    }
+
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        self.inner.is_transient_error(error)
+    }
+
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::wrapping("CachedStore", [self.inner.describe()])
+    }
+
+    // Cached sessions may have expired since they were read, so the session store must check expiry itself.
+    fn enforces_expiry(&self) -> bool {
+        false
+    }
+
+    fn accepts_session_id(&self, new_id: &SessionId, previous_id: Option<&SessionId>) -> bool {
+        self.inner.accepts_session_id(new_id, previous_id)
+    }
+
+    async fn create_session(
+        &mut self,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        self.invalidate(&[current_id]);
+        let result = self
+            .inner
+            .create_session(current_id, expiry, metadata, data)
+            .await;
+        self.invalidate(&[current_id]);
+        result
+    }
+
+    async fn read_session(
+        &mut self,
+        id: SessionId,
+    ) -> Result<Option<Session<SessionData>>, Error<Self::Error>> {
+        let cached = self.cache.lock().unwrap().get(&id, self.max_staleness());
+        if let Some((session, refresh)) = cached {
+            if let Some(invalidations) = refresh {
+                self.refresh(id, invalidations);
+            }
+            return Ok(Some(session));
+        }
+
+        let invalidations = self.cache.lock().unwrap().invalidations;
+        let session = self.inner.read_session(id.clone()).await?;
+        if let Some(session) = &session {
+            self.cache
+                .lock()
+                .unwrap()
+                .insert_read(id, invalidations, session.clone());
+        }
+        Ok(session)
+    }
+
+    async fn read_sessions(
+        &mut self,
+        ids: &[SessionId],
+    ) -> Result<Vec<Option<Session<SessionData>>>, Error<Self::Error>>
+    where
+        SessionData: Send,
+    {
+        let max_staleness = self.max_staleness();
+        let (cached, invalidations): (Vec<_>, _) = {
+            let mut cache = self.cache.lock().unwrap();
+            let cached = ids.iter().map(|id| cache.get(id, max_staleness)).collect();
+            (cached, cache.invalidations)
+        };
+        let mut sessions = Vec::with_capacity(ids.len());
+        for (id, cached) in ids.iter().zip(cached) {
+            sessions.push(cached.map(|(session, refresh)| {
+                if let Some(invalidations) = refresh {
+                    self.refresh(id.clone(), invalidations);
+                }
+                session
+            }));
+        }
+        let missing: Vec<_> = ids
+            .iter()
+            .zip(&sessions)
+            .filter(|(_, session)| session.is_none())
+            .map(|(id, _)| id.clone())
+            .collect();
+        if missing.is_empty() {
+            return Ok(sessions);
+        }
+
+        let mut read = self.inner.read_sessions(&missing).await?.into_iter();
+        let mut cache = self.cache.lock().unwrap();
+        for (id, session) in ids.iter().zip(&mut sessions) {
+            if session.is_none() {
+                *session = read.next().flatten();
+                if let Some(session) = session {
+                    cache.insert_read(id.clone(), invalidations, session.clone());
+                }
+            }
+        }
+        Ok(sessions)
+    }
+
+    async fn update_session(
+        &mut self,
+        current_id: &SessionId,
+        previous_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        self.invalidate(&[current_id, previous_id]);
+        let result = self
+            .inner
+            .update_session(current_id, previous_id, expiry, metadata, data)
+            .await;
+        self.invalidate(&[current_id, previous_id]);
+        result
+    }
+
+    async fn delete_session(&mut self, id: &SessionId) -> Result<(), Error<Self::Error>> {
+        self.invalidate(&[id]);
+        let result = self.inner.delete_session(id).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.invalidate(&ids.iter().collect::<Vec<_>>());
+        let result = self.inner.delete_sessions(ids).await;
+        self.invalidate(&ids.iter().collect::<Vec<_>>());
+        result
+    }
+
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        self.invalidate(&[id]);
+        let result = self.inner.create_tombstone(id, expiry).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.inner.is_tombstone(id, now).await
+    }
+
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.inner.is_session_valid(id, now).await
+    }
+
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        self.inner.now().await
+    }
+
+    fn set_request_context(&mut self, context: Option<&RequestContext>) {
+        self.inner.set_request_context(context);
+    }
+
+    async fn preload_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.read_sessions(ids).await?;
+        Ok(())
+    }
+
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        self.inner.count_sessions().await
+    }
+
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        self.inner.delete_expired_sessions(before).await
+    }
+
+    async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
+        self.invalidate_all();
+        let result = self.inner.clear().await;
+        self.invalidate_all();
+        result
+    }
+
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        self.invalidate_all();
+        let result = self.inner.clear_namespace(namespace).await;
+        self.invalidate_all();
+        result
+    }
+}
+
+impl<SessionData, SessionStoreConnection: Clone> Clone
+    for CachedStore<SessionData, SessionStoreConnection>
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            cache: self.cache.clone(),
+            stale_while_revalidate: self.stale_while_revalidate.as_ref().map(
+                |stale_while_revalidate| StaleWhileRevalidate {
+                    max_staleness: stale_while_revalidate.max_staleness,
+                    refresh: stale_while_revalidate.refresh.clone(),
+                },
+            ),
+        }
+    }
+}
+
+impl<SessionData, SessionStoreConnection: Debug> Debug
+    for CachedStore<SessionData, SessionStoreConnection>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let cache = self.cache.lock().unwrap();
+        f.debug_struct("CachedStore")
+            .field("inner", &self.inner)
+            .field("capacity", &cache.capacity)
+            .field("time_to_live", &cache.time_to_live)
+            .field("max_staleness", &self.max_staleness())
+            .field("cached_sessions", &cache.entries.len())
+            .finish()
+    }
+}
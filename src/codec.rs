@@ -0,0 +1,96 @@
+use std::fmt::Debug;
+
+/// A serialization format for session data.
+///
+/// Connectors that store session data as bytes can be generic over a codec, instead of deciding the serialization
+/// by themselves. This allows to write a single connector for any `SessionData: Serialize`.
+///
+/// Codec layers should prefix each record with a [`RecordHeader`](crate::RecordHeader) carrying the
+/// [`codec_id`](SessionDataCodec::codec_id), such that records stay readable after the codec changes.
+///
+/// Implementations for JSON and bincode are available as [`JsonCodec`] and [`BincodeCodec`]
+/// under the feature flags `json-codec` and `bincode-codec`.
+pub trait SessionDataCodec<SessionData> {
+    /// The error type of this codec.
+    type Error: Debug;
+
+    /// The id of this codec, to be stored in the [`RecordHeader`](crate::RecordHeader) of each record.
+    ///
+    /// The ids `0` to `127` are reserved for codecs of this crate.
+    fn codec_id(&self) -> u8;
+
+    /// Encode the given session data.
+    fn encode(&self, data: &SessionData) -> Result<Vec<u8>, Self::Error>;
+
+    /// Decode session data that was encoded with [`encode`](SessionDataCodec::encode).
+    fn decode(&self, bytes: &[u8]) -> Result<SessionData, Self::Error>;
+}
+
+/// A [`SessionDataCodec`] that encodes session data as JSON using [serde_json](https://crates.io/crates/serde_json),
+/// available under the feature flag `json-codec`.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{JsonCodec, SessionDataCodec};
+/// let bytes = JsonCodec.encode(&vec![1, 2]).unwrap();
+/// assert_eq!(bytes, b"[1,2]");
+/// assert_eq!(JsonCodec.decode(&bytes).ok(), Some(vec![1, 2]));
+/// ```
+#[cfg(feature = "json-codec")]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct JsonCodec;
+
+#[cfg(feature = "json-codec")]
+impl<SessionData: serde::Serialize + serde::de::DeserializeOwned> SessionDataCodec<SessionData>
+    for JsonCodec
+{
+    type Error = serde_json::Error;
+
+    fn codec_id(&self) -> u8 {
+        1
+    }
+
+    fn encode(&self, data: &SessionData) -> Result<Vec<u8>, Self::Error> {
+        serde_json::to_vec(data)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<SessionData, Self::Error> {
+        serde_json::from_slice(bytes)
+    }
+}
+
+/// A [`SessionDataCodec`] that encodes session data in the compact binary format of [bincode](https://crates.io/crates/bincode),
+/// available under the feature flag `bincode-codec`.
+///
+/// Bincode is not self-describing, so changing the type of the session data makes existing records undecodable.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{BincodeCodec, SessionDataCodec};
+/// let bytes = BincodeCodec.encode(&(1u8, true)).unwrap();
+/// assert_eq!(BincodeCodec.decode(&bytes).ok(), Some((1u8, true)));
+/// ```
+#[cfg(feature = "bincode-codec")]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct BincodeCodec;
+
+#[cfg(feature = "bincode-codec")]
+impl<SessionData: serde::Serialize + serde::de::DeserializeOwned> SessionDataCodec<SessionData>
+    for BincodeCodec
+{
+    type Error = bincode::Error;
+
+    fn codec_id(&self) -> u8 {
+        2
+    }
+
+    fn encode(&self, data: &SessionData) -> Result<Vec<u8>, Self::Error> {
+        bincode::serialize(data)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<SessionData, Self::Error> {
+        bincode::deserialize(bytes)
+    }
+}
@@ -0,0 +1,54 @@
+/// The state of the CSRF token of a request, as determined by the framework adapter.
+///
+/// The token is typically bound to the session, e.g. stored in its data or issued as a nonce with
+/// [`SessionStore::issue_nonce`](crate::SessionStore::issue_nonce),
+/// and sent by the client in a header or form field.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum CsrfTokenState {
+    /// The request does not carry a CSRF token.
+    Missing,
+    /// The request carries a CSRF token that does not match the session.
+    Invalid,
+    /// The request carries a CSRF token that matches the session.
+    Valid,
+}
+
+/// The decision of [`check_csrf`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[must_use]
+pub enum CsrfDecision {
+    /// The request may be processed.
+    Allow,
+    /// The request must be rejected, e.g. with `403 Forbidden`.
+    Reject,
+}
+
+/// Returns true if the given HTTP method is safe according to [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#section-9.2.1),
+/// i.e. requests with it must not change the state of the server and hence need no CSRF protection.
+///
+/// Method names are case-sensitive, and unknown methods are considered unsafe.
+pub fn is_safe_method(method: &str) -> bool {
+    matches!(method, "GET" | "HEAD" | "OPTIONS" | "TRACE")
+}
+
+/// Decide whether a request with the given HTTP method and CSRF token state must be rejected.
+///
+/// Requests with safe methods are always allowed, see [`is_safe_method`].
+/// All other requests are only allowed with a valid CSRF token.
+/// Framework adapters should use this function such that they all enforce identical rules.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{check_csrf, CsrfDecision, CsrfTokenState};
+/// assert_eq!(check_csrf("GET", CsrfTokenState::Missing), CsrfDecision::Allow);
+/// assert_eq!(check_csrf("POST", CsrfTokenState::Missing), CsrfDecision::Reject);
+/// assert_eq!(check_csrf("POST", CsrfTokenState::Valid), CsrfDecision::Allow);
+/// ```
+pub fn check_csrf(method: &str, token_state: CsrfTokenState) -> CsrfDecision {
+    if is_safe_method(method) || token_state == CsrfTokenState::Valid {
+        CsrfDecision::Allow
+    } else {
+        CsrfDecision::Reject
+    }
+}
@@ -0,0 +1,440 @@
+use crate::{
+    ConnectorDescription, Error, RecordHeader, RecordHeaderError, RequestContext, Session,
+    SessionDataCodec, SessionExpiry, SessionId, SessionMetadata, SessionStoreConnector,
+    WriteSessionResult,
+};
+use async_trait::async_trait;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+
+/// The length of the random nonce that is stored in front of each ciphertext.
+const NONCE_LENGTH: usize = 24;
+
+/// A session store connector that encrypts session data before delegating to an inner connector,
+/// available under the feature flag `encrypted-store`.
+///
+/// Session data is encoded with a [`SessionDataCodec`] and encrypted with XChaCha20-Poly1305 under a key derived
+/// from a user-supplied secret. The inner connector stores the resulting records as `Vec<u8>`.
+/// This protects session payloads if the database of the inner connector leaks,
+/// complementing the hashing of session ids.
+/// Each record is bound to its session id, such that records cannot be swapped between sessions unnoticed.
+/// The expiry and the [`SessionMetadata`] are not encrypted, since the inner connector may need them.
+///
+/// Each record carries a [`RecordHeader`] with the id of the key it was encrypted with.
+/// To rotate keys, construct the connector with the new key and add the old key with
+/// [`add_decryption_key`](EncryptedStore::add_decryption_key).
+/// Sessions encrypted with the old key stay readable, and are encrypted with the new key on their next update.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{EncryptedStore, JsonCodec, MemoryStore, Session, SessionRenewalStrategy, SessionStore};
+/// # async_std::task::block_on(async {
+/// let mut connection = EncryptedStore::new(MemoryStore::new(), JsonCodec, 1, b"a secret of sufficient length");
+/// let store: SessionStore<i32, _> = SessionStore::new(SessionRenewalStrategy::Ignore);
+/// store.store_session(Session::new_with_data(1), &mut connection).await.unwrap();
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct EncryptedStore<SessionStoreConnection, Codec> {
+    inner: SessionStoreConnection,
+    codec: Codec,
+    key_id: u32,
+    keys: BTreeMap<u32, XChaCha20Poly1305>,
+}
+
+/// The errors of an [`EncryptedStore`].
+#[derive(Debug, thiserror::Error)]
+pub enum EncryptedStoreError<SessionStoreConnectorError, CodecError> {
+    /// The inner connector returned an error.
+    #[error("{0:?}")]
+    Inner(SessionStoreConnectorError),
+
+    /// The codec could not encode or decode session data.
+    #[error("could not encode or decode session data: {0:?}")]
+    Codec(CodecError),
+
+    /// The header of a record is invalid.
+    #[error("invalid record header: {0}")]
+    RecordHeader(#[from] RecordHeaderError),
+
+    /// A record was encoded with a different codec.
+    #[error("the record was encoded with the unknown codec {0}")]
+    UnknownCodec(u8),
+
+    /// A record was encrypted with a key that is not known to this connector.
+    /// A record without a key id was not encrypted, which is reported as `None`.
+    #[error("the record was encrypted with the unknown key {0:?}")]
+    UnknownKey(Option<u32>),
+
+    /// A record is compressed, which this connector does not support.
+    #[error("the record is compressed")]
+    Compressed,
+
+    /// A record could not be decrypted, i.e. it was modified, or it belongs to a different session.
+    #[error("could not decrypt the record")]
+    Decryption,
+}
+
+type EncryptedStoreResult<T, SessionStoreConnection, Codec, SessionData> = Result<
+    T,
+    Error<
+        EncryptedStoreError<
+            <SessionStoreConnection as SessionStoreConnector<Vec<u8>>>::Error,
+            <Codec as SessionDataCodec<SessionData>>::Error,
+        >,
+    >,
+>;
+
+/// Derive a key for XChaCha20-Poly1305 from a user-supplied secret.
+fn cipher(secret: &[u8]) -> XChaCha20Poly1305 {
+    let key = blake3::derive_key("typed-session 2024-01-01 EncryptedStore key", secret);
+    XChaCha20Poly1305::new(&key.into())
+}
+
+impl<SessionStoreConnection, Codec> EncryptedStore<SessionStoreConnection, Codec> {
+    /// Wrap the given connector, encoding session data with the given codec and encrypting it
+    /// with a key derived from `secret`.
+    ///
+    /// The `key_id` is stored with each record, to select the key for decryption.
+    /// The secret should have at least 256 bits of entropy.
+    pub fn new(
+        inner: SessionStoreConnection,
+        codec: Codec,
+        key_id: u32,
+        secret: impl AsRef<[u8]>,
+    ) -> Self {
+        Self {
+            inner,
+            codec,
+            key_id,
+            keys: BTreeMap::from([(key_id, cipher(secret.as_ref()))]),
+        }
+    }
+
+    /// Add a key that is only used to decrypt records, e.g. a key that was replaced in a key rotation.
+    ///
+    /// Replaces the key with the same `key_id`, except for the key that is used for encryption.
+    pub fn add_decryption_key(&mut self, key_id: u32, secret: impl AsRef<[u8]>) {
+        if key_id != self.key_id {
+            self.keys.insert(key_id, cipher(secret.as_ref()));
+        }
+    }
+
+    /// Returns the id of the key that is used for encryption.
+    pub fn key_id(&self) -> u32 {
+        self.key_id
+    }
+
+    /// Returns the wrapped connector.
+    pub fn inner(&self) -> &SessionStoreConnection {
+        &self.inner
+    }
+
+    /// Returns the wrapped connector, consuming this wrapper.
+    pub fn into_inner(self) -> SessionStoreConnection {
+        self.inner
+    }
+
+    fn encrypt<SessionData>(
+        &self,
+        id: &SessionId,
+        data: &SessionData,
+    ) -> EncryptedStoreResult<Vec<u8>, SessionStoreConnection, Codec, SessionData>
+    where
+        SessionStoreConnection: SessionStoreConnector<Vec<u8>>,
+        Codec: SessionDataCodec<SessionData>,
+    {
+        let plaintext = self
+            .codec
+            .encode(data)
+            .map_err(EncryptedStoreError::Codec)?;
+        let mut nonce = [0; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self.keys[&self.key_id]
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: id.as_ref(),
+                },
+            )
+            .expect("encryption into a vector cannot fail");
+
+        let header = RecordHeader {
+            codec_id: self.codec.codec_id(),
+            key_id: Some(self.key_id),
+            compressed: false,
+        };
+        Ok(header.encode(&[&nonce[..], &ciphertext].concat()))
+    }
+
+    fn decrypt<SessionData>(
+        &self,
+        id: &SessionId,
+        record: &[u8],
+    ) -> EncryptedStoreResult<SessionData, SessionStoreConnection, Codec, SessionData>
+    where
+        SessionStoreConnection: SessionStoreConnector<Vec<u8>>,
+        Codec: SessionDataCodec<SessionData>,
+    {
+        let (header, payload) = RecordHeader::decode(record).map_err(EncryptedStoreError::from)?;
+        if header.codec_id != self.codec.codec_id() {
+            return Err(EncryptedStoreError::UnknownCodec(header.codec_id).into());
+        }
+        if header.compressed {
+            return Err(EncryptedStoreError::Compressed.into());
+        }
+        let Some(cipher) = header.key_id.and_then(|key_id| self.keys.get(&key_id)) else {
+            return Err(EncryptedStoreError::UnknownKey(header.key_id).into());
+        };
+        if payload.len() < NONCE_LENGTH {
+            return Err(EncryptedStoreError::Decryption.into());
+        }
+        let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+        let plaintext = cipher
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: id.as_ref(),
+                },
+            )
+            .map_err(|_| EncryptedStoreError::Decryption)?;
+        Ok(self
+            .codec
+            .decode(&plaintext)
+            .map_err(EncryptedStoreError::Codec)?)
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync,
+        SessionStoreConnection: SessionStoreConnector<Vec<u8>> + Sync + Send,
+        Codec: SessionDataCodec<SessionData> + Send + Sync,
+    > SessionStoreConnector<SessionData> for EncryptedStore<SessionStoreConnection, Codec>
+{
+    type Error = EncryptedStoreError<SessionStoreConnection::Error, Codec::Error>;
+
+    fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        self.inner.maximum_retries_on_id_collision()
+    }
+
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        match error {
+            EncryptedStoreError::Inner(error) => self.inner.is_transient_error(error),
+            _ => false,
+        }
+    }
+
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::wrapping("EncryptedStore", [self.inner.describe()])
+    }
+
+    fn enforces_expiry(&self) -> bool {
+        self.inner.enforces_expiry()
+    }
+
+    fn accepts_session_id(&self, new_id: &SessionId, previous_id: Option<&SessionId>) -> bool {
+        self.inner.accepts_session_id(new_id, previous_id)
+    }
+
+    async fn create_session(
+        &mut self,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let record = self.encrypt(current_id, data)?;
+        self.inner
+            .create_session(current_id, expiry, metadata, &record)
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    async fn read_session(
+        &mut self,
+        id: SessionId,
+    ) -> Result<Option<Session<SessionData>>, Error<Self::Error>> {
+        let Some(session) = self
+            .inner
+            .read_session(id.clone())
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))?
+        else {
+            return Ok(None);
+        };
+        let metadata = session.metadata().clone();
+        let (Some(record), Some(expiry)) = session.into_data_expiry_pair() else {
+            return Ok(None);
+        };
+        let data = self.decrypt(&id, &record)?;
+        Ok(Some(Session::new_from_session_store(
+            id, expiry, metadata, data,
+        )))
+    }
+
+    async fn read_sessions(
+        &mut self,
+        ids: &[SessionId],
+    ) -> Result<Vec<Option<Session<SessionData>>>, Error<Self::Error>>
+    where
+        SessionData: Send,
+    {
+        let sessions = self
+            .inner
+            .read_sessions(ids)
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))?;
+        let mut result = Vec::with_capacity(sessions.len());
+        for (id, session) in ids.iter().zip(sessions) {
+            let Some(session) = session else {
+                result.push(None);
+                continue;
+            };
+            let metadata = session.metadata().clone();
+            let (Some(record), Some(expiry)) = session.into_data_expiry_pair() else {
+                result.push(None);
+                continue;
+            };
+            let data = self.decrypt(id, &record)?;
+            result.push(Some(Session::new_from_session_store(
+                id.clone(),
+                expiry,
+                metadata,
+                data,
+            )));
+        }
+        Ok(result)
+    }
+
+    async fn update_session(
+        &mut self,
+        current_id: &SessionId,
+        previous_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let record = self.encrypt(current_id, data)?;
+        self.inner
+            .update_session(current_id, previous_id, expiry, metadata, &record)
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    async fn delete_session(&mut self, id: &SessionId) -> Result<(), Error<Self::Error>> {
+        self.inner
+            .delete_session(id)
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.inner
+            .delete_sessions(ids)
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        self.inner
+            .create_tombstone(id, expiry)
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.inner
+            .is_tombstone(id, now)
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.inner
+            .is_session_valid(id, now)
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        self.inner
+            .now()
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    fn set_request_context(&mut self, context: Option<&RequestContext>) {
+        self.inner.set_request_context(context);
+    }
+
+    async fn preload_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.inner
+            .preload_sessions(ids)
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        self.inner
+            .count_sessions()
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        self.inner
+            .delete_expired_sessions(before)
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
+        self.inner
+            .clear()
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        self.inner
+            .clear_namespace(namespace)
+            .await
+            .map_err(|error| error.map_connector_error(EncryptedStoreError::Inner))
+    }
+}
+
+impl<SessionStoreConnection: Debug, Codec: Debug> Debug
+    for EncryptedStore<SessionStoreConnection, Codec>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedStore")
+            .field("inner", &self.inner)
+            .field("codec", &self.codec)
+            .field("key_id", &self.key_id)
+            .field("decryption_key_ids", &self.keys.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
@@ -11,6 +11,11 @@ pub enum Error<SessionStoreConnectorError> {
     #[error("the session that was attempted to be updated does not exist, which indicates that it was concurrently modified or deleted")]
     UpdatedSessionDoesNotExist,
 
+    /// A child session was attempted to be created, but its parent session does not exist.
+    /// This may happen if the parent was concurrently modified or deleted, or if the parent was stored before the child.
+    #[error("the parent of the child session that was attempted to be created does not exist")]
+    ParentSessionDoesNotExist,
+
     /// Tried as often as desired to generate a session id, but all generated ids already exist.
     #[error("the maximum number of retries to generate a session id was reached")]
     MaximumSessionIdGenerationTriesReached {
@@ -27,6 +32,91 @@ pub enum Error<SessionStoreConnectorError> {
         actual: usize,
     },
 
+    /// A session was attempted to be locked with [`SessionStore::with_session_locked`](crate::SessionStore::with_session_locked),
+    /// but it is already locked.
+    #[error("the session is locked")]
+    SessionLocked,
+
+    /// The operation requires a session that was loaded from the session store, but the session is new or deleted.
+    #[error("the session was not loaded from the session store")]
+    SessionNotStored,
+
+    /// A request with the same idempotency key is still in progress,
+    /// see [`SessionStore::run_idempotent`](crate::SessionStore::run_idempotent).
+    #[error("a request with the same idempotency key is still in progress")]
+    IdempotentRequestInProgress,
+
+    /// A write was attempted on a [`ReadOnlyStore`](crate::ReadOnlyStore) that rejects writes.
+    #[error("the session store is read-only")]
+    ReadOnlyStore,
+
+    /// A session could not be routed, because all regions of a [`RegionRoutedStore`](crate::RegionRoutedStore)
+    /// are marked as unavailable.
+    #[error("no region is available to route the session to")]
+    NoAvailableRegion,
+
+    /// A cookie value is not valid according to [`SessionCookieGenerator::is_valid_cookie`](crate::SessionCookieGenerator::is_valid_cookie).
+    /// This may be either a cookie received from a client, or a cookie generated by a faulty cookie generator.
+    #[error("the cookie value contains invalid characters")]
+    InvalidCookieValue,
+
+    /// An externally created token was refused by [`SessionStore::adopt_external_token`](crate::SessionStore::adopt_external_token),
+    /// because its estimated entropy is too low.
+    #[error("the external token has too little entropy to be used as session cookie")]
+    WeakExternalToken,
+
+    /// A [`BudgetedStore`](crate::BudgetedStore) refused an operation,
+    /// because the request already spent its [operation budget](crate::RequestContext::operation_budget).
+    #[error("the request exceeded its budget of {budget} session store operations")]
+    OperationBudgetExceeded {
+        /// The operation budget of the request.
+        budget: u32,
+    },
+
+    /// The session store connector does not support the attempted operation.
+    #[error("the session store connector does not support {operation}")]
+    UnsupportedOperation {
+        /// The name of the unsupported operation.
+        operation: &'static str,
+    },
+
+    /// A new session was not created, since the store already holds the maximum number of sessions,
+    /// see [`SessionStore::set_maximum_sessions`](crate::SessionStore::set_maximum_sessions).
+    #[error("the session store holds the maximum of {maximum_sessions} sessions")]
+    StoreAtCapacity {
+        /// The maximum number of sessions.
+        maximum_sessions: usize,
+    },
+
+    /// The session store connector returned a session with an implausible expiry,
+    /// see [`SessionStore::set_expiry_sanitization`](crate::SessionStore::set_expiry_sanitization).
+    #[error("the session store connector returned the corrupted expiry {expiry}")]
+    CorruptedExpiry {
+        /// The corrupted expiry.
+        expiry: chrono::DateTime<chrono::Utc>,
+    },
+
+    /// The session store connector returned a different number of sessions than were requested
+    /// from [`SessionStoreConnector::read_sessions`](crate::SessionStoreConnector::read_sessions).
+    #[error(
+        "the session store connector returned {actual} sessions, but {expected} were requested"
+    )]
+    BatchReadLengthMismatch {
+        /// The number of requested sessions.
+        expected: usize,
+        /// The number of returned sessions.
+        actual: usize,
+    },
+
+    /// A connector operation was rejected by the [`Interceptor`](crate::Interceptor) of the session store.
+    #[error("the interceptor rejected {operation}: {reason}")]
+    Intercepted {
+        /// The rejected operation.
+        operation: crate::ConnectorOperation,
+        /// The reason given by the interceptor.
+        reason: String,
+    },
+
     /// An error occurred in the session store connector.
     #[error("{0}")]
     SessionStoreConnector(SessionStoreConnectorError),
@@ -40,6 +130,57 @@ impl<SessionStoreConnectorError> From<SessionStoreConnectorError>
     }
 }
 
+impl<SessionStoreConnectorError> Error<SessionStoreConnectorError> {
+    /// Returns true if this error was caused by a malformed cookie, i.e. [`Error::WrongCookieLength`]
+    /// or [`Error::InvalidCookieValue`].
+    ///
+    /// When loading a session, such cookies were not issued by this session store, e.g. because they were
+    /// tampered with or issued by an older deployment, so they can be treated like cookies of missing sessions.
+    pub fn is_malformed_cookie(&self) -> bool {
+        matches!(
+            self,
+            Self::WrongCookieLength { .. } | Self::InvalidCookieValue
+        )
+    }
+
+    /// Convert the error of the session store connector with the given function, keeping all other variants.
+    ///
+    /// This is useful for wrapping connectors that have their own error type.
+    pub fn map_connector_error<OtherSessionStoreConnectorError>(
+        self,
+        f: impl FnOnce(SessionStoreConnectorError) -> OtherSessionStoreConnectorError,
+    ) -> Error<OtherSessionStoreConnectorError> {
+        match self {
+            Self::UpdatedSessionDoesNotExist => Error::UpdatedSessionDoesNotExist,
+            Self::ParentSessionDoesNotExist => Error::ParentSessionDoesNotExist,
+            Self::MaximumSessionIdGenerationTriesReached { maximum } => {
+                Error::MaximumSessionIdGenerationTriesReached { maximum }
+            }
+            Self::WrongCookieLength { expected, actual } => {
+                Error::WrongCookieLength { expected, actual }
+            }
+            Self::SessionLocked => Error::SessionLocked,
+            Self::SessionNotStored => Error::SessionNotStored,
+            Self::IdempotentRequestInProgress => Error::IdempotentRequestInProgress,
+            Self::ReadOnlyStore => Error::ReadOnlyStore,
+            Self::NoAvailableRegion => Error::NoAvailableRegion,
+            Self::InvalidCookieValue => Error::InvalidCookieValue,
+            Self::WeakExternalToken => Error::WeakExternalToken,
+            Self::OperationBudgetExceeded { budget } => Error::OperationBudgetExceeded { budget },
+            Self::UnsupportedOperation { operation } => Error::UnsupportedOperation { operation },
+            Self::StoreAtCapacity { maximum_sessions } => {
+                Error::StoreAtCapacity { maximum_sessions }
+            }
+            Self::CorruptedExpiry { expiry } => Error::CorruptedExpiry { expiry },
+            Self::BatchReadLengthMismatch { expected, actual } => {
+                Error::BatchReadLengthMismatch { expected, actual }
+            }
+            Self::Intercepted { operation, reason } => Error::Intercepted { operation, reason },
+            Self::SessionStoreConnector(error) => Error::SessionStoreConnector(f(error)),
+        }
+    }
+}
+
 /*impl<SessionStoreConnectorError: Display> Display for Error<SessionStoreConnectorError> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -51,11 +192,129 @@ impl<SessionStoreConnectorError> From<SessionStoreConnectorError>
     }
 }*/
 
-mod expect_impl_error {
-    trait ExpectImplError: std::error::Error {}
-
-    impl<SessionStoreConnectorError: std::error::Error> ExpectImplError
-        for super::Error<SessionStoreConnectorError>
-    {
+// Ensure at compile time that the error type implements `std::error::Error` if the connector error does.
+const _: () = {
+    const fn assert_error<T: std::error::Error>() {}
+    const fn check<SessionStoreConnectorError: std::error::Error + 'static>() {
+        assert_error::<Error<SessionStoreConnectorError>>()
     }
+    check::<std::convert::Infallible>()
+};
+
+/// The reasons why a [`SessionRenewalStrategy`](crate::SessionRenewalStrategy) can be invalid,
+/// see [`SessionRenewalStrategy::automatic_renewal`](crate::SessionRenewalStrategy::automatic_renewal)
+/// and [`SessionRenewalStrategy::idle_and_absolute_timeout`](crate::SessionRenewalStrategy::idle_and_absolute_timeout).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+pub enum InvalidRenewalStrategy {
+    /// The time-to-live is zero or negative, so sessions would expire immediately.
+    #[error(
+        "the time-to-live of the session renewal strategy must be positive, but is {time_to_live}"
+    )]
+    NonPositiveTimeToLive {
+        /// The given time-to-live.
+        time_to_live: chrono::Duration,
+    },
+
+    /// The maximum remaining time-to-live for renewal is negative, so sessions would expire before being renewed.
+    #[error("the maximum remaining time-to-live for renewal must not be negative, but is {maximum_remaining_time_to_live_for_renewal}")]
+    NegativeRenewalThreshold {
+        /// The given maximum remaining time-to-live for renewal.
+        maximum_remaining_time_to_live_for_renewal: chrono::Duration,
+    },
+
+    /// The maximum remaining time-to-live for renewal is not smaller than the time-to-live,
+    /// so sessions would be renewed on every request.
+    #[error("the maximum remaining time-to-live for renewal ({maximum_remaining_time_to_live_for_renewal}) must be smaller than the time-to-live ({time_to_live})")]
+    RenewalThresholdNotBelowTimeToLive {
+        /// The given time-to-live.
+        time_to_live: chrono::Duration,
+        /// The given maximum remaining time-to-live for renewal.
+        maximum_remaining_time_to_live_for_renewal: chrono::Duration,
+    },
+
+    /// The absolute timeout is smaller than the idle timeout, so the idle timeout would never take effect.
+    #[error("the absolute timeout ({absolute_timeout}) must not be smaller than the idle timeout ({idle_timeout})")]
+    AbsoluteTimeoutBelowIdleTimeout {
+        /// The given idle timeout.
+        idle_timeout: chrono::Duration,
+        /// The given absolute timeout.
+        absolute_timeout: chrono::Duration,
+    },
+}
+
+/// The reasons why the way a [`SessionStore`](crate::SessionStore) derives session ids from cookie values can be invalid,
+/// see [`SessionStore::set_hashing_policy`](crate::SessionStore::set_hashing_policy).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+pub enum InvalidHashingConfiguration {
+    /// [`HashingPolicy::Never`](crate::HashingPolicy::Never) uses cookie values as session ids,
+    /// but the cookie generator generates cookies that do not have the length of session ids.
+    #[error("HashingPolicy::Never requires cookies of length {expected}, but the cookie generator generates cookies of length {actual}")]
+    WrongCookieLength {
+        /// The length of session ids.
+        expected: usize,
+        /// The length of the cookies of the cookie generator.
+        actual: usize,
+    },
+
+    /// A key prefix is folded into the hash of the cookie value, so it requires
+    /// [`HashingPolicy::Always`](crate::HashingPolicy::Always).
+    #[error("a key prefix requires HashingPolicy::Always")]
+    KeyPrefixWithoutHashing,
+
+    /// Legacy cookies are hashed into session ids, so a legacy cookie format requires
+    /// [`HashingPolicy::Always`](crate::HashingPolicy::Always).
+    #[error("a legacy cookie format requires HashingPolicy::Always")]
+    LegacyCookieFormatWithoutHashing,
+}
+
+/// A duration could not be added to a point in time, because the result is not representable,
+/// see [`Session::try_expire_in`](crate::Session::try_expire_in).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+#[error("the duration {duration:?} is too large to be added to {now}")]
+pub struct DurationOutOfRange {
+    /// The point in time the duration was added to.
+    pub now: chrono::DateTime<chrono::Utc>,
+    /// The duration that was added.
+    pub duration: std::time::Duration,
+}
+
+/// A duration string could not be parsed, see [`parse_duration`](crate::parse_duration).
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+#[error("invalid duration {input:?}, expected e.g. \"7d\" or \"1h 30min\"")]
+pub struct InvalidDuration {
+    /// The string that could not be parsed.
+    pub input: String,
+}
+
+/// The runtime configuration of a session store could not be changed through its
+/// [`SessionStoreConfigHandle`](crate::SessionStoreConfigHandle), because the handle receives the configuration
+/// from a watch channel. In this case, the configuration must be changed through the sender of the channel.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+#[error("the configuration is received from a watch channel, and can only be changed through its sender")]
+pub struct ConfigNotWritable;
+
+/// A region that does not exist was passed to a [`RegionRoutedStore`](crate::RegionRoutedStore).
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+#[error("unknown region {region:?}")]
+pub struct UnknownRegion {
+    /// The name of the unknown region.
+    pub region: String,
+}
+
+/// The reasons why [`self_check`](crate::self_check) can fail.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+pub enum SelfCheckError {
+    /// The blake3 implementation does not produce the official test vectors.
+    #[error("the blake3 implementation does not match its official test vectors")]
+    Blake3,
+
+    /// Session ids are not derived from cookie values as expected.
+    #[error("session ids are not derived from cookie values as expected")]
+    SessionIdDerivation,
+
+    /// The default cookie generator produces invalid cookies, duplicate cookies, or cookies with too little entropy.
+    #[error(
+        "the default cookie generator does not produce distinct valid cookies with enough entropy"
+    )]
+    CookieGenerator,
 }
@@ -0,0 +1,373 @@
+use crate::{
+    ConnectorDescription, Error, RequestContext, Session, SessionExpiry, SessionId,
+    SessionMetadata, SessionStoreConnector, WriteSessionResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::warn;
+
+/// A session store connector that uses a primary connector, and falls back to a secondary connector
+/// when the primary one fails.
+///
+/// Each operation is first passed to the primary connector.
+/// If it fails with an error of the connector, i.e. [`Error::SessionStoreConnector`], the operation is passed to the
+/// secondary connector instead. If that fails as well, the error of the primary connector is returned,
+/// and the error of the secondary connector is logged.
+/// Other errors, like [`Error::UpdatedSessionDoesNotExist`], are returned without falling back.
+///
+/// With [dual writes](FallbackStore::set_dual_write), all successful writes of the primary connector are also
+/// applied to the secondary connector, such that it can take over at any time.
+/// Updates of sessions that are missing in the secondary connector create them there instead.
+/// This allows to migrate from the primary to the secondary connector without invalidating all sessions:
+/// once all sessions that existed before dual writes were enabled have expired, the connectors can be swapped.
+/// Errors of dual writes are logged, but do not affect the result of an operation.
+///
+/// Without dual writes, the secondary connector only knows the sessions that were written while the primary
+/// connector failed. Either way, sessions written while the primary connector failed are not visible after it
+/// recovered, since reads only fall back on errors.
+///
+/// Clones share their fallback counter.
+#[derive(Debug, Clone)]
+pub struct FallbackStore<Primary, Secondary> {
+    primary: Primary,
+    secondary: Secondary,
+    dual_write: bool,
+    fallbacks: Arc<AtomicU64>,
+}
+
+impl<Primary, Secondary> FallbackStore<Primary, Secondary> {
+    /// Use `primary`, and fall back to `secondary` when it fails.
+    pub fn new(primary: Primary, secondary: Secondary) -> Self {
+        Self {
+            primary,
+            secondary,
+            dual_write: false,
+            fallbacks: Default::default(),
+        }
+    }
+
+    /// If set, each successful write of the primary connector is also applied to the secondary connector.
+    pub fn set_dual_write(&mut self, dual_write: bool) {
+        self.dual_write = dual_write;
+    }
+
+    /// Returns the primary connector.
+    pub fn primary(&self) -> &Primary {
+        &self.primary
+    }
+
+    /// Returns the secondary connector.
+    pub fn secondary(&self) -> &Secondary {
+        &self.secondary
+    }
+
+    /// Returns the primary and the secondary connector, consuming this wrapper.
+    pub fn into_inner(self) -> (Primary, Secondary) {
+        (self.primary, self.secondary)
+    }
+
+    /// Returns the number of operations that fell back to the secondary connector so far.
+    pub fn fallbacks(&self) -> u64 {
+        self.fallbacks.load(Ordering::Relaxed)
+    }
+
+    /// Record that the given operation failed on the primary connector,
+    /// and return the result of the secondary connector, or the error of the primary connector if both failed.
+    fn fall_back<Output, PrimaryError: Debug, SecondaryError: Debug>(
+        &self,
+        operation: &str,
+        error: PrimaryError,
+        secondary_result: Result<Output, Error<SecondaryError>>,
+    ) -> Result<Output, Error<PrimaryError>> {
+        warn!("Primary session store failed on {operation}, falling back to secondary: {error:?}");
+        self.fallbacks.fetch_add(1, Ordering::Relaxed);
+        secondary_result.map_err(|secondary_error| {
+            warn!("Secondary session store failed on {operation}: {secondary_error:?}");
+            Error::SessionStoreConnector(error)
+        })
+    }
+
+    fn check_dual_write<Output, SecondaryError: Debug>(
+        &self,
+        operation: &str,
+        result: Result<Output, Error<SecondaryError>>,
+    ) {
+        if let Err(error) = result {
+            warn!("Dual write to secondary session store failed on {operation}: {error:?}");
+        }
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync,
+        Primary: SessionStoreConnector<SessionData> + Send,
+        Secondary: SessionStoreConnector<SessionData> + Send,
+    > SessionStoreConnector<SessionData> for FallbackStore<Primary, Secondary>
+where
+    // The error of the primary connector is kept while falling back.
+    Primary::Error: Send,
+{
+    type Error = Primary::Error;
+
+    fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        self.primary.maximum_retries_on_id_collision()
+    }
+
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        self.primary.is_transient_error(error)
+    }
+
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::wrapping(
+            "FallbackStore",
+            [self.primary.describe(), self.secondary.describe()],
+        )
+    }
+
+    fn enforces_expiry(&self) -> bool {
+        self.primary.enforces_expiry() && self.secondary.enforces_expiry()
+    }
+
+    /// Returns true if both connectors accept the id, since sessions may be written to either of them.
+    fn accepts_session_id(&self, new_id: &SessionId, previous_id: Option<&SessionId>) -> bool {
+        self.primary.accepts_session_id(new_id, previous_id)
+            && self.secondary.accepts_session_id(new_id, previous_id)
+    }
+
+    async fn create_session(
+        &mut self,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let result = match self
+            .primary
+            .create_session(current_id, expiry, metadata, data)
+            .await
+        {
+            Err(Error::SessionStoreConnector(error)) => {
+                let secondary_result = self
+                    .secondary
+                    .create_session(current_id, expiry, metadata, data)
+                    .await;
+                return self.fall_back("create session", error, secondary_result);
+            }
+            result => result?,
+        };
+        if self.dual_write && matches!(result, WriteSessionResult::Ok(())) {
+            let secondary_result = self
+                .secondary
+                .create_session(current_id, expiry, metadata, data)
+                .await;
+            self.check_dual_write("create session", secondary_result);
+        }
+        Ok(result)
+    }
+
+    async fn read_session(
+        &mut self,
+        id: SessionId,
+    ) -> Result<Option<Session<SessionData>>, Error<Self::Error>> {
+        match self.primary.read_session(id.clone()).await {
+            Err(Error::SessionStoreConnector(error)) => {
+                let secondary_result = self.secondary.read_session(id).await;
+                self.fall_back("read session", error, secondary_result)
+            }
+            result => result,
+        }
+    }
+
+    async fn update_session(
+        &mut self,
+        current_id: &SessionId,
+        previous_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let result = match self
+            .primary
+            .update_session(current_id, previous_id, expiry, metadata, data)
+            .await
+        {
+            Err(Error::SessionStoreConnector(error)) => {
+                let secondary_result = self
+                    .secondary
+                    .update_session(current_id, previous_id, expiry, metadata, data)
+                    .await;
+                return self.fall_back("update session", error, secondary_result);
+            }
+            result => result?,
+        };
+        if self.dual_write && matches!(result, WriteSessionResult::Ok(())) {
+            let needs_backfill = match self
+                .secondary
+                .update_session(current_id, previous_id, expiry, metadata, data)
+                .await
+            {
+                Err(Error::UpdatedSessionDoesNotExist) => true,
+                secondary_result => {
+                    self.check_dual_write("update session", secondary_result);
+                    false
+                }
+            };
+            if needs_backfill {
+                // The session was created before dual writes were enabled, so we create it.
+                let secondary_result = self
+                    .secondary
+                    .create_session(current_id, expiry, metadata, data)
+                    .await;
+                self.check_dual_write("update session", secondary_result);
+            }
+        }
+        Ok(result)
+    }
+
+    async fn delete_session(&mut self, id: &SessionId) -> Result<(), Error<Self::Error>> {
+        match self.primary.delete_session(id).await {
+            Err(Error::SessionStoreConnector(error)) => {
+                let secondary_result = self.secondary.delete_session(id).await;
+                self.fall_back("delete session", error, secondary_result)
+            }
+            result => {
+                result?;
+                if self.dual_write {
+                    let secondary_result = self.secondary.delete_session(id).await;
+                    self.check_dual_write("delete session", secondary_result);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        match self.primary.delete_sessions(ids).await {
+            Err(Error::SessionStoreConnector(error)) => {
+                let secondary_result = self.secondary.delete_sessions(ids).await;
+                self.fall_back("delete sessions", error, secondary_result)
+            }
+            result => {
+                result?;
+                if self.dual_write {
+                    let secondary_result = self.secondary.delete_sessions(ids).await;
+                    self.check_dual_write("delete sessions", secondary_result);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        match self.primary.create_tombstone(id, expiry).await {
+            Err(Error::SessionStoreConnector(error)) => {
+                let secondary_result = self.secondary.create_tombstone(id, expiry).await;
+                self.fall_back("create tombstone", error, secondary_result)
+            }
+            result => {
+                result?;
+                if self.dual_write {
+                    let secondary_result = self.secondary.create_tombstone(id, expiry).await;
+                    self.check_dual_write("create tombstone", secondary_result);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        match self.primary.is_tombstone(id, now).await {
+            Err(Error::SessionStoreConnector(error)) => {
+                let secondary_result = self.secondary.is_tombstone(id, now).await;
+                self.fall_back("is tombstone", error, secondary_result)
+            }
+            result => result,
+        }
+    }
+
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        match self.primary.is_session_valid(id, now).await {
+            Err(Error::SessionStoreConnector(error)) => {
+                let secondary_result = self.secondary.is_session_valid(id, now).await;
+                self.fall_back("is session valid", error, secondary_result)
+            }
+            result => result,
+        }
+    }
+
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        match self.primary.now().await {
+            Err(Error::SessionStoreConnector(error)) => {
+                let secondary_result = self.secondary.now().await;
+                self.fall_back("now", error, secondary_result)
+            }
+            result => result,
+        }
+    }
+
+    fn set_request_context(&mut self, context: Option<&RequestContext>) {
+        self.primary.set_request_context(context);
+        self.secondary.set_request_context(context);
+    }
+
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        match self.primary.count_sessions().await {
+            Err(Error::SessionStoreConnector(error)) => {
+                let secondary_result = self.secondary.count_sessions().await;
+                self.fall_back("count sessions", error, secondary_result)
+            }
+            result => result,
+        }
+    }
+
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        // Expired sessions are deleted from both connectors, since the secondary one may hold sessions
+        // that were written while the primary one failed.
+        let primary_result = self.primary.delete_expired_sessions(before).await;
+        let secondary_result = self.secondary.delete_expired_sessions(before).await;
+        match primary_result {
+            Err(Error::SessionStoreConnector(error)) => {
+                self.fall_back("delete expired sessions", error, secondary_result)
+            }
+            primary_result => {
+                self.check_dual_write("delete expired sessions", secondary_result);
+                primary_result
+            }
+        }
+    }
+
+    async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
+        self.primary.clear().await?;
+        if let Err(error) = self.secondary.clear().await {
+            warn!("Secondary session store failed on clear: {error:?}");
+        }
+        Ok(())
+    }
+
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        self.primary.clear_namespace(namespace).await?;
+        if let Err(error) = self.secondary.clear_namespace(namespace).await {
+            warn!("Secondary session store failed on clear_namespace: {error:?}");
+        }
+        Ok(())
+    }
+}
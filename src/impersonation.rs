@@ -0,0 +1,125 @@
+use crate::Session;
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+
+/// Session data that supports impersonation, e.g. an administrator acting as another user.
+///
+/// Use this as the `SessionData` of the session store, wrapping the actual session data.
+/// While impersonating, the session holds the impersonated data, and keeps the data of the impersonator
+/// such that it can be restored when the impersonation ends.
+///
+/// Both starting and stopping an impersonation change the session data, so the session gets a new id
+/// when it is stored. Since the data of the impersonator is stored within the same session, reverting is
+/// atomic: there is never a moment where both the impersonating and the original session are valid.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{ImpersonationSession, Session};
+/// # use chrono::Utc;
+/// let mut session: Session<_> = Session::new_with_data(ImpersonationSession::new("admin"));
+/// assert!(session.start_impersonation("user", Utc::now()));
+/// assert_eq!(*session.data().data(), "user");
+/// assert_eq!(session.data().impersonator(), Some(&"admin"));
+///
+/// assert!(session.stop_impersonation());
+/// assert_eq!(*session.data().data(), "admin");
+/// assert!(!session.data().is_impersonating());
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ImpersonationSession<SessionData> {
+    data: SessionData,
+    impersonator: Option<Impersonator<SessionData>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Impersonator<SessionData> {
+    data: Box<SessionData>,
+    started_at: DateTime<Utc>,
+}
+
+impl<SessionData> ImpersonationSession<SessionData> {
+    /// Create new session data that is not impersonating anyone.
+    pub fn new(data: SessionData) -> Self {
+        Self {
+            data,
+            impersonator: None,
+        }
+    }
+
+    /// Returns the current session data.
+    /// While impersonating, this is the impersonated data.
+    pub fn data(&self) -> &SessionData {
+        &self.data
+    }
+
+    /// Returns the current session data mutably.
+    /// While impersonating, this is the impersonated data.
+    pub fn data_mut(&mut self) -> &mut SessionData {
+        &mut self.data
+    }
+
+    /// Returns true if the session is currently impersonating.
+    pub fn is_impersonating(&self) -> bool {
+        self.impersonator.is_some()
+    }
+
+    /// Returns the session data of the impersonator, if the session is currently impersonating.
+    pub fn impersonator(&self) -> Option<&SessionData> {
+        self.impersonator
+            .as_ref()
+            .map(|impersonator| impersonator.data.as_ref())
+    }
+
+    /// Returns the time when the current impersonation was started, if the session is currently impersonating.
+    pub fn impersonation_started_at(&self) -> Option<DateTime<Utc>> {
+        self.impersonator
+            .as_ref()
+            .map(|impersonator| impersonator.started_at)
+    }
+
+    /// Consumes the session data and returns the current session data.
+    pub fn into_data(self) -> SessionData {
+        self.data
+    }
+}
+
+impl<SessionData: Debug, const COOKIE_LENGTH: usize>
+    Session<ImpersonationSession<SessionData>, COOKIE_LENGTH>
+{
+    /// Start impersonating, replacing the current session data with `impersonated_data`.
+    /// The current session data is kept, such that it can be restored with [`stop_impersonation`](Session::stop_impersonation).
+    ///
+    /// Returns false and does nothing if the session is already impersonating, as nested impersonations are not supported.
+    pub fn start_impersonation(
+        &mut self,
+        impersonated_data: SessionData,
+        now: DateTime<Utc>,
+    ) -> bool {
+        if self.data().is_impersonating() {
+            return false;
+        }
+
+        let data = self.data_mut();
+        let impersonator_data = std::mem::replace(&mut data.data, impersonated_data);
+        data.impersonator = Some(Impersonator {
+            data: Box::new(impersonator_data),
+            started_at: now,
+        });
+        true
+    }
+
+    /// Stop impersonating, restoring the session data of the impersonator.
+    ///
+    /// Returns false and does nothing if the session is not impersonating.
+    pub fn stop_impersonation(&mut self) -> bool {
+        if !self.data().is_impersonating() {
+            return false;
+        }
+
+        let data = self.data_mut();
+        let impersonator = data.impersonator.take().unwrap();
+        data.data = *impersonator.data;
+        true
+    }
+}
@@ -15,6 +15,11 @@
 //! data is stored in the database.
 //! The user on the front-end is responsible for communicating session cookies to the client
 //! by performing the [`SessionCookieCommand`] returned by [`SessionStore::store_session`].
+//! For [tower](https://crates.io/crates/tower)-based web frameworks like axum, the [`SessionLayer`]
+//! available under the feature flag `tower` does this out of the box,
+//! and so does the [`SessionMiddleware`] for actix-web, available under the feature flag `actix`.
+//! The command carries the [`CookieSettings`] of the session store, so
+//! [`SessionCookieCommand::set_cookie_header`] can build the complete `Set-Cookie` header.
 //!
 //! On the "back-end" of this crate, the trait [`SessionStoreConnector`]
 //! expects a simple [*CRUD*](https://en.wikipedia.org/wiki/Create,_read,_update_and_delete)-based
@@ -31,6 +36,10 @@
 //! Once the session is stored, if either the data or expiry is accessed mutably by a future request, it is updated.
 //! Each update generates a new session id to prevent simultaneous updates of the same session from producing unexpected results.
 //! If the session is not updated, then we neither touch the session store, nor do we communicate any session cookie to the client.
+//! The current state of a session can be inspected with [`Session::state_kind`].
+//! To find out why a session was marked as changed, its accesses can be recorded with [`Session::enable_access_audit`].
+//! Sessions can be converted losslessly into a [`SessionSnapshot`] and back, e.g. to hand them over between processes.
+//! With the `serde` feature, snapshots can be serialised.
 //!
 //! ## Session expiry
 //!
@@ -39,9 +48,15 @@
 //! The session's expiry can be updated manually, or automatically with a [`SessionRenewalStrategy`].
 //! In case the session is renewed automatically, the session may be updated by the session store,
 //! even if neither its data nor expiry was accessed mutably.
+//! Different renewal strategies can be applied depending on the session data with a [`SessionRenewalStrategySelector`].
+//! Sessions that are about to expire can be observed with [`SessionStore::set_pre_expiry_hook`],
+//! e.g. to prompt for re-authentication.
+//! Implausible expiries returned by the backend, e.g. from corrupted rows, can be clamped, treated as expired
+//! or reported as errors with [`SessionStore::set_expiry_sanitization`].
 //!
 //! Note that **expired sessions are not deleted** from the session store. This is left to a background
-//! job that needs to be set up independently of this crate. Also, expired cookies are not deleted,
+//! job, e.g. a [`SessionCleaner`] under the feature flag `cleaner`, which periodically calls
+//! [`SessionStoreConnector::delete_expired_sessions`]. Also, expired cookies are not deleted,
 //! it is left to the browser to take care of that.
 //!
 //! ## Manual session removal
@@ -51,6 +66,216 @@
 //! This marks the session for deletion, such that it is deleted from the store when [`SessionStore::store_session`]
 //! is called. The return value of `store_session` is then [`SessionCookieCommand::Delete`],
 //! indicating to the web framework to set the `Set-Cookie` header such that the cookie is deleted.
+//! It carries the [`CookieSettings`] of the session store, such that exactly the cookie that was set gets deleted.
+//! With [`DeletionMode::Tombstone`], deleted sessions are replaced by short-lived tombstones instead,
+//! such that attempts to reuse a deleted session can be detected.
+//! Connectors that can enumerate their sessions allow to delete all sessions matching a predicate in batches,
+//! see [`SessionStore::delete_sessions_where`].
+//!
+//! ## Step-up authentication
+//!
+//! Flows like "re-enter your password for this sensitive action" are supported by raising the
+//! [`AssuranceLevel`] of a session for a limited time with [`Session::raise_assurance`].
+//! The assurance level is stored in the [`SessionMetadata`] and decays automatically when its time is up.
+//!
+//! ## Child sessions
+//!
+//! A session can spawn child sessions with [`Session::spawn_child`], e.g. for limited-scope tokens
+//! such as a token for a single file download. Child sessions are deleted together with their parent.
+//! This requires a backend that implements [`ChildSessionStoreConnector`].
+//!
+//! ## Device linking
+//!
+//! To sign in a second device, e.g. after it scanned a QR code shown by the first one,
+//! [`SessionStore::clone_session_data`] creates a new session with a copy of the data of an existing session.
+//! The new session receives its own cookie and expiry, and is independent of the original session afterwards.
+//!
+//! ## Impersonation
+//!
+//! Administrators can act as another user by using [`ImpersonationSession`] as session data.
+//! It keeps the data of the impersonator within the same session, such that reverting the impersonation is atomic.
+//!
+//! ## Idempotency
+//!
+//! Endpoints like checkouts can be made idempotent per session with [`SessionStore::run_idempotent`].
+//! The results are recorded by a backend that implements [`IdempotencyStoreConnector`],
+//! and deleted together with the session.
+//! Likewise, [`SessionStore::store_session_idempotently`] makes the creation of sessions safe against retries
+//! of the same request, if the backend implements [`IdempotentCreateStoreConnector`].
+//!
+//! ## Transactions
+//!
+//! If several middleware layers or handlers change the session of a request, they can stage their changes
+//! in a [`SessionTransaction`] instead of storing the session each time.
+//! [`SessionStore::commit_transaction`] then writes the session once, such that it is rotated only once.
+//!
+//! ## Locking
+//!
+//! By default, concurrent modifications of a session are detected optimistically, see
+//! [`Error::UpdatedSessionDoesNotExist`].
+//! For critical endpoints, handlers can instead be serialized with [`SessionStore::with_session_locked`],
+//! which requires a backend that implements [`LockingSessionStoreConnector`].
+//!
+//! ## Nonces
+//!
+//! Single-use tokens that are bound to a session, e.g. for email confirmation continuations or download links,
+//! can be issued with [`SessionStore::issue_nonce`] and consumed with [`SessionStore::consume_nonce`].
+//! They are hashed like session ids and deleted together with their session.
+//! This requires a backend that implements [`NonceStoreConnector`].
+//!
+//! ## Attachments
+//!
+//! Occasional large blobs, e.g. an uploaded avatar that is pending confirmation, can be stored alongside a session
+//! with [`SessionStore::put_attachment`], without being loaded on every request.
+//! They are deleted together with their session, and require a backend that implements [`AttachmentStoreConnector`].
+//!
+//! ## Erasure of user data
+//!
+//! Sessions can be associated with a user via [`Session::set_user_id`].
+//! With a backend that implements [`UserIndexedSessionStoreConnector`], all sessions of a user can then be deleted
+//! with [`SessionStore::erase_user_data`].
+//! To log a user out everywhere, e.g. after a password change, use [`SessionStore::invalidate_user_sessions`],
+//! which respects the [`DeletionMode`]. The sessions of a user can be listed with [`SessionStore::user_sessions`].
+//!
+//! ## Sampling
+//!
+//! A fraction of all session writes can be recorded for analysis with [`SessionStore::set_session_sampler`].
+//! The session data of each sample is scrubbed by a user-provided redactor before it leaves the session store.
+//!
+//! ## Events
+//!
+//! To emit audit logs or metrics whenever a session is created, renewed, updated, deleted or found to be expired,
+//! register a [`SessionEventListener`] with [`SessionStore::set_event_listener`].
+//! For cross-cutting concerns on the level of connector operations, like authorisation checks or quota accounting,
+//! an [`Interceptor`] set with [`SessionStore::set_interceptor`] is called before and after each of them.
+//!
+//! ## Priorities
+//!
+//! Sessions can be tagged with a [`SessionPriority`] derived from their data with [`SessionStore::set_priority_classifier`].
+//! Bounded connectors like a [`MemoryStore`] with a [capacity](MemoryStore::set_capacity) evict sessions of low priority
+//! first, e.g. anonymous sessions before authenticated ones.
+//! With any connector that can count its sessions, [`SessionStore::set_maximum_sessions`] instead refuses to create
+//! sessions beyond a hard cap with [`Error::StoreAtCapacity`].
+//!
+//! ## Anomaly scoring
+//!
+//! Risk engines can inspect the signals of each session load, e.g. the age of the session or a channel binding mismatch,
+//! and decide to allow, challenge or revoke the session, see [`SessionStore::set_anomaly_scorer`].
+//!
+//! ## Read-only mode
+//!
+//! Any connector can be wrapped into a [`ReadOnlyStore`], which forwards reads but suppresses writes.
+//! This is useful e.g. for traffic replay, or to verify a migration target before cutting over to it.
+//! For individual requests, e.g. to health checks or static pages, [`SessionStore::load_session_read_only`]
+//! loads a [`SessionRef`] that is never renewed or written.
+//!
+//! ## Migrating between backends
+//!
+//! To migrate between two connectors without downtime, wrap them into a [`MirroringStore`].
+//! It writes to both connectors, but reads only from the primary one, and counts divergences of the secondary one.
+//! A [`FallbackStore`] instead passes operations to the secondary connector when the primary one fails,
+//! and can dual-write to keep the secondary connector ready to take over.
+//!
+//! ## Overlays
+//!
+//! An [`OverlayStore`] layers a writable connector over a connector that is only read from.
+//! This allows e.g. a preview environment to share the sessions of production without mutating them.
+//!
+//! ## Caching
+//!
+//! For read-heavy workloads, a [`CachedStore`] keeps recently read sessions in-process in front of any connector,
+//! with a bounded capacity and time-to-live. Writes go through to the connector and invalidate the cached sessions.
+//! In [stale-while-revalidate mode](CachedStore::set_stale_while_revalidate), sessions are served from the cache
+//! for a bounded time after their time-to-live ended, while they are refreshed in the background by a [`Spawner`].
+//! Jobs that process many sessions at once can load them with [`SessionStore::load_sessions`],
+//! which needs only one round-trip for connectors that implement [`SessionStoreConnector::read_sessions`].
+//!
+//! ## Multiple regions
+//!
+//! Globally distributed applications can use a [`RegionRoutedStore`] to route each session to the connector of its
+//! home region via consistent hashing, keeping reads local without giving up the atomicity of updates.
+//!
+//! ## Sharding
+//!
+//! Large deployments can spread their sessions over multiple backend instances with a [`ShardedStore`],
+//! which routes each session by a stable hash of its id, and keeps each update within a single shard.
+//!
+//! ## Retries
+//!
+//! Writes whose generated session id collides with an existing one are retried with a new id.
+//! A [`RetryPolicy`] like [`ExponentialBackoff`] additionally retries writes that failed with errors that the connector
+//! considers [transient](SessionStoreConnector::is_transient_error), see [`SessionStore::set_retry_policy`].
+//! Since this crate does not depend on an async runtime, the delays between retries are awaited with a [`Sleeper`].
+//!
+//! ## Clocks
+//!
+//! By default, expiry times are computed and checked with the clock of the application server.
+//! If the clocks of multiple servers are unreliable, the session store can instead use the clock of the backend,
+//! see [`ClockAuthority::Backend`].
+//!
+//! ## Runtime statistics
+//!
+//! To right-size session expiry times and storage, histograms of session ages and data sizes can be recorded in-process
+//! with [`SessionStore::enable_runtime_statistics`], and retrieved with [`SessionStore::runtime_statistics`].
+//! With a connector that can enumerate its sessions, [`SessionStore::export_session_analytics`] aggregates statistics
+//! about all stored sessions without exposing their ids or data, optionally with differentially private noise.
+//!
+//! Under the feature flag `metrics`, session stores report loads, load misses, creations, updates, deletions,
+//! id collisions and the latency of [`SessionStore::store_session`] through the [`metrics`](https://crates.io/crates/metrics) facade,
+//! e.g. to be exported to Prometheus. The metric names start with `typed_session_`,
+//! and [`describe_session_metrics`] registers their descriptions.
+//!
+//! ## Multiple session data types
+//!
+//! Unrelated concerns, like authentication and user preferences, can be kept in separate session data types
+//! that share one backend, each with its own cookie name, see [`MultiSessionStore`].
+//! Session stores with a [key prefix](SessionStore::set_key_prefix) only delete their own sessions
+//! with [`SessionStore::clear_store`], which allows multiple applications to share a backend.
+//!
+//! ## Request correlation
+//!
+//! Sessions can be loaded and stored on behalf of a [`RequestContext`], which is passed to the connector
+//! and recorded in tracing spans, see [`SessionStore::load_session_with_context`].
+//! Under the feature flag `tracing-spans`, [`SessionStore::load_session`], [`SessionStore::store_session`]
+//! and each connector operation are additionally wrapped in debug-level spans,
+//! which carry the [redacted](SessionId::redacted) session id where available.
+//! A [`BudgetedStore`] caps the number of connector operations per request according to its context.
+//!
+//! ## Channel binding
+//!
+//! For high-security deployments, sessions can be bound to a channel binding value like a TLS exporter
+//! with [`Session::bind_to_channel`]. Loading them over a different channel is handled according to the [`ChannelBindingPolicy`].
+//! To follow clients that roam between networks without rejecting their sessions, bind sessions to the
+//! [`ip_network_binding`] of the client address and use [`ChannelBindingPolicy::Rotate`],
+//! which issues a fresh cookie whenever the network changes.
+//!
+//! ## CSRF protection
+//!
+//! Framework adapters can decide whether a request must be rejected due to a missing or invalid CSRF token
+//! with [`check_csrf`], such that all of them enforce identical rules.
+//!
+//! ## Rate limiting
+//!
+//! Per-user rate limits can be enforced with a [`SessionRateLimiter`], which stores its token bucket in an attachment
+//! of the session, such that no separate datastore is needed and the session id is not rotated on every request.
+//!
+//! ## Schema versions
+//!
+//! To catch incompatible session data across deployments early, a schema version can be stored with each session
+//! with [`SessionStore::set_schema_version`]. Sessions with a different version are handled according to the [`SchemaMismatchPolicy`].
+//!
+//! ## Key rotation
+//!
+//! After changing encryption keys or codecs in the backend, all sessions can be re-written under the new configuration
+//! with [`SessionStore::rewrite_all_sessions`], if the connector implements [`EnumerableSessionStoreConnector`].
+//! Connectors can leave the serialization of session data to a [`SessionDataCodec`], and codec layers can prefix
+//! each record with a [`RecordHeader`], such that records of the previous configuration stay readable until then.
+//!
+//! ## Strengthening cookies
+//!
+//! To switch to a stronger cookie format without invalidating existing sessions, the previous format can be accepted
+//! for a transition window with [`SessionStore::set_legacy_cookie_format`].
+//! Sessions loaded with a legacy cookie receive a cookie of the current format when they are stored.
 //!
 //! ## Security
 //!
@@ -68,6 +293,9 @@
 //! Note that the OWASP® Foundation does not require session ids to be hashed. We anyways use the
 //! fast and secure hash function provided by crate [blake3] for additional security in case the
 //! session store gets compromised.
+//! Backends that already store only opaque references to the actual secrets may turn hashing off
+//! with [`HashingPolicy::Never`], but this passes the plain session cookies to the backend.
+//! The hashing and cookie generation can be verified against embedded test vectors at startup with [`self_check`].
 //!
 //! This crate updates the session id whenever the session data has changed or the session is expired.
 //! The session id update must be supported by the session store backend in a way that does not allow
@@ -75,13 +303,18 @@
 //! a single session.
 //! See [`SessionStoreConnector::update_session`] for more details.
 //!
+//! Tokens that are minted outside of this crate, e.g. by an SSO gateway, should not be written with the connector
+//! directly, but adopted with [`SessionStore::adopt_external_token`], which checks their format and entropy.
+//!
 //! ## Example
 //!
 //! ```
+//! # #[cfg(feature = "memory-store")]
 //! use typed_session::{Session, SessionStore, MemoryStore};
 //!
 //! # use typed_session::{Error, SessionCookieCommand, SessionRenewalStrategy};
 //! # use std::convert::Infallible;
+//! # #[cfg(feature = "memory-store")]
 //! # fn main() -> Result<(), Error<Infallible>> {
 //! use rand::thread_rng;
 //! # async_std::task::block_on(async {
@@ -103,18 +336,50 @@
 //! let SessionCookieCommand::Set { cookie_value, .. } = store.store_session(session, &mut connection)
 //!     .await? else { unreachable!("New sessions without expiry always set the cookie") };
 //! // The set_cookie_command contains the cookie value and the expiry to be sent to the client.
+//! // The cookie value is a `CookieValue`, which is redacted when debug-printed.
 //!
 //! // Retrieve the session using the cookie.
-//! let session = store.load_session(cookie_value, &mut connection).await?.unwrap();
+//! // Cookie values received from the client are wrapped with `CookieValue::new`.
+//! let session = store.load_session(&cookie_value, &mut connection).await?.unwrap();
 //! assert_eq!(*session.data(), 15);
 //! #
 //! # Ok(()) }) }
+//! # #[cfg(not(feature = "memory-store"))]
+//! # fn main() {}
 //! ```
 //!
+//! Code written against earlier versions of this crate, where the session store owned its connection,
+//! can be migrated step by step with an [`OwnedConnectionSessionStore`].
+//!
+//! ## Runtime configuration
+//!
+//! The [`SessionStoreConfig`] of a session store can be changed at runtime through a [`SessionStoreConfigHandle`].
+//! Under the feature flag `watch-config`, the configuration can also be received through a
+//! `tokio::sync::watch` channel, e.g. from a config service, by passing the receiver to
+//! [`SessionStore::new_with_config`].
+//! During incidents or maintenance windows, all session writes can be frozen at runtime
+//! with the emergency [read-only mode](SessionStoreConfig::read_only), while sessions are still loaded.
+//! Under the feature flag `serde`, the policies of a session store can be read from e.g. a YAML or TOML file
+//! as a [`PolicyConfig`] with durations like `"7d"`, and applied with [`SessionStore::apply_policy_config`].
+//! Under the feature flag `testkit`, renewal strategies can be compared before deploying them by replaying
+//! synthetic access patterns with `testkit::simulate_renewal`.
+//! The time used for expiry can be replaced with [`SessionStore::set_clock`], e.g. by the `testkit::MockClock`
+//! to test expiry and renewal deterministically.
+//! Hand-written connectors can be checked for overwriting existing sessions on id collisions
+//! with `testkit::fuzz_id_collisions`.
+//!
 //! ## Debugging
 //!
 //! To aid in debugging, this crate offers a debug backend implementation called [`MemoryStore`]
 //! under the feature flag `memory-store`.
+//! For production, connectors for Redis, PostgreSQL and SQLite are available as `RedisStore`, `PostgresStore`
+//! and `SqliteStore` under the feature flags `redis-store`, `postgres-store` and `sqlite-store`.
+//! Session data can be encrypted at rest by wrapping any connector into an `EncryptedStore`,
+//! available under the feature flag `encrypted-store`.
+//! To tell apart cookies of different environments, they can carry a non-secret tag, see [`TaggedCookieGenerator`].
+//! To drop forged cookies without a database round-trip, cookies can be signed with a secret key, see [`SignedCookieGenerator`].
+//! The effective configuration of a session store and its stack of connectors can be dumped at startup
+//! with [`SessionStore::describe`].
 //!
 //! ## Comparison with crate [async-session](https://crates.io/crates/async-session)
 //!
@@ -133,22 +398,144 @@
     unused_qualifications
 )]
 
+#[cfg(feature = "actix")]
+mod actix_middleware;
+mod budgeted_store;
+mod cached_store;
+mod codec;
+mod csrf;
+#[cfg(feature = "encrypted-store")]
+mod encrypted_store;
 mod error;
+mod fallback_store;
+mod impersonation;
 #[cfg(feature = "memory-store")]
 mod memory_store;
+mod mirroring_store;
+mod network_binding;
+mod overlay_store;
+mod owned_connection_session_store;
+#[cfg(feature = "serde")]
+mod policy_config;
+#[cfg(feature = "postgres-store")]
+mod postgres_store;
+mod rate_limiter;
+mod read_only_store;
+mod record_header;
+#[cfg(feature = "redis-store")]
+mod redis_store;
+mod region_routed_store;
+mod routed_store;
+mod self_check;
 mod session;
+#[cfg(feature = "cleaner")]
+mod session_cleaner;
+#[cfg(feature = "tower")]
+mod session_layer;
 mod session_store;
+mod sharded_store;
+mod sleeper;
+mod spawner;
+#[cfg(feature = "sqlite-store")]
+mod sqlite_store;
+#[cfg(feature = "testkit")]
+pub mod testkit;
 
-pub use error::Error;
+#[cfg(feature = "actix")]
+pub use actix_middleware::{SessionMiddleware, SessionMiddlewareService};
+pub use budgeted_store::BudgetedStore;
+pub use cached_store::CachedStore;
+#[cfg(feature = "bincode-codec")]
+pub use codec::BincodeCodec;
+#[cfg(feature = "json-codec")]
+pub use codec::JsonCodec;
+pub use codec::SessionDataCodec;
+pub use csrf::{check_csrf, is_safe_method, CsrfDecision, CsrfTokenState};
+#[cfg(feature = "encrypted-store")]
+pub use encrypted_store::{EncryptedStore, EncryptedStoreError};
+pub use error::{
+    ConfigNotWritable, DurationOutOfRange, Error, InvalidDuration, InvalidHashingConfiguration,
+    InvalidRenewalStrategy, SelfCheckError, UnknownRegion,
+};
+pub use fallback_store::FallbackStore;
+pub use impersonation::ImpersonationSession;
 #[cfg(feature = "memory-store")]
 pub use memory_store::{
     DefaultLogger, MemoryStore, MemoryStoreOperationLogger, NoLogger, Operation,
 };
-pub use session::{Session, SessionExpiry, SessionId, SessionIdType};
+pub use mirroring_store::MirroringStore;
+pub use network_binding::ip_network_binding;
+pub use overlay_store::OverlayStore;
+pub use owned_connection_session_store::OwnedConnectionSessionStore;
+#[cfg(feature = "serde")]
+pub use policy_config::{
+    parse_duration, DeletionConfig, ExpirySanitizationConfig, PolicyConfig, PolicyConfigError,
+    RenewalConfig,
+};
+#[cfg(feature = "postgres-store")]
+pub use postgres_store::{PostgresStore, PostgresStoreError};
+pub use rate_limiter::{RateLimitDecision, SessionRateLimiter};
+pub use read_only_store::{ReadOnlyMode, ReadOnlyStore};
+pub use record_header::{RecordHeader, RecordHeaderError};
+#[cfg(feature = "redis-store")]
+pub use redis_store::{RedisStore, RedisStoreError};
+pub use region_routed_store::RegionRoutedStore;
+pub use self_check::self_check;
+pub use session::{
+    Assurance, AssuranceLevel, CookieValue, RedactedSessionId, Session, SessionAccess,
+    SessionExpiry, SessionId, SessionIdType, SessionMetadata, SessionPriority, SessionSnapshot,
+    SessionSnapshotState, SessionStateKind,
+};
+#[cfg(feature = "cleaner")]
+pub use session_cleaner::SessionCleaner;
+#[cfg(feature = "tower")]
+pub use session_layer::{SessionLayer, SessionService};
+#[cfg(feature = "metrics")]
+pub use session_store::instrumentation::describe_session_metrics;
 pub use session_store::{
+    analytics::SessionAnalytics,
+    anomaly::{AnomalyAction, AnomalyScorer, AnomalySignals},
+    attachments::AttachmentStoreConnector,
+    child_sessions::{ChildSession, ChildSessionStoreConnector},
+    clock::{Clock, UtcClock},
+    config::{SessionStoreConfig, SessionStoreConfigHandle},
     cookie_generator::{
-        DebugSessionCookieGenerator, DefaultSessionCookieGenerator, SessionCookieGenerator,
+        is_valid_cookie_value, percent_encode_cookie_value, DebugSessionCookieGenerator,
+        DefaultSessionCookieGenerator, SessionCookieGenerator, SignedCookieGenerator,
+        TaggedCookieGenerator,
     },
-    SessionCookieCommand, SessionRenewalStrategy, SessionStore, SessionStoreConnector,
-    WriteSessionResult,
+    cookie_upgrade::LegacyCookieFormat,
+    description::{ConnectorDescription, SessionStoreDescription, SessionStoreFeature},
+    enumeration::{EnumerableSessionStoreConnector, RewriteProgress},
+    events::{SessionEvent, SessionEventListener},
+    expiry_sanitization::ExpirySanitizationPolicy,
+    idempotency::{IdempotencyRecord, IdempotencyStoreConnector},
+    idempotent_create::IdempotentCreateStoreConnector,
+    interceptor::{ConnectorOperation, Interceptor, OperationOutcome},
+    locking::LockingSessionStoreConnector,
+    multi::{MultiSessionStore, MultiSessionStoreBuilder, SessionChannel},
+    nonces::NonceStoreConnector,
+    priority::SessionPriorityClassifier,
+    read_only_load::SessionRef,
+    request_context::RequestContext,
+    retry::{ExponentialBackoff, RetryDecision, RetryPolicy, RetryReason},
+    sampling::{SessionSampleSink, SessionWriteKind, SessionWriteSample},
+    schema::SchemaMismatchPolicy,
+    statistics::{Histogram, RuntimeStatistics},
+    transaction::SessionTransaction,
+    user_index::{ErasureReport, UserIndexedSessionStoreConnector},
+    ChannelBindingPolicy, ClockAuthority, CookieDeletionReason, CookieSettings, DeletionMode,
+    HashingPolicy, SameSite, SessionCookieCommand, SessionRenewalStrategy,
+    SessionRenewalStrategySelector, SessionStore, SessionStoreConnector, WriteSessionResult,
 };
+pub use sharded_store::ShardedStore;
+#[cfg(feature = "async-std-sleeper")]
+pub use sleeper::AsyncStdSleeper;
+pub use sleeper::Sleeper;
+#[cfg(feature = "tokio-sleeper")]
+pub use sleeper::TokioSleeper;
+#[cfg(feature = "wasm-sleeper")]
+pub use sleeper::WasmSleeper;
+pub use spawner::Spawner;
+#[cfg(feature = "sqlite-store")]
+pub use sqlite_store::{SqliteStore, SqliteStoreError};
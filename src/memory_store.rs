@@ -1,7 +1,13 @@
 use crate::session_store::WriteSessionResult;
-use crate::{Error, Session, SessionExpiry, SessionId, SessionStoreConnector};
+use crate::{
+    AttachmentStoreConnector, ChildSessionStoreConnector, ConnectorDescription,
+    EnumerableSessionStoreConnector, Error, IdempotencyRecord, IdempotencyStoreConnector,
+    IdempotentCreateStoreConnector, LockingSessionStoreConnector, NonceStoreConnector, Session,
+    SessionExpiry, SessionId, SessionMetadata, SessionStoreConnector,
+    UserIndexedSessionStoreConnector,
+};
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::fmt::Debug;
@@ -11,7 +17,7 @@ use std::sync::{Arc, Mutex};
 ///
 /// This store stores sessions in memory, without any persistence. It is intended to be used for debugging purposes.
 /// Sessions are deleted only when calling [delete_session](MemoryStore::delete_session)
-/// or when they are expired and [delete_expired_sessions](MemoryStore::delete_expired_sessions) is called.
+/// or when they are expired and [delete_expired_sessions](SessionStoreConnector::delete_expired_sessions) is called.
 ///
 /// This store implements [`SessionStoreConnector`], i.e. it can be passed to the session store
 /// to perform session updates directly on it, instead of through a separate connection type.
@@ -23,15 +29,38 @@ pub struct MemoryStore<SessionData, OperationLogger> {
 #[derive(Debug)]
 struct MemoryStoreData<SessionData, OperationLogger> {
     session_map: HashMap<SessionId, SessionBody<SessionData>>,
+    tombstones: HashMap<SessionId, DateTime<Utc>>,
+    locks: HashMap<SessionId, DateTime<Utc>>,
+    creation_tokens: HashMap<SessionId, SessionId>,
+    backend_time: Option<DateTime<Utc>>,
     operation_logger: OperationLogger,
     maximum_retries_on_id_collision: Option<u32>,
+    capacity: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
 struct SessionBody<SessionData> {
     current_id: SessionId,
     expiry: SessionExpiry,
+    metadata: SessionMetadata,
     data: SessionData,
+    parent_id: Option<SessionId>,
+    child_ids: Vec<SessionId>,
+    nonces: HashMap<SessionId, Nonce>,
+    idempotency_records: HashMap<String, IdempotencyEntry>,
+    attachments: HashMap<String, Vec<u8>>,
+}
+
+#[derive(Debug, Clone)]
+struct IdempotencyEntry {
+    result: Option<Vec<u8>>,
+    expiry: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct Nonce {
+    purpose: String,
+    expiry: DateTime<Utc>,
 }
 
 #[async_trait]
@@ -46,22 +75,29 @@ impl<
         self.store.lock().unwrap().maximum_retries_on_id_collision
     }
 
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::new("MemoryStore")
+    }
+
     async fn create_session(
         &mut self,
         id: &SessionId,
         expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
         data: &SessionData,
     ) -> Result<WriteSessionResult, Error<Self::Error>> {
         let mut store = self.store.lock().unwrap();
         store.operation_logger.log_create_session(id, expiry, data);
 
         // replace with `try_insert` once stable #82766
-        if store.session_map.contains_key(id) {
+        if store.session_map.contains_key(id) || store.tombstones.contains_key(id) {
             Ok(WriteSessionResult::SessionIdExists)
         } else {
-            store
-                .session_map
-                .insert(id.clone(), SessionBody::new_cloned(id, expiry, data));
+            store.make_room(None);
+            store.session_map.insert(
+                id.clone(),
+                SessionBody::new_cloned(id, expiry, metadata, data),
+            );
             Ok(WriteSessionResult::Ok(()))
         }
     }
@@ -73,10 +109,14 @@ impl<
         let store = self.store.lock().unwrap();
         store.operation_logger.log_read_session(&id);
 
-        Ok(store
-            .session_map
-            .get(&id)
-            .map(|body| Session::new_from_session_store(id, body.expiry, body.data.clone())))
+        Ok(store.session_map.get(&id).map(|body| {
+            Session::new_from_session_store(
+                id,
+                body.expiry,
+                body.metadata.clone(),
+                body.data.clone(),
+            )
+        }))
     }
 
     async fn update_session(
@@ -84,6 +124,7 @@ impl<
         current_id: &SessionId,
         previous_id: &SessionId,
         expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
         data: &SessionData,
     ) -> Result<WriteSessionResult, Error<Self::Error>> {
         let mut store = self.store.lock().unwrap();
@@ -91,14 +132,9 @@ impl<
             .operation_logger
             .log_update_session(current_id, previous_id, expiry, data);
 
-        if store.session_map.contains_key(current_id) {
+        if store.session_map.contains_key(current_id) || store.tombstones.contains_key(current_id) {
             Ok(WriteSessionResult::SessionIdExists)
-        } else if let Some(mut session_body) = store.session_map.remove(previous_id) {
-            session_body.current_id = current_id.clone();
-            session_body.expiry = *expiry;
-            session_body.data = data.clone();
-
-            store.session_map.insert(current_id.clone(), session_body);
+        } else if store.move_session(current_id, previous_id, expiry, metadata, data) {
             Ok(WriteSessionResult::Ok(()))
         } else {
             Err(Error::UpdatedSessionDoesNotExist)
@@ -109,19 +145,561 @@ impl<
         let mut store = self.store.lock().unwrap();
         store.operation_logger.log_delete_session(id);
 
-        store.session_map.remove(id);
+        store.remove_session(id);
+        Ok(())
+    }
+
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        store.operation_logger.log_delete_sessions(ids);
+
+        for id in ids {
+            store.remove_session(id);
+        }
         Ok(())
     }
 
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        store.operation_logger.log_create_tombstone(id, &expiry);
+
+        store.remove_session(id);
+        store.tombstones.insert(id.clone(), expiry);
+        Ok(())
+    }
+
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .tombstones
+            .get(id)
+            .map(|expiry| *expiry > now)
+            .unwrap_or(false))
+    }
+
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .session_map
+            .get(id)
+            .map(|body| !body.expiry.is_expired(now))
+            .unwrap_or(false))
+    }
+
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        Ok(self.store.lock().unwrap().backend_time)
+    }
+
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        Ok(self.store.lock().unwrap().session_map.len())
+    }
+
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        Ok(self.store.lock().unwrap().delete_expired_sessions(before))
+    }
+
     async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
         let mut store = self.store.lock().unwrap();
         store.operation_logger.log_clear();
         store.session_map.clear();
+        store.tombstones.clear();
+        store.creation_tokens.clear();
+        Ok(())
+    }
+
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        let MemoryStoreData {
+            session_map,
+            locks,
+            operation_logger,
+            ..
+        } = &mut *store;
+        operation_logger.log_clear_namespace(namespace);
+        session_map.retain(|_, body| body.metadata.namespace.as_deref() != Some(namespace));
+        locks.retain(|id, _| session_map.contains_key(id));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync + Clone,
+        OperationLogger: Send + Sync + MemoryStoreOperationLogger<SessionData>,
+    > ChildSessionStoreConnector<SessionData> for MemoryStore<SessionData, OperationLogger>
+{
+    async fn create_child_session(
+        &mut self,
+        parent_id: &SessionId,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        store
+            .operation_logger
+            .log_create_child_session(parent_id, current_id, expiry, data);
+
+        if store.session_map.contains_key(current_id) || store.tombstones.contains_key(current_id) {
+            Ok(WriteSessionResult::SessionIdExists)
+        } else if store.session_map.contains_key(parent_id) {
+            store.make_room(Some(parent_id));
+            let parent = store.session_map.get_mut(parent_id).unwrap();
+            parent.child_ids.push(current_id.clone());
+            let mut session_body = SessionBody::new_cloned(current_id, expiry, metadata, data);
+            session_body.parent_id = Some(parent_id.clone());
+            store.session_map.insert(current_id.clone(), session_body);
+            Ok(WriteSessionResult::Ok(()))
+        } else {
+            Err(Error::ParentSessionDoesNotExist)
+        }
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync + Clone,
+        OperationLogger: Send + Sync + MemoryStoreOperationLogger<SessionData>,
+    > LockingSessionStoreConnector<SessionData> for MemoryStore<SessionData, OperationLogger>
+{
+    async fn lock_session(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+        expiry: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        if matches!(store.locks.get(id), Some(lock_expiry) if *lock_expiry > now) {
+            Ok(false)
+        } else {
+            store.locks.insert(id.clone(), expiry);
+            Ok(true)
+        }
+    }
+
+    async fn unlock_session(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        if store.locks.get(id) == Some(&expiry) {
+            store.locks.remove(id);
+        }
         Ok(())
     }
 }
 
+#[async_trait]
+impl<
+        SessionData: Send + Sync + Clone,
+        OperationLogger: Send + Sync + MemoryStoreOperationLogger<SessionData>,
+    > IdempotencyStoreConnector<SessionData> for MemoryStore<SessionData, OperationLogger>
+{
+    async fn begin_idempotent_request(
+        &mut self,
+        session_id: &SessionId,
+        key: &str,
+        now: DateTime<Utc>,
+        expiry: DateTime<Utc>,
+    ) -> Result<IdempotencyRecord, Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        let session_body = store
+            .session_map
+            .get_mut(session_id)
+            .ok_or(Error::UpdatedSessionDoesNotExist)?;
+
+        match session_body.idempotency_records.get(key) {
+            Some(entry) if entry.expiry > now => Ok(match &entry.result {
+                Some(result) => IdempotencyRecord::Completed(result.clone()),
+                None => IdempotencyRecord::InProgress,
+            }),
+            _ => {
+                session_body.idempotency_records.insert(
+                    key.to_string(),
+                    IdempotencyEntry {
+                        result: None,
+                        expiry,
+                    },
+                );
+                Ok(IdempotencyRecord::New)
+            }
+        }
+    }
+
+    async fn complete_idempotent_request(
+        &mut self,
+        session_id: &SessionId,
+        key: &str,
+        result: &[u8],
+    ) -> Result<(), Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        if let Some(entry) = store
+            .session_map
+            .get_mut(session_id)
+            .and_then(|session_body| session_body.idempotency_records.get_mut(key))
+        {
+            entry.result = Some(result.to_vec());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync + Clone,
+        OperationLogger: Send + Sync + MemoryStoreOperationLogger<SessionData>,
+    > NonceStoreConnector<SessionData> for MemoryStore<SessionData, OperationLogger>
+{
+    async fn create_nonce(
+        &mut self,
+        session_id: &SessionId,
+        nonce_id: &SessionId,
+        purpose: &str,
+        expiry: DateTime<Utc>,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        let session_body = store
+            .session_map
+            .get_mut(session_id)
+            .ok_or(Error::UpdatedSessionDoesNotExist)?;
+
+        if session_body.nonces.contains_key(nonce_id) {
+            Ok(WriteSessionResult::SessionIdExists)
+        } else {
+            session_body.nonces.insert(
+                nonce_id.clone(),
+                Nonce {
+                    purpose: purpose.to_string(),
+                    expiry,
+                },
+            );
+            Ok(WriteSessionResult::Ok(()))
+        }
+    }
+
+    async fn consume_nonce(
+        &mut self,
+        session_id: &SessionId,
+        nonce_id: &SessionId,
+        purpose: &str,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        let Some(session_body) = store.session_map.get_mut(session_id) else {
+            return Ok(false);
+        };
+
+        match session_body.nonces.get(nonce_id) {
+            Some(nonce) if nonce.purpose == purpose => {
+                let nonce = session_body.nonces.remove(nonce_id).unwrap();
+                Ok(nonce.expiry > now)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync + Clone,
+        OperationLogger: Send + Sync + MemoryStoreOperationLogger<SessionData>,
+    > AttachmentStoreConnector<SessionData> for MemoryStore<SessionData, OperationLogger>
+{
+    async fn put_attachment(
+        &mut self,
+        session_id: &SessionId,
+        key: &str,
+        bytes: &[u8],
+    ) -> Result<(), Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        let session_body = store
+            .session_map
+            .get_mut(session_id)
+            .ok_or(Error::UpdatedSessionDoesNotExist)?;
+        session_body
+            .attachments
+            .insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+
+    async fn get_attachment(
+        &mut self,
+        session_id: &SessionId,
+        key: &str,
+    ) -> Result<Option<Vec<u8>>, Error<Self::Error>> {
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .session_map
+            .get(session_id)
+            .and_then(|session_body| session_body.attachments.get(key).cloned()))
+    }
+
+    async fn delete_attachment(
+        &mut self,
+        session_id: &SessionId,
+        key: &str,
+    ) -> Result<bool, Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        Ok(store
+            .session_map
+            .get_mut(session_id)
+            .is_some_and(|session_body| session_body.attachments.remove(key).is_some()))
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync + Clone,
+        OperationLogger: Send + Sync + MemoryStoreOperationLogger<SessionData>,
+    > EnumerableSessionStoreConnector<SessionData> for MemoryStore<SessionData, OperationLogger>
+{
+    async fn list_session_ids(
+        &mut self,
+        after: Option<&SessionId>,
+        limit: usize,
+    ) -> Result<Vec<SessionId>, Error<Self::Error>> {
+        let store = self.store.lock().unwrap();
+        let mut ids: Vec<_> = store
+            .session_map
+            .keys()
+            .filter(|id| after.is_none_or(|after| *id > after))
+            .cloned()
+            .collect();
+        ids.sort_unstable();
+        ids.truncate(limit);
+        Ok(ids)
+    }
+
+    async fn rewrite_session(
+        &mut self,
+        id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<bool, Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        store.operation_logger.log_rewrite_session(id, expiry, data);
+
+        if let Some(session_body) = store.session_map.get_mut(id) {
+            session_body.expiry = *expiry;
+            session_body.metadata = metadata.clone();
+            session_body.data = data.clone();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync + Clone,
+        OperationLogger: Send + Sync + MemoryStoreOperationLogger<SessionData>,
+    > UserIndexedSessionStoreConnector<SessionData> for MemoryStore<SessionData, OperationLogger>
+{
+    async fn delete_sessions_of_user(
+        &mut self,
+        user_id: &str,
+    ) -> Result<usize, Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        store.operation_logger.log_delete_sessions_of_user(user_id);
+
+        let ids: Vec<_> = store
+            .session_map
+            .iter()
+            .filter(|(_, body)| body.metadata.user_id.as_deref() == Some(user_id))
+            .map(|(id, _)| id.clone())
+            .collect();
+        let previous_len = store.session_map.len();
+        for id in &ids {
+            store.remove_session(id);
+        }
+        Ok(previous_len - store.session_map.len())
+    }
+
+    async fn read_sessions_of_user(
+        &mut self,
+        user_id: &str,
+    ) -> Result<Vec<Session<SessionData>>, Error<Self::Error>> {
+        let store = self.store.lock().unwrap();
+        store.operation_logger.log_read_sessions_of_user(user_id);
+
+        Ok(store
+            .session_map
+            .iter()
+            .filter(|(_, body)| body.metadata.user_id.as_deref() == Some(user_id))
+            .map(|(id, body)| {
+                Session::new_from_session_store(
+                    id.clone(),
+                    body.expiry,
+                    body.metadata.clone(),
+                    body.data.clone(),
+                )
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync + Clone,
+        OperationLogger: Send + Sync + MemoryStoreOperationLogger<SessionData>,
+    > IdempotentCreateStoreConnector<SessionData> for MemoryStore<SessionData, OperationLogger>
+{
+    async fn create_session_with_token(
+        &mut self,
+        token_id: &SessionId,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let mut store = self.store.lock().unwrap();
+        store
+            .operation_logger
+            .log_create_session_with_token(token_id, current_id, expiry, data);
+
+        if store.session_map.contains_key(current_id) || store.tombstones.contains_key(current_id) {
+            return Ok(WriteSessionResult::SessionIdExists);
+        }
+
+        // A token may still point to a session that was since deleted or updated, which then does not count.
+        let previous_id = store.creation_tokens.get(token_id).cloned();
+        if !previous_id.is_some_and(|previous_id| {
+            store.move_session(current_id, &previous_id, expiry, metadata, data)
+        }) {
+            store.make_room(None);
+            store.session_map.insert(
+                current_id.clone(),
+                SessionBody::new_cloned(current_id, expiry, metadata, data),
+            );
+        }
+        store
+            .creation_tokens
+            .insert(token_id.clone(), current_id.clone());
+        Ok(WriteSessionResult::Ok(()))
+    }
+}
+
+impl<SessionData: Clone, OperationLogger> MemoryStoreData<SessionData, OperationLogger> {
+    /// Moves the session with id `previous_id` to `current_id` and overwrites its contents.
+    /// Returns false if there is no session with id `previous_id`.
+    fn move_session(
+        &mut self,
+        current_id: &SessionId,
+        previous_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> bool {
+        let Some(mut session_body) = self.session_map.remove(previous_id) else {
+            return false;
+        };
+        session_body.current_id = current_id.clone();
+        session_body.expiry = *expiry;
+        session_body.metadata = metadata.clone();
+        session_body.data = data.clone();
+
+        if let Some(parent_id) = &session_body.parent_id {
+            let parent = self.session_map.get_mut(parent_id).unwrap();
+            for child_id in &mut parent.child_ids {
+                if child_id == previous_id {
+                    *child_id = current_id.clone();
+                }
+            }
+        }
+        for child_id in &session_body.child_ids {
+            self.session_map.get_mut(child_id).unwrap().parent_id = Some(current_id.clone());
+        }
+
+        self.session_map.insert(current_id.clone(), session_body);
+        true
+    }
+}
+
+impl<SessionData, OperationLogger> MemoryStoreData<SessionData, OperationLogger> {
+    /// Deletes all sessions and tombstones that expired before `before`, and returns the number of deleted sessions.
+    fn delete_expired_sessions(&mut self, before: DateTime<Utc>) -> u64 {
+        tracing::trace!("Cleaning up memory store...");
+        let initial_len = self.session_map.len();
+        self.session_map.retain(|_, body| match body.expiry {
+            SessionExpiry::DateTime(expiry) => expiry > before,
+            SessionExpiry::Never => true,
+        });
+        self.tombstones.retain(|_, expiry| *expiry > before);
+        self.locks.retain(|_, expiry| *expiry > before);
+        let session_map = &self.session_map;
+        self.creation_tokens
+            .retain(|_, id| session_map.contains_key(id));
+        let deleted = (initial_len - self.session_map.len()) as u64;
+        tracing::trace!("Deleted {deleted} expired sessions");
+        deleted
+    }
+
+    /// Evicts sessions until one more session fits into the capacity, sparing the session with id `keep`.
+    ///
+    /// Sessions with the lowest [priority](SessionMetadata::priority) are evicted first,
+    /// and among those the sessions that expire first.
+    /// Sessions with child sessions are never evicted, since this would also delete their children.
+    fn make_room(&mut self, keep: Option<&SessionId>) {
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+        while self.session_map.len() >= capacity {
+            let Some(id) = self
+                .session_map
+                .iter()
+                .filter(|(id, body)| body.child_ids.is_empty() && Some(*id) != keep)
+                .min_by_key(|(_, body)| (body.metadata.priority, body.expiry))
+                .map(|(id, _)| id.clone())
+            else {
+                return;
+            };
+            tracing::trace!("Evicting session to stay within the capacity of {capacity}");
+            self.remove_session(&id);
+        }
+    }
+
+    /// Removes the session with the given id, unlinks it from its parent and removes all its children.
+    fn remove_session(&mut self, id: &SessionId) {
+        if let Some(session_body) = self.session_map.remove(id) {
+            if let Some(parent) = session_body
+                .parent_id
+                .and_then(|parent_id| self.session_map.get_mut(&parent_id))
+            {
+                parent.child_ids.retain(|child_id| child_id != id);
+            }
+            for child_id in session_body.child_ids {
+                self.remove_session(&child_id);
+            }
+        }
+    }
+}
+
 impl<SessionData, OperationLogger> MemoryStore<SessionData, OperationLogger> {
+    /// Sets the time reported by [`SessionStoreConnector::now`], to simulate a backend with an authoritative clock.
+    /// If `None`, no time is reported, which is the default.
+    pub fn set_backend_time(&self, backend_time: Option<DateTime<Utc>>) {
+        self.store.lock().unwrap().backend_time = backend_time;
+    }
+
     /// Sets the maximum retries on id collision, see [SessionStoreConnector::maximum_retries_on_id_collision] for details.
     pub fn set_maximum_retries_on_id_collision(
         &mut self,
@@ -131,6 +709,22 @@ impl<SessionData, OperationLogger> MemoryStore<SessionData, OperationLogger> {
             maximum_retries_on_id_collision;
     }
 
+    /// Returns the maximum number of sessions in the memory store, see [`set_capacity`](MemoryStore::set_capacity).
+    pub fn capacity(&self) -> Option<usize> {
+        self.store.lock().unwrap().capacity
+    }
+
+    /// Sets the maximum number of sessions in the memory store.
+    /// If `None`, the number of sessions is unbounded, which is the default.
+    ///
+    /// When a new session would exceed the capacity, sessions are evicted to make room for it.
+    /// Sessions with the lowest [priority](SessionMetadata::priority) are evicted first, where sessions without
+    /// priority count as lower than all others, and among those the sessions that expire first.
+    /// Sessions with child sessions are never evicted.
+    pub fn set_capacity(&mut self, capacity: Option<usize>) {
+        self.store.lock().unwrap().capacity = capacity;
+    }
+
     /// Returns the number of elements in the memory store.
     pub fn len(&self) -> usize {
         self.store.lock().unwrap().session_map.len()
@@ -141,23 +735,6 @@ impl<SessionData, OperationLogger> MemoryStore<SessionData, OperationLogger> {
         self.store.lock().unwrap().session_map.is_empty()
     }
 
-    /// Deletes all expired sessions.
-    pub fn delete_expired_sessions(&mut self) -> Result<(), Error<()>> {
-        let mut store = self.store.lock().unwrap();
-        tracing::trace!("Cleaning up memory store...");
-        let now = Utc::now();
-        let initial_len = store.session_map.len();
-        store.session_map.retain(|_, body| match body.expiry {
-            SessionExpiry::DateTime(expiry) => expiry > now,
-            SessionExpiry::Never => true,
-        });
-        tracing::trace!(
-            "Deleted {} expired sessions",
-            initial_len - store.session_map.len()
-        );
-        Ok(())
-    }
-
     /// Consumes the store and returns the logged operations.
     pub fn into_logger(self) -> OperationLogger
     where
@@ -181,7 +758,12 @@ impl<SessionData: Clone, OperationLogger> MemoryStore<SessionData, OperationLogg
             .session_map
             .iter()
             .map(|(id, body)| {
-                Session::new_from_session_store(id.clone(), body.expiry, body.data.clone())
+                Session::new_from_session_store(
+                    id.clone(),
+                    body.expiry,
+                    body.metadata.clone(),
+                    body.data.clone(),
+                )
             })
             .for_each(f);
     }
@@ -192,8 +774,13 @@ impl<SessionData> MemoryStore<SessionData, NoLogger> {
     pub fn new() -> Self {
         MemoryStoreData {
             session_map: Default::default(),
+            tombstones: Default::default(),
+            locks: Default::default(),
+            creation_tokens: Default::default(),
+            backend_time: None,
             operation_logger: NoLogger,
             maximum_retries_on_id_collision: None,
+            capacity: None,
         }
         .into()
     }
@@ -204,19 +791,35 @@ impl<SessionData> MemoryStore<SessionData, DefaultLogger<SessionData>> {
     pub fn new_with_logger() -> Self {
         MemoryStoreData {
             session_map: Default::default(),
+            tombstones: Default::default(),
+            locks: Default::default(),
+            creation_tokens: Default::default(),
+            backend_time: None,
             operation_logger: Default::default(),
             maximum_retries_on_id_collision: None,
+            capacity: None,
         }
         .into()
     }
 }
 
 impl<SessionData: Clone> SessionBody<SessionData> {
-    fn new_cloned(current_id: &SessionId, expiry: &SessionExpiry, data: &SessionData) -> Self {
+    fn new_cloned(
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Self {
         Self {
             current_id: current_id.clone(),
             expiry: *expiry,
+            metadata: metadata.clone(),
             data: data.clone(),
+            parent_id: None,
+            child_ids: Vec::new(),
+            nonces: HashMap::new(),
+            idempotency_records: HashMap::new(),
+            attachments: HashMap::new(),
         }
     }
 }
@@ -225,8 +828,13 @@ impl<SessionData, OperationLogger: Default> Default for MemoryStore<SessionData,
     fn default() -> Self {
         MemoryStoreData {
             session_map: Default::default(),
+            tombstones: Default::default(),
+            locks: Default::default(),
+            creation_tokens: Default::default(),
+            backend_time: None,
             operation_logger: Default::default(),
             maximum_retries_on_id_collision: None,
+            capacity: None,
         }
         .into()
     }
@@ -238,6 +846,32 @@ pub trait MemoryStoreOperationLogger<SessionData> {
     /// Log a create session operation.
     fn log_create_session(&mut self, id: &SessionId, expiry: &SessionExpiry, data: &SessionData);
 
+    /// Log a create session with token operation.
+    ///
+    /// The default implementation does nothing.
+    fn log_create_session_with_token(
+        &mut self,
+        _token_id: &SessionId,
+        _id: &SessionId,
+        _expiry: &SessionExpiry,
+        _data: &SessionData,
+    ) {
+        // do nothing
+    }
+
+    /// Log a create child session operation.
+    ///
+    /// The default implementation does nothing.
+    fn log_create_child_session(
+        &mut self,
+        _parent_id: &SessionId,
+        _id: &SessionId,
+        _expiry: &SessionExpiry,
+        _data: &SessionData,
+    ) {
+        // do nothing
+    }
+
     /// Log a read session operation.
     fn log_read_session(&self, id: &SessionId);
 
@@ -250,11 +884,58 @@ pub trait MemoryStoreOperationLogger<SessionData> {
         data: &SessionData,
     );
 
+    /// Log a rewrite session operation.
+    ///
+    /// The default implementation does nothing.
+    fn log_rewrite_session(
+        &mut self,
+        _id: &SessionId,
+        _expiry: &SessionExpiry,
+        _data: &SessionData,
+    ) {
+        // do nothing
+    }
+
     /// Log a delete session operation.
     fn log_delete_session(&mut self, current_id: &SessionId);
 
+    /// Log a delete sessions operation.
+    ///
+    /// The default implementation does nothing.
+    fn log_delete_sessions(&mut self, _ids: &[SessionId]) {
+        // do nothing
+    }
+
+    /// Log a create tombstone operation.
+    ///
+    /// The default implementation does nothing.
+    fn log_create_tombstone(&mut self, _current_id: &SessionId, _expiry: &DateTime<Utc>) {
+        // do nothing
+    }
+
+    /// Log a delete sessions of user operation.
+    ///
+    /// The default implementation does nothing.
+    fn log_delete_sessions_of_user(&mut self, _user_id: &str) {
+        // do nothing
+    }
+
+    /// Log a read sessions of user operation.
+    ///
+    /// The default implementation does nothing.
+    fn log_read_sessions_of_user(&self, _user_id: &str) {
+        // do nothing
+    }
+
     /// Log a clear operation.
     fn log_clear(&mut self);
+
+    /// Log a clear namespace operation.
+    ///
+    /// The default implementation does nothing.
+    fn log_clear_namespace(&mut self, _namespace: &str) {
+        // do nothing
+    }
 }
 
 /// A logger that ignores all logging operations.
@@ -309,6 +990,18 @@ pub enum Operation<SessionData> {
         expiry: SessionExpiry,
         data: SessionData,
     },
+    CreateSessionWithToken {
+        token_id: SessionId,
+        id: SessionId,
+        expiry: SessionExpiry,
+        data: SessionData,
+    },
+    CreateChildSession {
+        parent_id: SessionId,
+        id: SessionId,
+        expiry: SessionExpiry,
+        data: SessionData,
+    },
     ReadSession {
         id: SessionId,
     },
@@ -318,10 +1011,31 @@ pub enum Operation<SessionData> {
         expiry: SessionExpiry,
         data: SessionData,
     },
+    RewriteSession {
+        id: SessionId,
+        expiry: SessionExpiry,
+        data: SessionData,
+    },
     DeleteSession {
         current_id: SessionId,
     },
+    DeleteSessions {
+        ids: Vec<SessionId>,
+    },
+    CreateTombstone {
+        current_id: SessionId,
+        expiry: DateTime<Utc>,
+    },
+    DeleteSessionsOfUser {
+        user_id: String,
+    },
+    ReadSessionsOfUser {
+        user_id: String,
+    },
     Clear,
+    ClearNamespace {
+        namespace: String,
+    },
 }
 
 impl<SessionData: Clone> MemoryStoreOperationLogger<SessionData> for DefaultLogger<SessionData> {
@@ -333,6 +1047,42 @@ impl<SessionData: Clone> MemoryStoreOperationLogger<SessionData> for DefaultLogg
         });
     }
 
+    fn log_create_session_with_token(
+        &mut self,
+        token_id: &SessionId,
+        id: &SessionId,
+        expiry: &SessionExpiry,
+        data: &SessionData,
+    ) {
+        self.log
+            .lock()
+            .unwrap()
+            .push(Operation::CreateSessionWithToken {
+                token_id: token_id.clone(),
+                id: id.clone(),
+                expiry: *expiry,
+                data: data.clone(),
+            });
+    }
+
+    fn log_create_child_session(
+        &mut self,
+        parent_id: &SessionId,
+        id: &SessionId,
+        expiry: &SessionExpiry,
+        data: &SessionData,
+    ) {
+        self.log
+            .lock()
+            .unwrap()
+            .push(Operation::CreateChildSession {
+                parent_id: parent_id.clone(),
+                id: id.clone(),
+                expiry: *expiry,
+                data: data.clone(),
+            });
+    }
+
     fn log_read_session(&self, id: &SessionId) {
         self.log
             .lock()
@@ -355,15 +1105,61 @@ impl<SessionData: Clone> MemoryStoreOperationLogger<SessionData> for DefaultLogg
         });
     }
 
+    fn log_rewrite_session(&mut self, id: &SessionId, expiry: &SessionExpiry, data: &SessionData) {
+        self.log.lock().unwrap().push(Operation::RewriteSession {
+            id: id.clone(),
+            expiry: *expiry,
+            data: data.clone(),
+        });
+    }
+
     fn log_delete_session(&mut self, current_id: &SessionId) {
         self.log.lock().unwrap().push(Operation::DeleteSession {
             current_id: current_id.clone(),
         });
     }
 
+    fn log_delete_sessions(&mut self, ids: &[SessionId]) {
+        self.log
+            .lock()
+            .unwrap()
+            .push(Operation::DeleteSessions { ids: ids.to_vec() });
+    }
+
+    fn log_create_tombstone(&mut self, current_id: &SessionId, expiry: &DateTime<Utc>) {
+        self.log.lock().unwrap().push(Operation::CreateTombstone {
+            current_id: current_id.clone(),
+            expiry: *expiry,
+        });
+    }
+
+    fn log_delete_sessions_of_user(&mut self, user_id: &str) {
+        self.log
+            .lock()
+            .unwrap()
+            .push(Operation::DeleteSessionsOfUser {
+                user_id: user_id.to_string(),
+            });
+    }
+
+    fn log_read_sessions_of_user(&self, user_id: &str) {
+        self.log
+            .lock()
+            .unwrap()
+            .push(Operation::ReadSessionsOfUser {
+                user_id: user_id.to_string(),
+            });
+    }
+
     fn log_clear(&mut self) {
         self.log.lock().unwrap().push(Operation::Clear);
     }
+
+    fn log_clear_namespace(&mut self, namespace: &str) {
+        self.log.lock().unwrap().push(Operation::ClearNamespace {
+            namespace: namespace.to_string(),
+        });
+    }
 }
 
 impl<SessionData> DefaultLogger<SessionData> {
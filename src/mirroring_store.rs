@@ -0,0 +1,300 @@
+use crate::session::SessionState;
+use crate::{
+    ConnectorDescription, Error, RequestContext, Session, SessionExpiry, SessionId,
+    SessionMetadata, SessionStoreConnector, WriteSessionResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::warn;
+
+/// A session store connector that writes to two connectors, but reads only from the primary one.
+///
+/// This allows to migrate between connectors without downtime: first, mirror all writes into the new connector
+/// as secondary, and once all sessions that existed before the mirroring started have expired,
+/// switch to using the new connector alone.
+///
+/// The primary connector is the source of truth.
+/// Errors and inconsistencies of the secondary connector are logged and counted as divergences,
+/// but do not affect the result of an operation.
+/// Updates of sessions that are missing in the secondary connector create them there instead.
+///
+/// Clones share their divergence counter.
+#[derive(Debug, Clone)]
+pub struct MirroringStore<Primary, Secondary> {
+    primary: Primary,
+    secondary: Secondary,
+    verify_reads: bool,
+    divergences: Arc<AtomicU64>,
+}
+
+impl<Primary, Secondary> MirroringStore<Primary, Secondary> {
+    /// Mirror all writes of `primary` into `secondary`.
+    pub fn new(primary: Primary, secondary: Secondary) -> Self {
+        Self {
+            primary,
+            secondary,
+            verify_reads: false,
+            divergences: Default::default(),
+        }
+    }
+
+    /// If set, each read from the primary connector is verified against the secondary connector.
+    /// A read counts as diverged if the session exists in only one of the connectors,
+    /// or if the expiry or metadata differ.
+    ///
+    /// This doubles the number of reads.
+    pub fn set_verify_reads(&mut self, verify_reads: bool) {
+        self.verify_reads = verify_reads;
+    }
+
+    /// Returns the primary connector.
+    pub fn primary(&self) -> &Primary {
+        &self.primary
+    }
+
+    /// Returns the secondary connector.
+    pub fn secondary(&self) -> &Secondary {
+        &self.secondary
+    }
+
+    /// Returns the number of operations where the secondary connector diverged from the primary connector so far.
+    pub fn divergences(&self) -> u64 {
+        self.divergences.load(Ordering::Relaxed)
+    }
+
+    fn record_divergence(&self, operation: &str, reason: impl Debug) {
+        warn!("Secondary session store diverged on {operation}: {reason:?}");
+        self.divergences.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn check_secondary_write<SecondaryError: Debug>(
+        &self,
+        operation: &str,
+        result: Result<WriteSessionResult, Error<SecondaryError>>,
+    ) {
+        match result {
+            Ok(WriteSessionResult::Ok(())) => {}
+            Ok(WriteSessionResult::SessionIdExists) => {
+                self.record_divergence(operation, "session id exists")
+            }
+            Err(error) => self.record_divergence(operation, error),
+        }
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync,
+        Primary: SessionStoreConnector<SessionData> + Send,
+        Secondary: SessionStoreConnector<SessionData> + Send,
+    > SessionStoreConnector<SessionData> for MirroringStore<Primary, Secondary>
+{
+    type Error = Primary::Error;
+
+    fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        self.primary.maximum_retries_on_id_collision()
+    }
+
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        self.primary.is_transient_error(error)
+    }
+
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::wrapping(
+            "MirroringStore",
+            [self.primary.describe(), self.secondary.describe()],
+        )
+    }
+
+    fn enforces_expiry(&self) -> bool {
+        self.primary.enforces_expiry()
+    }
+
+    /// Returns true if both connectors accept the id, since writes are mirrored to the secondary connector.
+    fn accepts_session_id(&self, new_id: &SessionId, previous_id: Option<&SessionId>) -> bool {
+        self.primary.accepts_session_id(new_id, previous_id)
+            && self.secondary.accepts_session_id(new_id, previous_id)
+    }
+
+    async fn create_session(
+        &mut self,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let result = self
+            .primary
+            .create_session(current_id, expiry, metadata, data)
+            .await?;
+        if matches!(result, WriteSessionResult::Ok(())) {
+            let secondary_result = self
+                .secondary
+                .create_session(current_id, expiry, metadata, data)
+                .await;
+            self.check_secondary_write("create session", secondary_result);
+        }
+        Ok(result)
+    }
+
+    async fn read_session(
+        &mut self,
+        id: SessionId,
+    ) -> Result<Option<Session<SessionData>>, Error<Self::Error>> {
+        let session = self.primary.read_session(id.clone()).await?;
+        if self.verify_reads {
+            match self.secondary.read_session(id).await {
+                Ok(secondary_session) => {
+                    let diverged = match (&session, &secondary_session) {
+                        (None, None) => false,
+                        (Some(session), Some(secondary_session)) => {
+                            stored_expiry(session) != stored_expiry(secondary_session)
+                                || session.metadata() != secondary_session.metadata()
+                        }
+                        _ => true,
+                    };
+                    if diverged {
+                        self.record_divergence("read session", "sessions differ");
+                    }
+                }
+                Err(error) => self.record_divergence("read session", error),
+            }
+        }
+        Ok(session)
+    }
+
+    async fn update_session(
+        &mut self,
+        current_id: &SessionId,
+        previous_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let result = self
+            .primary
+            .update_session(current_id, previous_id, expiry, metadata, data)
+            .await?;
+        if matches!(result, WriteSessionResult::Ok(())) {
+            let needs_backfill = match self
+                .secondary
+                .update_session(current_id, previous_id, expiry, metadata, data)
+                .await
+            {
+                Err(Error::UpdatedSessionDoesNotExist) => true,
+                secondary_result => {
+                    self.check_secondary_write("update session", secondary_result);
+                    false
+                }
+            };
+            if needs_backfill {
+                // The session was created before mirroring started, so we create it.
+                let secondary_result = self
+                    .secondary
+                    .create_session(current_id, expiry, metadata, data)
+                    .await;
+                self.check_secondary_write("update session", secondary_result);
+            }
+        }
+        Ok(result)
+    }
+
+    async fn delete_session(&mut self, id: &SessionId) -> Result<(), Error<Self::Error>> {
+        self.primary.delete_session(id).await?;
+        if let Err(error) = self.secondary.delete_session(id).await {
+            self.record_divergence("delete session", error);
+        }
+        Ok(())
+    }
+
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.primary.delete_sessions(ids).await?;
+        if let Err(error) = self.secondary.delete_sessions(ids).await {
+            self.record_divergence("delete sessions", error);
+        }
+        Ok(())
+    }
+
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        self.primary.create_tombstone(id, expiry).await?;
+        if let Err(error) = self.secondary.create_tombstone(id, expiry).await {
+            self.record_divergence("create tombstone", error);
+        }
+        Ok(())
+    }
+
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.primary.is_tombstone(id, now).await
+    }
+
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.primary.is_session_valid(id, now).await
+    }
+
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        self.primary.now().await
+    }
+
+    fn set_request_context(&mut self, context: Option<&RequestContext>) {
+        self.primary.set_request_context(context);
+        self.secondary.set_request_context(context);
+    }
+
+    async fn preload_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.primary.preload_sessions(ids).await
+    }
+
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        self.primary.count_sessions().await
+    }
+
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        let deleted = self.primary.delete_expired_sessions(before).await?;
+        if let Err(error) = self.secondary.delete_expired_sessions(before).await {
+            self.record_divergence("delete expired sessions", error);
+        }
+        Ok(deleted)
+    }
+
+    async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
+        self.primary.clear().await?;
+        if let Err(error) = self.secondary.clear().await {
+            self.record_divergence("clear", error);
+        }
+        Ok(())
+    }
+
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        self.primary.clear_namespace(namespace).await?;
+        if let Err(error) = self.secondary.clear_namespace(namespace).await {
+            self.record_divergence("clear_namespace", error);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the expiry of a session that was read from a connector.
+fn stored_expiry<SessionData>(session: &Session<SessionData>) -> Option<SessionExpiry> {
+    match &session.state {
+        SessionState::Unchanged { expiry, .. } => Some(*expiry),
+        _ => None,
+    }
+}
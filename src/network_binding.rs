@@ -0,0 +1,45 @@
+use std::net::IpAddr;
+
+/// Returns a channel binding value that identifies the network of the given client address,
+/// for use with [`Session::bind_to_channel`](crate::Session::bind_to_channel) and
+/// [`SessionStore::load_session_with_channel_binding`](crate::SessionStore::load_session_with_channel_binding).
+///
+/// The value covers the `/24` network of IPv4 addresses and the `/64` network of IPv6 addresses,
+/// such that changes of the address within a network, e.g. due to IPv6 privacy extensions, do not count as a change of channel.
+/// IPv4-mapped IPv6 addresses are treated as IPv4 addresses.
+///
+/// Combined with [`ChannelBindingPolicy::Rotate`](crate::ChannelBindingPolicy::Rotate),
+/// this rotates the session cookie whenever a client roams between networks.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::net::IpAddr;
+/// # use typed_session::ip_network_binding;
+/// let address: IpAddr = "192.0.2.17".parse().unwrap();
+/// let same_network: IpAddr = "192.0.2.200".parse().unwrap();
+/// let other_network: IpAddr = "198.51.100.17".parse().unwrap();
+/// assert_eq!(ip_network_binding(address), ip_network_binding(same_network));
+/// assert_ne!(ip_network_binding(address), ip_network_binding(other_network));
+/// ```
+pub fn ip_network_binding(address: IpAddr) -> Vec<u8> {
+    let address = match address {
+        IpAddr::V6(address) => address
+            .to_ipv4_mapped()
+            .map(IpAddr::V4)
+            .unwrap_or(IpAddr::V6(address)),
+        address => address,
+    };
+    match address {
+        IpAddr::V4(address) => {
+            let mut binding = vec![4];
+            binding.extend_from_slice(&address.octets()[..3]);
+            binding
+        }
+        IpAddr::V6(address) => {
+            let mut binding = vec![6];
+            binding.extend_from_slice(&address.octets()[..8]);
+            binding
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{
+    ConnectorDescription, Error, RequestContext, Session, SessionExpiry, SessionId,
+    SessionMetadata, SessionStoreConnector, WriteSessionResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// A session store connector that layers a writable upper connector over a lower connector that is never written to.
+///
+/// Reads check the upper connector first, and fall back to the lower connector.
+/// All writes go to the upper connector.
+/// This allows e.g. a preview environment to use the sessions of production without mutating them.
+///
+/// When a session of the lower connector is updated, it is copied into the upper connector under its new id.
+/// When a session of the lower connector is updated or deleted, its old id is masked by a tombstone in the upper
+/// connector, such that it cannot be loaded or updated again.
+/// Hence, the upper connector must support tombstones, see [`SessionStoreConnector::create_tombstone`].
+/// Without tombstones, sessions of the lower connector would reappear after being updated or deleted.
+#[derive(Debug, Clone)]
+pub struct OverlayStore<Upper, Lower> {
+    upper: Upper,
+    lower: Lower,
+}
+
+impl<Upper, Lower> OverlayStore<Upper, Lower> {
+    /// Layer the `upper` connector over the `lower` connector.
+    pub fn new(upper: Upper, lower: Lower) -> Self {
+        Self { upper, lower }
+    }
+
+    /// Returns the upper connector, which receives all writes.
+    pub fn upper(&self) -> &Upper {
+        &self.upper
+    }
+
+    /// Returns the lower connector, which is only read from.
+    pub fn lower(&self) -> &Lower {
+        &self.lower
+    }
+
+    /// Returns the upper and the lower connector, consuming this wrapper.
+    pub fn into_inner(self) -> (Upper, Lower) {
+        (self.upper, self.lower)
+    }
+}
+
+impl<Upper: Send, Lower> OverlayStore<Upper, Lower> {
+    async fn is_masked<SessionData: Send + Sync>(
+        &mut self,
+        id: &SessionId,
+    ) -> Result<bool, Error<Upper::Error>>
+    where
+        Upper: SessionStoreConnector<SessionData>,
+    {
+        let now = self.upper.now().await?.unwrap_or_else(Utc::now);
+        self.upper.is_tombstone(id, now).await
+    }
+
+    /// Returns true if the session with the given `id` is visible in the lower connector.
+    async fn exists_in_lower<SessionData: Send + Sync>(
+        &mut self,
+        id: &SessionId,
+    ) -> Result<bool, Error<Upper::Error>>
+    where
+        Upper: SessionStoreConnector<SessionData>,
+        Lower: SessionStoreConnector<SessionData, Error = Upper::Error>,
+    {
+        Ok(!self.is_masked(id).await? && self.lower.read_session(id.clone()).await?.is_some())
+    }
+
+    async fn mask<SessionData: Send + Sync>(
+        &mut self,
+        id: &SessionId,
+    ) -> Result<(), Error<Upper::Error>>
+    where
+        Upper: SessionStoreConnector<SessionData>,
+    {
+        // The lower session may still be renewed by the environment that owns it, so the mask never expires.
+        self.upper
+            .create_tombstone(id, DateTime::<Utc>::MAX_UTC)
+            .await
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync,
+        Upper: SessionStoreConnector<SessionData> + Send,
+        Lower: SessionStoreConnector<SessionData, Error = Upper::Error> + Send,
+    > SessionStoreConnector<SessionData> for OverlayStore<Upper, Lower>
+{
+    type Error = Upper::Error;
+
+    fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        self.upper.maximum_retries_on_id_collision()
+    }
+
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        self.upper.is_transient_error(error)
+    }
+
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::wrapping(
+            "OverlayStore",
+            [self.upper.describe(), self.lower.describe()],
+        )
+    }
+
+    fn enforces_expiry(&self) -> bool {
+        self.upper.enforces_expiry() && self.lower.enforces_expiry()
+    }
+
+    /// All writes go to the upper connector, so only the upper connector decides.
+    fn accepts_session_id(&self, new_id: &SessionId, previous_id: Option<&SessionId>) -> bool {
+        self.upper.accepts_session_id(new_id, previous_id)
+    }
+
+    async fn create_session(
+        &mut self,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        if self.exists_in_lower(current_id).await? {
+            return Ok(WriteSessionResult::SessionIdExists);
+        }
+        self.upper
+            .create_session(current_id, expiry, metadata, data)
+            .await
+    }
+
+    async fn read_session(
+        &mut self,
+        id: SessionId,
+    ) -> Result<Option<Session<SessionData>>, Error<Self::Error>> {
+        if let Some(session) = self.upper.read_session(id.clone()).await? {
+            return Ok(Some(session));
+        }
+        if self.is_masked(&id).await? {
+            return Ok(None);
+        }
+        self.lower.read_session(id).await
+    }
+
+    async fn update_session(
+        &mut self,
+        current_id: &SessionId,
+        previous_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        if self.exists_in_lower(current_id).await? {
+            return Ok(WriteSessionResult::SessionIdExists);
+        }
+        match self
+            .upper
+            .update_session(current_id, previous_id, expiry, metadata, data)
+            .await
+        {
+            Err(Error::UpdatedSessionDoesNotExist) => {}
+            result => return result,
+        }
+
+        if !self.exists_in_lower(previous_id).await? {
+            return Err(Error::UpdatedSessionDoesNotExist);
+        }
+        let result = self
+            .upper
+            .create_session(current_id, expiry, metadata, data)
+            .await?;
+        if let WriteSessionResult::Ok(()) = result {
+            self.mask(previous_id).await?;
+        }
+        Ok(result)
+    }
+
+    async fn delete_session(&mut self, id: &SessionId) -> Result<(), Error<Self::Error>> {
+        self.upper.delete_session(id).await?;
+        if self.exists_in_lower(id).await? {
+            self.mask(id).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.upper.delete_sessions(ids).await?;
+        for id in ids {
+            if self.exists_in_lower(id).await? {
+                self.mask(id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        self.upper.create_tombstone(id, expiry).await
+    }
+
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        Ok(self.upper.is_tombstone(id, now).await? || self.lower.is_tombstone(id, now).await?)
+    }
+
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        self.upper.now().await
+    }
+
+    fn set_request_context(&mut self, context: Option<&RequestContext>) {
+        self.upper.set_request_context(context);
+        self.lower.set_request_context(context);
+    }
+
+    async fn preload_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.upper.preload_sessions(ids).await?;
+        self.lower.preload_sessions(ids).await
+    }
+
+    /// Returns the sum of the counts of both connectors.
+    /// Sessions of the lower connector that were masked by an update or deletion are still counted.
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        let upper = self.upper.count_sessions().await?;
+        let lower = self.lower.count_sessions().await?;
+        Ok(upper + lower)
+    }
+
+    /// Deletes expired sessions only from the upper connector, since the lower connector is never written to.
+    /// Expired sessions of the lower connector are not visible anyway.
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        self.upper.delete_expired_sessions(before).await
+    }
+
+    /// Clears only the upper connector, which makes all sessions of the lower connector visible again.
+    async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
+        self.upper.clear().await
+    }
+
+    /// Clears the namespace only in the upper connector,
+    /// which makes the sessions of the namespace in the lower connector visible again.
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        self.upper.clear_namespace(namespace).await
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    CookieValue, DefaultSessionCookieGenerator, Error, Session, SessionCookieCommand,
+    SessionCookieGenerator, SessionStore, SessionStoreConnector,
+};
+use std::fmt::Debug;
+
+/// A session store that owns its connection, for code written against the earlier API of this crate,
+/// where the connection was not passed to each call.
+///
+/// It wraps a [`SessionStore`] together with a connection, and forwards
+/// [`load_session`](OwnedConnectionSessionStore::load_session) and
+/// [`store_session`](OwnedConnectionSessionStore::store_session) to the session store with the owned connection.
+/// This allows to upgrade without rewriting all call sites at once.
+/// Everything else is available through [`store`](OwnedConnectionSessionStore::store) and
+/// [`connection_mut`](OwnedConnectionSessionStore::connection_mut),
+/// and [`into_parts`](OwnedConnectionSessionStore::into_parts) completes the migration.
+///
+/// New code should use the [`SessionStore`] directly, which can be shared between requests
+/// while each request uses its own connection, e.g. from a pool.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "memory-store")] {
+/// # use typed_session::{MemoryStore, OwnedConnectionSessionStore, Session, SessionCookieCommand, SessionRenewalStrategy, SessionStore};
+/// # async_std::task::block_on(async {
+/// let store: SessionStore<u64, _> = SessionStore::new(SessionRenewalStrategy::Ignore);
+/// let mut store = OwnedConnectionSessionStore::new(store, MemoryStore::new());
+///
+/// let SessionCookieCommand::Set { cookie_value, .. } =
+///     store.store_session(Session::new_with_data(1)).await.unwrap()
+/// else {
+///     unreachable!()
+/// };
+/// let session = store.load_session(&cookie_value).await.unwrap().unwrap();
+/// assert_eq!(*session.data(), 1);
+/// # })
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct OwnedConnectionSessionStore<
+    SessionData,
+    SessionStoreConnection,
+    CookieGenerator = DefaultSessionCookieGenerator,
+> {
+    store: SessionStore<SessionData, SessionStoreConnection, CookieGenerator>,
+    connection: SessionStoreConnection,
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator>
+    OwnedConnectionSessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Wrap the given session store together with the connection it should use.
+    pub fn new(
+        store: SessionStore<SessionData, SessionStoreConnection, CookieGenerator>,
+        connection: SessionStoreConnection,
+    ) -> Self {
+        Self { store, connection }
+    }
+
+    /// Returns the wrapped session store.
+    pub fn store(&self) -> &SessionStore<SessionData, SessionStoreConnection, CookieGenerator> {
+        &self.store
+    }
+
+    /// Returns the wrapped session store mutably, e.g. to change its settings.
+    pub fn store_mut(
+        &mut self,
+    ) -> &mut SessionStore<SessionData, SessionStoreConnection, CookieGenerator> {
+        &mut self.store
+    }
+
+    /// Returns the owned connection.
+    pub fn connection(&self) -> &SessionStoreConnection {
+        &self.connection
+    }
+
+    /// Returns the owned connection mutably, e.g. to pass it to methods of the [`store`](OwnedConnectionSessionStore::store).
+    pub fn connection_mut(&mut self) -> &mut SessionStoreConnection {
+        &mut self.connection
+    }
+
+    /// Returns the wrapped session store and the owned connection, consuming this wrapper.
+    pub fn into_parts(
+        self,
+    ) -> (
+        SessionStore<SessionData, SessionStoreConnection, CookieGenerator>,
+        SessionStoreConnection,
+    ) {
+        (self.store, self.connection)
+    }
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > OwnedConnectionSessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Get a session from the storage backend, see [`SessionStore::load_session`].
+    pub async fn load_session(
+        &mut self,
+        cookie_value: &CookieValue,
+    ) -> Result<Option<Session<SessionData>>, Error<SessionStoreConnection::Error>> {
+        self.store
+            .load_session(cookie_value, &mut self.connection)
+            .await
+    }
+
+    /// Store a session in the storage backend, see [`SessionStore::store_session`].
+    pub async fn store_session(
+        &mut self,
+        session: Session<SessionData>,
+    ) -> Result<SessionCookieCommand, Error<SessionStoreConnection::Error>> {
+        self.store
+            .store_session(session, &mut self.connection)
+            .await
+    }
+
+    /// Empties the store, see [`SessionStore::clear_store`].
+    pub async fn clear_store(&mut self) -> Result<(), Error<SessionStoreConnection::Error>> {
+        self.store.clear_store(&mut self.connection).await
+    }
+}
+
+impl<SessionData, SessionStoreConnection: Clone, CookieGenerator: Clone> Clone
+    for OwnedConnectionSessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            connection: self.connection.clone(),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{
+    ChannelBindingPolicy, ClockAuthority, ConfigNotWritable, DeletionMode,
+    ExpirySanitizationPolicy, InvalidDuration, InvalidRenewalStrategy, SessionCookieGenerator,
+    SessionRenewalStrategy, SessionStore,
+};
+use chrono::Duration;
+use serde::{Deserialize, Deserializer};
+
+/// The policies of a [`SessionStore`] as they are written in a configuration file,
+/// available under the feature flag `serde`.
+///
+/// All durations are given as strings like `"7d"` or `"1h 30min"`, see [`parse_duration`].
+/// Apart from the renewal strategy, all fields are optional and default to the defaults of the session store.
+/// The [hashing policy](SessionStore::set_hashing_policy) is deliberately not configurable,
+/// since changing it invalidates all existing sessions.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{PolicyConfig, SessionRenewalStrategy, SessionStore};
+/// let config: PolicyConfig = serde_json::from_str(
+///     r#"{
+///         "renewal": {
+///             "strategy": "AutomaticRenewal",
+///             "time_to_live": "7d",
+///             "maximum_remaining_time_to_live_for_renewal": "6d"
+///         },
+///         "deletion": { "mode": "Tombstone", "time_to_live": "30 days" },
+///         "clock_authority": "Backend"
+///     }"#,
+/// )
+/// .unwrap();
+///
+/// let mut store: SessionStore<(), ()> = SessionStore::new(SessionRenewalStrategy::Ignore);
+/// store.apply_policy_config(&config).unwrap();
+/// assert!(matches!(store.session_renewal_strategy(), SessionRenewalStrategy::AutomaticRenewal { .. }));
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct PolicyConfig {
+    /// The session renewal strategy.
+    pub renewal: RenewalConfig,
+    /// The deletion mode.
+    #[serde(default)]
+    pub deletion: DeletionConfig,
+    /// The clock authority.
+    #[serde(default)]
+    pub clock_authority: ClockAuthority,
+    /// The channel binding policy.
+    #[serde(default)]
+    pub channel_binding_policy: ChannelBindingPolicy,
+    /// The expiry sanitization, which is disabled if not given.
+    #[serde(default)]
+    pub expiry_sanitization: Option<ExpirySanitizationConfig>,
+    /// The maximum number of retries on id collisions, which defers to the connector if not given.
+    #[serde(default)]
+    pub maximum_retries_on_id_collision: Option<u32>,
+    /// The maximum number of sessions in the backend, which is unbounded if not given.
+    #[serde(default)]
+    pub maximum_sessions: Option<usize>,
+}
+
+/// A [`SessionRenewalStrategy`] as it is written in a configuration file, see [`PolicyConfig`].
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "strategy")]
+pub enum RenewalConfig {
+    /// See [`SessionRenewalStrategy::Ignore`].
+    Ignore,
+    /// See [`SessionRenewalStrategy::AutomaticRenewal`].
+    AutomaticRenewal {
+        /// The time-to-live for a new or renewed session.
+        #[serde(deserialize_with = "deserialize_duration")]
+        time_to_live: Duration,
+        /// The maximum remaining time-to-live to trigger a session renewal.
+        #[serde(deserialize_with = "deserialize_duration")]
+        maximum_remaining_time_to_live_for_renewal: Duration,
+    },
+    /// See [`SessionRenewalStrategy::IdleAndAbsoluteTimeout`].
+    IdleAndAbsoluteTimeout {
+        /// The time of inactivity after which a session expires.
+        #[serde(deserialize_with = "deserialize_duration")]
+        idle_timeout: Duration,
+        /// The maximum lifetime of a session, measured from its creation.
+        #[serde(deserialize_with = "deserialize_duration")]
+        absolute_timeout: Duration,
+    },
+}
+
+/// A [`DeletionMode`] as it is written in a configuration file, see [`PolicyConfig`].
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(tag = "mode")]
+pub enum DeletionConfig {
+    /// See [`DeletionMode::Hard`].
+    #[default]
+    Hard,
+    /// See [`DeletionMode::Tombstone`].
+    Tombstone {
+        /// The time-to-live of the tombstone.
+        #[serde(deserialize_with = "deserialize_duration")]
+        time_to_live: Duration,
+    },
+}
+
+/// The expiry sanitization as it is written in a configuration file,
+/// see [`SessionStore::set_expiry_sanitization`].
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ExpirySanitizationConfig {
+    /// The maximum plausible time-to-live of a loaded session.
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub maximum_time_to_live: Duration,
+    /// The policy for implausible expiries.
+    #[serde(default)]
+    pub policy: ExpirySanitizationPolicy,
+}
+
+impl SessionRenewalStrategy {
+    /// Create a session renewal strategy from its configuration,
+    /// validating it like [`automatic_renewal`](SessionRenewalStrategy::automatic_renewal)
+    /// and [`idle_and_absolute_timeout`](SessionRenewalStrategy::idle_and_absolute_timeout).
+    pub fn from_config(config: &RenewalConfig) -> Result<Self, InvalidRenewalStrategy> {
+        match *config {
+            RenewalConfig::Ignore => Ok(Self::Ignore),
+            RenewalConfig::AutomaticRenewal {
+                time_to_live,
+                maximum_remaining_time_to_live_for_renewal,
+            } => Self::automatic_renewal(time_to_live, maximum_remaining_time_to_live_for_renewal),
+            RenewalConfig::IdleAndAbsoluteTimeout {
+                idle_timeout,
+                absolute_timeout,
+            } => Self::idle_and_absolute_timeout(idle_timeout, absolute_timeout),
+        }
+    }
+}
+
+impl DeletionMode {
+    /// Create a deletion mode from its configuration.
+    pub fn from_config(config: &DeletionConfig) -> Self {
+        match *config {
+            DeletionConfig::Hard => Self::Hard,
+            DeletionConfig::Tombstone { time_to_live } => Self::Tombstone { time_to_live },
+        }
+    }
+}
+
+/// The reasons why [`SessionStore::apply_policy_config`] can fail, available under the feature flag `serde`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+pub enum PolicyConfigError {
+    /// The configured renewal strategy is invalid.
+    #[error(transparent)]
+    InvalidRenewalStrategy(#[from] InvalidRenewalStrategy),
+    /// The runtime configuration of the session store is received from a watch channel.
+    #[error(transparent)]
+    ConfigNotWritable(#[from] ConfigNotWritable),
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator: SessionCookieGenerator>
+    SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Apply all policies of the given configuration to this session store.
+    ///
+    /// The renewal strategy and deletion mode are updated through the [configuration handle](SessionStore::config_handle),
+    /// so they are shared with all clones of this session store.
+    ///
+    /// If the renewal strategy is invalid, or the configuration handle receives the configuration from a watch channel,
+    /// an error is returned and nothing is changed.
+    pub fn apply_policy_config(&mut self, config: &PolicyConfig) -> Result<(), PolicyConfigError> {
+        let session_renewal_strategy = SessionRenewalStrategy::from_config(&config.renewal)?;
+        self.config_handle().update(|runtime_config| {
+            runtime_config.session_renewal_strategy = session_renewal_strategy;
+            runtime_config.deletion_mode = DeletionMode::from_config(&config.deletion);
+        })?;
+        self.set_clock_authority(config.clock_authority);
+        self.set_channel_binding_policy(config.channel_binding_policy);
+        if let Some(expiry_sanitization) = &config.expiry_sanitization {
+            self.set_expiry_sanitization(
+                expiry_sanitization.maximum_time_to_live,
+                expiry_sanitization.policy,
+            );
+        }
+        if let Some(maximum_retries_on_id_collision) = config.maximum_retries_on_id_collision {
+            self.set_maximum_retries_on_id_collision(maximum_retries_on_id_collision);
+        }
+        if let Some(maximum_sessions) = config.maximum_sessions {
+            self.set_maximum_sessions(Some(maximum_sessions));
+        }
+        Ok(())
+    }
+}
+
+/// Parse a humantime-style duration, i.e. a sequence of numbers with units, optionally separated by whitespace.
+///
+/// The supported units are `ns`, `us`, `ms`, `s`, `m`, `h`, `d` and `w`, and their long forms like
+/// `msec`, `sec`, `min`, `hours`, `day` or `weeks`.
+/// Months and years are not supported, since their length is ambiguous.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::parse_duration;
+/// # use chrono::Duration;
+/// assert_eq!(parse_duration("7d"), Ok(Duration::days(7)));
+/// assert_eq!(parse_duration("1h 30min"), Ok(Duration::minutes(90)));
+/// assert_eq!(parse_duration("2 weeks"), Ok(Duration::weeks(2)));
+/// assert!(parse_duration("7").is_err());
+/// ```
+pub fn parse_duration(input: &str) -> Result<Duration, InvalidDuration> {
+    let invalid = || InvalidDuration {
+        input: input.to_string(),
+    };
+
+    let mut nanoseconds: u128 = 0;
+    let mut rest = input.trim_start();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let number: u128 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = rest[digits..].trim_start();
+
+        let letters = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let unit: u128 = match &rest[..letters] {
+            "nanoseconds" | "nanosecond" | "nsec" | "ns" => 1,
+            "microseconds" | "microsecond" | "usec" | "us" => 1_000,
+            "milliseconds" | "millisecond" | "msec" | "ms" => 1_000_000,
+            "seconds" | "second" | "secs" | "sec" | "s" => 1_000_000_000,
+            "minutes" | "minute" | "mins" | "min" | "m" => 60 * 1_000_000_000,
+            "hours" | "hour" | "hrs" | "hr" | "h" => 60 * 60 * 1_000_000_000,
+            "days" | "day" | "d" => 24 * 60 * 60 * 1_000_000_000,
+            "weeks" | "week" | "w" => 7 * 24 * 60 * 60 * 1_000_000_000,
+            _ => return Err(invalid()),
+        };
+        rest = rest[letters..].trim_start();
+
+        nanoseconds = number
+            .checked_mul(unit)
+            .and_then(|part| nanoseconds.checked_add(part))
+            .ok_or_else(invalid)?;
+    }
+
+    let seconds = u64::try_from(nanoseconds / 1_000_000_000).map_err(|_| invalid())?;
+    // The remainder is smaller than one billion, so it fits into an `u32`.
+    let total = std::time::Duration::new(seconds, (nanoseconds % 1_000_000_000) as u32);
+    Duration::from_std(total).map_err(|_| invalid())
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let input = String::deserialize(deserializer)?;
+    parse_duration(&input).map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,209 @@
+use crate::{AttachmentStoreConnector, Error, Session, SessionCookieGenerator, SessionStore};
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// The prefix of the attachment keys under which the buckets of [`SessionRateLimiter`]s are stored.
+const ATTACHMENT_KEY_PREFIX: &str = "rate-limit:";
+
+/// The token bucket of a single rate limiter.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct RateLimitBucket {
+    /// The tokens in the bucket at `updated`.
+    tokens: u32,
+    /// The time from which the next token is refilled.
+    updated: DateTime<Utc>,
+}
+
+impl RateLimitBucket {
+    /// Encode the bucket as attachment.
+    fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16);
+        bytes.extend_from_slice(&self.tokens.to_le_bytes());
+        bytes.extend_from_slice(&self.updated.timestamp().to_le_bytes());
+        bytes.extend_from_slice(&self.updated.timestamp_subsec_nanos().to_le_bytes());
+        bytes
+    }
+
+    /// Decode a bucket encoded with [`to_bytes`](RateLimitBucket::to_bytes), or return `None` if the bytes are invalid.
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; 16] = bytes.try_into().ok()?;
+        let tokens = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+        let seconds = i64::from_le_bytes(bytes[4..12].try_into().unwrap());
+        let nanoseconds = u32::from_le_bytes(bytes[12..].try_into().unwrap());
+        Some(Self {
+            tokens,
+            updated: DateTime::from_timestamp(seconds, nanoseconds)?,
+        })
+    }
+}
+
+/// The decision of [`SessionRateLimiter::try_acquire`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[must_use]
+pub enum RateLimitDecision {
+    /// The request may be processed.
+    Allow {
+        /// The number of requests that may still be processed before the limit is hit.
+        remaining: u32,
+    },
+    /// The request must be rejected, e.g. with `429 Too Many Requests`.
+    Reject {
+        /// The time until the next request is allowed, e.g. for a `Retry-After` header.
+        retry_after: Duration,
+    },
+}
+
+/// A token-bucket rate limiter that stores its state in an [attachment](SessionStore::put_attachment) of the session.
+///
+/// This allows per-user rate limiting without a separate datastore.
+/// Each session has a bucket of at most `capacity` tokens, which is refilled by one token every `refill_interval`.
+/// Every allowed request takes one token.
+///
+/// The state is not part of the session data, so acquiring a token does not change the session.
+/// In particular, it does not rotate the session id, such that concurrent requests with the same session cookie
+/// keep working. Only allowed requests write the attachment.
+/// Since reading and writing the attachment is not atomic, concurrent requests may take the same token,
+/// such that a burst of concurrent requests may slightly exceed the limit.
+///
+/// Since the state is stored with the session, a client can reset its limit by dropping its session cookie.
+/// Hence, this is meant to limit authenticated users, whose sessions cannot be recreated at will.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "memory-store")] {
+/// # use typed_session::{MemoryStore, RateLimitDecision, Session, SessionCookieCommand, SessionRateLimiter, SessionRenewalStrategy, SessionStore};
+/// # use std::time::Duration;
+/// # async_std::task::block_on(async {
+/// let store: SessionStore<(), _> = SessionStore::new(SessionRenewalStrategy::Ignore);
+/// let mut connection = MemoryStore::new();
+/// # let SessionCookieCommand::Set { cookie_value, .. } = store.store_session(Session::new_with_data(()), &mut connection).await? else { unreachable!() };
+/// let session = store.load_session(&cookie_value, &mut connection).await?.unwrap();
+///
+/// let limiter = SessionRateLimiter::new("api", 2, Duration::from_secs(60));
+/// let now = chrono::Utc::now();
+/// assert_eq!(
+///     limiter.try_acquire(&store, &session, now, &mut connection).await?,
+///     RateLimitDecision::Allow { remaining: 1 },
+/// );
+/// assert_eq!(
+///     limiter.try_acquire(&store, &session, now, &mut connection).await?,
+///     RateLimitDecision::Allow { remaining: 0 },
+/// );
+/// assert_eq!(
+///     limiter.try_acquire(&store, &session, now, &mut connection).await?,
+///     RateLimitDecision::Reject { retry_after: Duration::from_secs(60) },
+/// );
+/// assert!(!session.is_changed());
+/// # Ok::<(), typed_session::Error<std::convert::Infallible>>(()) }).unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SessionRateLimiter {
+    name: String,
+    capacity: u32,
+    refill_interval: Duration,
+}
+
+impl SessionRateLimiter {
+    /// Create a rate limiter that stores its bucket under the given `name`.
+    ///
+    /// The bucket holds at most `capacity` tokens, and is refilled by one token every `refill_interval`.
+    /// A zero `refill_interval` is treated as one nanosecond.
+    pub fn new(name: impl Into<String>, capacity: u32, refill_interval: Duration) -> Self {
+        Self {
+            name: name.into(),
+            capacity,
+            refill_interval: refill_interval.max(Duration::from_nanos(1)),
+        }
+    }
+
+    /// Returns the name of the bucket of this rate limiter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the maximum number of tokens in the bucket.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Returns the time it takes to refill one token.
+    pub fn refill_interval(&self) -> Duration {
+        self.refill_interval
+    }
+
+    /// Take a token from the bucket of the given session, if there is one.
+    ///
+    /// The bucket is stored in an [attachment](SessionStore::put_attachment) of the session,
+    /// which is only written if the request is allowed.
+    /// The session must have been loaded from the session store, otherwise [`Error::SessionNotStored`] is returned.
+    pub async fn try_acquire<
+        SessionData: Debug,
+        SessionStoreConnection: AttachmentStoreConnector<SessionData>,
+        CookieGenerator: SessionCookieGenerator,
+    >(
+        &self,
+        store: &SessionStore<SessionData, SessionStoreConnection, CookieGenerator>,
+        session: &Session<SessionData>,
+        now: DateTime<Utc>,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<RateLimitDecision, Error<SessionStoreConnection::Error>> {
+        if session.current_id().is_none() {
+            return Err(Error::SessionNotStored);
+        }
+        let key = self.attachment_key();
+        let bucket = store
+            .get_attachment(session, &key, connection)
+            .await?
+            .and_then(|bytes| RateLimitBucket::from_bytes(&bytes));
+        let bucket = self.refill(bucket.as_ref(), now);
+        if bucket.tokens == 0 {
+            let elapsed = (now - bucket.updated).to_std().unwrap_or(Duration::ZERO);
+            return Ok(RateLimitDecision::Reject {
+                retry_after: self.refill_interval.saturating_sub(elapsed),
+            });
+        }
+
+        let remaining = bucket.tokens - 1;
+        let bucket = RateLimitBucket {
+            tokens: remaining,
+            updated: bucket.updated,
+        };
+        store
+            .put_attachment(session, &key, &bucket.to_bytes(), connection)
+            .await?;
+        Ok(RateLimitDecision::Allow { remaining })
+    }
+
+    /// The key of the attachment that stores the bucket of this rate limiter.
+    fn attachment_key(&self) -> String {
+        format!("{ATTACHMENT_KEY_PREFIX}{}", self.name)
+    }
+
+    /// Returns the bucket with the tokens refilled until `now`.
+    fn refill(&self, bucket: Option<&RateLimitBucket>, now: DateTime<Utc>) -> RateLimitBucket {
+        let full = RateLimitBucket {
+            tokens: self.capacity,
+            updated: now,
+        };
+        let Some(bucket) = bucket else {
+            return full;
+        };
+        let elapsed = (now - bucket.updated).to_std().unwrap_or(Duration::ZERO);
+        let refills = elapsed.as_nanos() / self.refill_interval.as_nanos();
+        if u128::from(bucket.tokens) + refills >= u128::from(self.capacity) {
+            return full;
+        }
+
+        // `refills` is smaller than the capacity, so it fits into an `u32`.
+        let refills = refills as u32;
+        RateLimitBucket {
+            tokens: bucket.tokens + refills,
+            updated: bucket.updated
+                + chrono::Duration::from_std(self.refill_interval * refills)
+                    .unwrap_or(chrono::Duration::zero()),
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{
+    ConnectorDescription, Error, RequestContext, Session, SessionExpiry, SessionId,
+    SessionMetadata, SessionStoreConnector, WriteSessionResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::info;
+
+/// A session store connector that forwards all reads to an inner connector, but does not perform any writes.
+///
+/// This is useful e.g. for replaying production traffic, or for verifying a migration target before cutting over to it.
+/// Each suppressed write is logged, and then handled according to the [`ReadOnlyMode`].
+#[derive(Debug, Clone)]
+pub struct ReadOnlyStore<SessionStoreConnection> {
+    inner: SessionStoreConnection,
+    mode: ReadOnlyMode,
+}
+
+/// How a [`ReadOnlyStore`] handles writes.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum ReadOnlyMode {
+    /// Writes succeed without having any effect.
+    #[default]
+    Simulate,
+    /// Writes fail with [`Error::ReadOnlyStore`].
+    Reject,
+}
+
+impl<SessionStoreConnection> ReadOnlyStore<SessionStoreConnection> {
+    /// Wrap the given connector, handling writes according to `mode`.
+    pub fn new(inner: SessionStoreConnection, mode: ReadOnlyMode) -> Self {
+        Self { inner, mode }
+    }
+
+    /// Returns the wrapped connector.
+    pub fn inner(&self) -> &SessionStoreConnection {
+        &self.inner
+    }
+
+    /// Returns the wrapped connector, consuming this wrapper.
+    pub fn into_inner(self) -> SessionStoreConnection {
+        self.inner
+    }
+
+    /// Returns how this store handles writes.
+    pub fn mode(&self) -> ReadOnlyMode {
+        self.mode
+    }
+
+    fn suppress_write<InnerError>(&self, operation: &str) -> Result<(), Error<InnerError>> {
+        info!("Suppressed {operation} in read-only session store");
+        match self.mode {
+            ReadOnlyMode::Simulate => Ok(()),
+            ReadOnlyMode::Reject => Err(Error::ReadOnlyStore),
+        }
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+    > SessionStoreConnector<SessionData> for ReadOnlyStore<SessionStoreConnection>
+{
+    type Error = SessionStoreConnection::Error;
+
+    fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        self.inner.maximum_retries_on_id_collision()
+    }
+
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        self.inner.is_transient_error(error)
+    }
+
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::wrapping("ReadOnlyStore", [self.inner.describe()])
+    }
+
+    fn enforces_expiry(&self) -> bool {
+        self.inner.enforces_expiry()
+    }
+
+    fn accepts_session_id(&self, new_id: &SessionId, previous_id: Option<&SessionId>) -> bool {
+        self.inner.accepts_session_id(new_id, previous_id)
+    }
+
+    async fn create_session(
+        &mut self,
+        _current_id: &SessionId,
+        _expiry: &SessionExpiry,
+        _metadata: &SessionMetadata,
+        _data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        self.suppress_write("create session")?;
+        Ok(WriteSessionResult::Ok(()))
+    }
+
+    async fn read_session(
+        &mut self,
+        id: SessionId,
+    ) -> Result<Option<Session<SessionData>>, Error<Self::Error>> {
+        self.inner.read_session(id).await
+    }
+
+    async fn read_sessions(
+        &mut self,
+        ids: &[SessionId],
+    ) -> Result<Vec<Option<Session<SessionData>>>, Error<Self::Error>>
+    where
+        SessionData: Send,
+    {
+        self.inner.read_sessions(ids).await
+    }
+
+    async fn update_session(
+        &mut self,
+        _current_id: &SessionId,
+        _previous_id: &SessionId,
+        _expiry: &SessionExpiry,
+        _metadata: &SessionMetadata,
+        _data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        self.suppress_write("update session")?;
+        Ok(WriteSessionResult::Ok(()))
+    }
+
+    async fn delete_session(&mut self, _id: &SessionId) -> Result<(), Error<Self::Error>> {
+        self.suppress_write("delete session")
+    }
+
+    async fn delete_sessions(&mut self, _ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.suppress_write("delete sessions")
+    }
+
+    async fn create_tombstone(
+        &mut self,
+        _id: &SessionId,
+        _expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        self.suppress_write("create tombstone")
+    }
+
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.inner.is_tombstone(id, now).await
+    }
+
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.inner.is_session_valid(id, now).await
+    }
+
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        self.inner.now().await
+    }
+
+    fn set_request_context(&mut self, context: Option<&RequestContext>) {
+        self.inner.set_request_context(context);
+    }
+
+    async fn preload_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.inner.preload_sessions(ids).await
+    }
+
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        self.inner.count_sessions().await
+    }
+
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        let _ = before;
+        self.suppress_write("delete expired sessions")?;
+        Ok(0)
+    }
+
+    async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
+        self.suppress_write("clear")
+    }
+
+    async fn clear_namespace(&mut self, _namespace: &str) -> Result<(), Error<Self::Error>> {
+        self.suppress_write("clear_namespace")
+    }
+}
@@ -0,0 +1,111 @@
+/// A small header that is stored in front of an encoded session record, such that readers know how to decode it.
+///
+/// Codec layers write the header with [`encode`](RecordHeader::encode) and honor it on read with
+/// [`decode`](RecordHeader::decode), i.e. they select the codec, key and decompression by the header of
+/// each record instead of by their current configuration.
+/// This allows to change codecs and keys gradually: new records are written with the new configuration,
+/// while old records stay readable until they are rewritten, e.g. with
+/// [`SessionStore::rewrite_all_sessions`](crate::SessionStore::rewrite_all_sessions).
+///
+/// The header has the following binary format:
+///
+/// | Bytes  | Content                                                                 |
+/// |--------|-------------------------------------------------------------------------|
+/// | `0`    | the format version, currently `1`                                       |
+/// | `1`    | the codec id                                                            |
+/// | `2`    | flags: bit `0` is the compression flag, bit `1` marks a present key id  |
+/// | `3..7` | the key id as big-endian `u32`, only if present                         |
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::RecordHeader;
+/// let header = RecordHeader {
+///     codec_id: 1,
+///     key_id: Some(7),
+///     compressed: false,
+/// };
+/// let record = header.encode(b"payload");
+/// assert_eq!(RecordHeader::decode(&record).unwrap(), (header, &b"payload"[..]));
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct RecordHeader {
+    /// The id of the codec that encoded the payload.
+    /// The assignment of ids to codecs is up to the codec layer.
+    pub codec_id: u8,
+    /// The id of the key that encrypted the payload, or `None` if the payload is not encrypted.
+    pub key_id: Option<u32>,
+    /// True if the payload is compressed.
+    pub compressed: bool,
+}
+
+/// The errors of [`RecordHeader::decode`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+pub enum RecordHeaderError {
+    /// The record is shorter than its header.
+    #[error("the record is shorter than its header")]
+    Truncated,
+
+    /// The header has a format version that is not supported by this version of the crate.
+    #[error("unsupported record header version {0}")]
+    UnsupportedVersion(u8),
+
+    /// The header has flags set that are not supported by this version of the crate.
+    #[error("unsupported record header flags {0:#010b}")]
+    UnsupportedFlags(u8),
+}
+
+const VERSION: u8 = 1;
+const COMPRESSED: u8 = 0b01;
+const KEY_ID: u8 = 0b10;
+
+impl RecordHeader {
+    /// Returns the header followed by the given payload.
+    pub fn encode(&self, payload: &[u8]) -> Vec<u8> {
+        let mut record = Vec::with_capacity(7 + payload.len());
+        let mut flags = 0;
+        if self.compressed {
+            flags |= COMPRESSED;
+        }
+        if self.key_id.is_some() {
+            flags |= KEY_ID;
+        }
+        record.extend_from_slice(&[VERSION, self.codec_id, flags]);
+        if let Some(key_id) = self.key_id {
+            record.extend_from_slice(&key_id.to_be_bytes());
+        }
+        record.extend_from_slice(payload);
+        record
+    }
+
+    /// Split the given record into its header and its payload.
+    pub fn decode(record: &[u8]) -> Result<(Self, &[u8]), RecordHeaderError> {
+        let [version, codec_id, flags, rest @ ..] = record else {
+            return Err(RecordHeaderError::Truncated);
+        };
+        if *version != VERSION {
+            return Err(RecordHeaderError::UnsupportedVersion(*version));
+        }
+        if flags & !(COMPRESSED | KEY_ID) != 0 {
+            return Err(RecordHeaderError::UnsupportedFlags(*flags));
+        }
+
+        let (key_id, payload) = if flags & KEY_ID != 0 {
+            let [a, b, c, d, payload @ ..] = rest else {
+                return Err(RecordHeaderError::Truncated);
+            };
+            (Some(u32::from_be_bytes([*a, *b, *c, *d])), payload)
+        } else {
+            (None, rest)
+        };
+
+        Ok((
+            Self {
+                codec_id: *codec_id,
+                key_id,
+                compressed: flags & COMPRESSED != 0,
+            },
+            payload,
+        ))
+    }
+}
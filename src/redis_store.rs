@@ -0,0 +1,473 @@
+use crate::{
+    ConnectorDescription, Error, Session, SessionExpiry, SessionId, SessionMetadata,
+    SessionStoreConnector, WriteSessionResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use redis::aio::ConnectionManager;
+use redis::{AsyncCommands, ErrorKind, Script};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+/// Creates a session, unless the session or a tombstone with its id exists.
+///
+/// Keys: the session key and the tombstone key of the id.
+/// Arguments: the serialised session and its expiry in milliseconds since the epoch, or an empty string.
+const CREATE_SESSION_SCRIPT: &str = r"
+if redis.call('EXISTS', KEYS[1], KEYS[2]) > 0 then
+    return 0
+end
+redis.call('SET', KEYS[1], ARGV[1])
+if ARGV[2] ~= '' then
+    redis.call('PEXPIREAT', KEYS[1], ARGV[2])
+end
+return 1
+";
+
+/// Moves a session to a new id and overwrites it, unless the session or a tombstone with the new id exists.
+/// Deleting the previous key and checking the result makes concurrent updates of the same session fail.
+///
+/// Keys: the session key and the tombstone key of the new id, and the session key of the previous id.
+/// Arguments: the serialised session and its expiry in milliseconds since the epoch, or an empty string.
+const UPDATE_SESSION_SCRIPT: &str = r"
+if redis.call('EXISTS', KEYS[1], KEYS[2]) > 0 then
+    return 0
+end
+if redis.call('DEL', KEYS[3]) == 0 then
+    return -1
+end
+redis.call('SET', KEYS[1], ARGV[1])
+if ARGV[2] ~= '' then
+    redis.call('PEXPIREAT', KEYS[1], ARGV[2])
+end
+return 1
+";
+
+/// Replaces a session by a tombstone.
+///
+/// Keys: the session key and the tombstone key of the id.
+/// Arguments: the expiry of the tombstone in milliseconds since the epoch.
+const CREATE_TOMBSTONE_SCRIPT: &str = r"
+redis.call('DEL', KEYS[1])
+redis.call('SET', KEYS[2], '')
+redis.call('PEXPIREAT', KEYS[2], ARGV[1])
+return 1
+";
+
+/// A session store connector for [Redis](https://redis.io), available under the feature flag `redis-store`.
+///
+/// Sessions are stored as JSON under the key `<key prefix>session:<hex encoded session id>`,
+/// and expire natively via Redis TTLs.
+/// Creating and updating sessions is done atomically by Lua scripts, such that concurrent updates of the same session
+/// are detected, as required by [`SessionStoreConnector::update_session`].
+///
+/// Child sessions are not supported.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use typed_session::{CookieValue, RedisStore, SessionRenewalStrategy, SessionStore};
+/// # async fn example(cookie_value: CookieValue) -> Result<(), Box<dyn std::error::Error>> {
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let mut connection: RedisStore<String> =
+///     RedisStore::new(redis::aio::ConnectionManager::new(client).await?);
+/// let store: SessionStore<String, _> = SessionStore::new(SessionRenewalStrategy::Ignore);
+/// let session = store.load_session(&cookie_value, &mut connection).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RedisStore<SessionData> {
+    connection: ConnectionManager,
+    key_prefix: String,
+    maximum_retries_on_id_collision: Option<u32>,
+    create_session_script: Script,
+    update_session_script: Script,
+    create_tombstone_script: Script,
+    data: PhantomData<fn() -> SessionData>,
+}
+
+/// The errors of a [`RedisStore`].
+#[derive(Debug, thiserror::Error)]
+pub enum RedisStoreError {
+    /// Redis returned an error.
+    #[error("redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+
+    /// A session could not be serialised or deserialised.
+    #[error("could not serialise or deserialise a session: {0}")]
+    Serialisation(#[from] serde_json::Error),
+}
+
+#[derive(Deserialize)]
+struct Record<SessionData> {
+    expiry: SessionExpiry,
+    metadata: SessionMetadata,
+    data: SessionData,
+}
+
+#[derive(Serialize)]
+struct RecordRef<'a, SessionData> {
+    expiry: &'a SessionExpiry,
+    metadata: &'a SessionMetadata,
+    data: &'a SessionData,
+}
+
+impl<SessionData> RedisStore<SessionData> {
+    /// Create a new connector that stores sessions through the given connection with the key prefix `typed-session:`.
+    pub fn new(connection: ConnectionManager) -> Self {
+        Self {
+            connection,
+            key_prefix: "typed-session:".to_string(),
+            maximum_retries_on_id_collision: None,
+            create_session_script: Script::new(CREATE_SESSION_SCRIPT),
+            update_session_script: Script::new(UPDATE_SESSION_SCRIPT),
+            create_tombstone_script: Script::new(CREATE_TOMBSTONE_SCRIPT),
+            data: PhantomData,
+        }
+    }
+
+    /// Returns the prefix of all keys written by this connector.
+    pub fn key_prefix(&self) -> &str {
+        &self.key_prefix
+    }
+
+    /// Sets the prefix of all keys written by this connector.
+    /// [`clear`](SessionStoreConnector::clear) deletes all keys with this prefix.
+    pub fn set_key_prefix(&mut self, key_prefix: impl Into<String>) {
+        self.key_prefix = key_prefix.into();
+    }
+
+    /// Sets the maximum retries on id collision, see [SessionStoreConnector::maximum_retries_on_id_collision] for details.
+    pub fn set_maximum_retries_on_id_collision(
+        &mut self,
+        maximum_retries_on_id_collision: Option<u32>,
+    ) {
+        self.maximum_retries_on_id_collision = maximum_retries_on_id_collision;
+    }
+
+    fn session_key(&self, id: &SessionId) -> String {
+        format!("{}session:{}", self.key_prefix, hex(id))
+    }
+
+    fn tombstone_key(&self, id: &SessionId) -> String {
+        format!("{}tombstone:{}", self.key_prefix, hex(id))
+    }
+
+    /// Returns all keys that match the given pattern after the key prefix.
+    async fn scan(&mut self, pattern: &str) -> Result<Vec<String>, RedisStoreError> {
+        let pattern = format!("{}{pattern}", self.key_prefix);
+        let mut iter = self.connection.scan_match::<_, String>(pattern).await?;
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+
+    async fn delete_keys(&mut self, keys: &[String]) -> Result<(), RedisStoreError> {
+        if !keys.is_empty() {
+            let _: usize = self.connection.del(keys).await?;
+        }
+        Ok(())
+    }
+}
+
+impl<SessionData: Serialize> RedisStore<SessionData> {
+    /// Serialise the given session and return it together with its expiry as argument for the Lua scripts.
+    fn serialise(
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<(String, String), RedisStoreError> {
+        let record = serde_json::to_string(&RecordRef {
+            expiry,
+            metadata,
+            data,
+        })?;
+        let expiry = match expiry {
+            SessionExpiry::DateTime(expiry) => expiry.timestamp_millis().to_string(),
+            SessionExpiry::Never => String::new(),
+        };
+        Ok((record, expiry))
+    }
+}
+
+#[async_trait]
+impl<SessionData: Serialize + DeserializeOwned + Send + Sync> SessionStoreConnector<SessionData>
+    for RedisStore<SessionData>
+{
+    type Error = RedisStoreError;
+
+    fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        self.maximum_retries_on_id_collision
+    }
+
+    /// Only errors that are known to occur before Redis executed a command are transient,
+    /// i.e. refused connections and replies that reject the command while the server is unavailable.
+    /// After e.g. a timeout or a dropped connection, a script may have been executed,
+    /// and retrying an update would then fail with [`Error::UpdatedSessionDoesNotExist`].
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        match error {
+            RedisStoreError::Redis(error) => {
+                error.is_connection_refusal()
+                    || matches!(
+                        error.kind(),
+                        ErrorKind::BusyLoadingError
+                            | ErrorKind::TryAgain
+                            | ErrorKind::ClusterDown
+                            | ErrorKind::MasterDown
+                    )
+            }
+            RedisStoreError::Serialisation(_) => false,
+        }
+    }
+
+    fn enforces_expiry(&self) -> bool {
+        true
+    }
+
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::new("RedisStore")
+    }
+
+    async fn create_session(
+        &mut self,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let (record, expiry) = Self::serialise(expiry, metadata, data)?;
+        let created: i32 = self
+            .create_session_script
+            .key(self.session_key(current_id))
+            .key(self.tombstone_key(current_id))
+            .arg(record)
+            .arg(expiry)
+            .invoke_async(&mut self.connection)
+            .await
+            .map_err(RedisStoreError::from)?;
+        if created == 1 {
+            Ok(WriteSessionResult::Ok(()))
+        } else {
+            Ok(WriteSessionResult::SessionIdExists)
+        }
+    }
+
+    async fn read_session(
+        &mut self,
+        id: SessionId,
+    ) -> Result<Option<Session<SessionData>>, Error<Self::Error>> {
+        let record: Option<String> = self
+            .connection
+            .get(self.session_key(&id))
+            .await
+            .map_err(RedisStoreError::from)?;
+        let Some(record) = record else {
+            return Ok(None);
+        };
+        let Record {
+            expiry,
+            metadata,
+            data,
+        } = serde_json::from_str(&record).map_err(RedisStoreError::from)?;
+        Ok(Some(Session::new_from_session_store(
+            id, expiry, metadata, data,
+        )))
+    }
+
+    async fn read_sessions(
+        &mut self,
+        ids: &[SessionId],
+    ) -> Result<Vec<Option<Session<SessionData>>>, Error<Self::Error>>
+    where
+        SessionData: Send,
+    {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let keys: Vec<_> = ids.iter().map(|id| self.session_key(id)).collect();
+        let records: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(keys)
+            .query_async(&mut self.connection)
+            .await
+            .map_err(RedisStoreError::from)?;
+
+        let mut sessions = Vec::with_capacity(ids.len());
+        for (id, record) in ids.iter().zip(records) {
+            let Some(record) = record else {
+                sessions.push(None);
+                continue;
+            };
+            let Record {
+                expiry,
+                metadata,
+                data,
+            } = serde_json::from_str(&record).map_err(RedisStoreError::from)?;
+            sessions.push(Some(Session::new_from_session_store(
+                id.clone(),
+                expiry,
+                metadata,
+                data,
+            )));
+        }
+        Ok(sessions)
+    }
+
+    async fn update_session(
+        &mut self,
+        current_id: &SessionId,
+        previous_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let (record, expiry) = Self::serialise(expiry, metadata, data)?;
+        let updated: i32 = self
+            .update_session_script
+            .key(self.session_key(current_id))
+            .key(self.tombstone_key(current_id))
+            .key(self.session_key(previous_id))
+            .arg(record)
+            .arg(expiry)
+            .invoke_async(&mut self.connection)
+            .await
+            .map_err(RedisStoreError::from)?;
+        match updated {
+            1 => Ok(WriteSessionResult::Ok(())),
+            0 => Ok(WriteSessionResult::SessionIdExists),
+            _ => Err(Error::UpdatedSessionDoesNotExist),
+        }
+    }
+
+    async fn delete_session(&mut self, id: &SessionId) -> Result<(), Error<Self::Error>> {
+        let key = self.session_key(id);
+        Ok(self.delete_keys(&[key]).await?)
+    }
+
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        let keys: Vec<_> = ids.iter().map(|id| self.session_key(id)).collect();
+        Ok(self.delete_keys(&keys).await?)
+    }
+
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        let _: i32 = self
+            .create_tombstone_script
+            .key(self.session_key(id))
+            .key(self.tombstone_key(id))
+            .arg(expiry.timestamp_millis())
+            .invoke_async(&mut self.connection)
+            .await
+            .map_err(RedisStoreError::from)?;
+        Ok(())
+    }
+
+    /// Redis deletes expired tombstones by itself, hence `now` is ignored.
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        let _ = now;
+        Ok(self
+            .connection
+            .exists(self.tombstone_key(id))
+            .await
+            .map_err(RedisStoreError::from)?)
+    }
+
+    /// Redis deletes expired sessions by itself, hence `now` is ignored.
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        let _ = now;
+        Ok(self
+            .connection
+            .exists(self.session_key(id))
+            .await
+            .map_err(RedisStoreError::from)?)
+    }
+
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        let (seconds, microseconds): (i64, u32) = redis::cmd("TIME")
+            .query_async(&mut self.connection)
+            .await
+            .map_err(RedisStoreError::from)?;
+        Ok(Utc.timestamp_opt(seconds, microseconds * 1000).single())
+    }
+
+    // Sessions and tombstones expire natively via their TTL.
+    async fn delete_expired_sessions(
+        &mut self,
+        _before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        Ok(0)
+    }
+
+    async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
+        let keys = self.scan("*").await?;
+        Ok(self.delete_keys(&keys).await?)
+    }
+
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        let mut keys = Vec::new();
+        for key in self.scan("session:*").await? {
+            let record: Option<String> = self
+                .connection
+                .get(&key)
+                .await
+                .map_err(RedisStoreError::from)?;
+            // The session may have expired in the meantime.
+            let Some(record) = record else {
+                continue;
+            };
+            let record: Record<serde::de::IgnoredAny> =
+                serde_json::from_str(&record).map_err(RedisStoreError::from)?;
+            if record.metadata.namespace.as_deref() == Some(namespace) {
+                keys.push(key);
+            }
+        }
+        Ok(self.delete_keys(&keys).await?)
+    }
+}
+
+impl<SessionData> Debug for RedisStore<SessionData> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisStore")
+            .field("key_prefix", &self.key_prefix)
+            .field(
+                "maximum_retries_on_id_collision",
+                &self.maximum_retries_on_id_collision,
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl<SessionData> Clone for RedisStore<SessionData> {
+    fn clone(&self) -> Self {
+        Self {
+            connection: self.connection.clone(),
+            key_prefix: self.key_prefix.clone(),
+            maximum_retries_on_id_collision: self.maximum_retries_on_id_collision,
+            create_session_script: self.create_session_script.clone(),
+            update_session_script: self.update_session_script.clone(),
+            create_tombstone_script: self.create_tombstone_script.clone(),
+            data: PhantomData,
+        }
+    }
+}
+
+fn hex(id: &SessionId) -> String {
+    id.as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
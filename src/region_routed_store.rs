@@ -0,0 +1,155 @@
+use crate::routed_store::{RoutedStore, Routing};
+use crate::{SessionId, UnknownRegion};
+use std::collections::HashSet;
+
+/// The number of points each region occupies on the hash ring.
+const VIRTUAL_NODES_PER_REGION: u32 = 64;
+
+/// A session store connector that routes each session to the connector of its home region.
+///
+/// The home region of a session is determined from its id by consistent hashing.
+/// If a region is marked as unavailable, its sessions are routed to the next region on the hash ring instead,
+/// while the sessions of all other regions keep their home region.
+///
+/// Updates are only performed within a single region, so the atomicity guarantees of
+/// [`SessionStoreConnector::update_session`](crate::SessionStoreConnector::update_session) are preserved.
+/// To achieve this, new ids that would belong to a different region than the previous id are not
+/// [accepted](crate::SessionStoreConnector::accepts_session_id), such that the session store generates a different id.
+/// Similarly, if a local region is set, new sessions are only created with ids that belong to the local region.
+/// This requires on average as many tries as there are available regions, which do not count as id collisions.
+pub type RegionRoutedStore<SessionStoreConnection> =
+    RoutedStore<RegionRouting, SessionStoreConnection>;
+
+/// The [`Routing`] of the [`RegionRoutedStore`].
+#[derive(Debug, Clone)]
+pub struct RegionRouting {
+    names: Vec<String>,
+    ring: Vec<(u64, usize)>,
+    unavailable_regions: HashSet<usize>,
+    local_region: Option<usize>,
+}
+
+impl RegionRouting {
+    fn region_index(&self, region: &str) -> Result<usize, UnknownRegion> {
+        self.names
+            .iter()
+            .position(|name| name == region)
+            .ok_or_else(|| UnknownRegion {
+                region: region.to_string(),
+            })
+    }
+}
+
+impl Routing for RegionRouting {
+    const NAME: &'static str = "RegionRoutedStore";
+
+    fn route(&self, id: &SessionId) -> Option<usize> {
+        let key = hash_prefix(blake3::hash(id.as_ref()).as_bytes());
+        let start = self.ring.partition_point(|(point, _)| *point < key);
+        self.ring[start..]
+            .iter()
+            .chain(&self.ring[..start])
+            .map(|(_, index)| *index)
+            .find(|index| !self.unavailable_regions.contains(index))
+    }
+
+    /// If a local region is set, only ids that belong to the local region are accepted.
+    fn accepts_new_session_id(&self, id: &SessionId) -> bool {
+        self.local_region
+            .is_none_or(|local_region| self.route(id) == Some(local_region))
+    }
+
+    fn is_available(&self, index: usize) -> bool {
+        !self.unavailable_regions.contains(&index)
+    }
+}
+
+impl<SessionStoreConnection> RegionRoutedStore<SessionStoreConnection> {
+    /// Route sessions to the given regions, identified by their names.
+    ///
+    /// The mapping from sessions to regions depends only on the names of the regions, not on their order.
+    ///
+    /// **Panics** if there are no regions or if region names are not unique.
+    pub fn new(
+        regions: impl IntoIterator<Item = (impl Into<String>, SessionStoreConnection)>,
+    ) -> Self {
+        let (names, connections): (Vec<String>, Vec<_>) = regions
+            .into_iter()
+            .map(|(name, connection)| (name.into(), connection))
+            .unzip();
+        assert!(!names.is_empty(), "there must be at least one region");
+        assert_eq!(
+            names.iter().collect::<HashSet<_>>().len(),
+            names.len(),
+            "region names must be unique"
+        );
+
+        let mut ring = Vec::new();
+        for (index, name) in names.iter().enumerate() {
+            for virtual_node in 0..VIRTUAL_NODES_PER_REGION {
+                let mut hasher = blake3::Hasher::new();
+                hasher.update(name.as_bytes());
+                hasher.update(&virtual_node.to_le_bytes());
+                ring.push((hash_prefix(hasher.finalize().as_bytes()), index));
+            }
+        }
+        ring.sort_unstable();
+
+        Self {
+            routing: RegionRouting {
+                names,
+                ring,
+                unavailable_regions: Default::default(),
+                local_region: None,
+            },
+            connections,
+        }
+    }
+
+    /// Only create new sessions with ids that belong to the given region, such that they can be read locally.
+    /// If `None`, new sessions are created in any region.
+    ///
+    /// Returns an error and keeps the previous local region if the region does not exist.
+    pub fn set_local_region(&mut self, region: Option<&str>) -> Result<(), UnknownRegion> {
+        self.routing.local_region = region
+            .map(|region| self.routing.region_index(region))
+            .transpose()?;
+        Ok(())
+    }
+
+    /// Mark the given region as available or unavailable.
+    /// Sessions of unavailable regions are routed to the next available region on the hash ring.
+    ///
+    /// Returns an error if the region does not exist.
+    pub fn set_region_available(
+        &mut self,
+        region: &str,
+        available: bool,
+    ) -> Result<(), UnknownRegion> {
+        let index = self.routing.region_index(region)?;
+        if available {
+            self.routing.unavailable_regions.remove(&index);
+        } else {
+            self.routing.unavailable_regions.insert(index);
+        }
+        Ok(())
+    }
+
+    /// Returns the name of the region the session with the given id is routed to,
+    /// or `None` if no region is available.
+    pub fn home_region(&self, id: &SessionId) -> Option<&str> {
+        self.routing
+            .route(id)
+            .map(|index| self.routing.names[index].as_str())
+    }
+
+    /// Returns the connector of the given region, or `None` if the region does not exist.
+    pub fn region(&self, region: &str) -> Option<&SessionStoreConnection> {
+        let index = self.routing.region_index(region).ok()?;
+        Some(&self.connections[index])
+    }
+}
+
+fn hash_prefix(hash: &[u8; blake3::OUT_LEN]) -> u64 {
+    u64::from_le_bytes(hash[..8].try_into().unwrap())
+}
@@ -0,0 +1,299 @@
+use crate::{
+    ConnectorDescription, Error, RequestContext, Session, SessionExpiry, SessionId,
+    SessionMetadata, SessionStoreConnector, WriteSessionResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// Decides which of the connectors of a [`RoutedStore`] holds a session.
+///
+/// This is the only part that differs between the [`ShardedStore`](crate::ShardedStore)
+/// and the [`RegionRoutedStore`](crate::RegionRoutedStore).
+pub trait Routing {
+    /// The name of the store, used in its [description](SessionStoreConnector::describe).
+    const NAME: &'static str;
+
+    /// Returns the index of the connector the session with the given id belongs to,
+    /// or `None` if no connector is available.
+    fn route(&self, id: &SessionId) -> Option<usize>;
+
+    /// Returns true if new sessions may be created with the given id.
+    ///
+    /// The default implementation returns true.
+    fn accepts_new_session_id(&self, id: &SessionId) -> bool {
+        let _ = id;
+        true
+    }
+
+    /// Returns true if the connector with the given index is available.
+    ///
+    /// The default implementation returns true.
+    fn is_available(&self, index: usize) -> bool {
+        let _ = index;
+        true
+    }
+}
+
+/// The positions and ids of the sessions of each connector.
+type Groups = Vec<(Vec<usize>, Vec<SessionId>)>;
+
+/// A session store connector that spreads sessions over multiple connectors according to its [`Routing`].
+///
+/// Updates are only performed within a single connector, so the atomicity guarantees of
+/// [`SessionStoreConnector::update_session`] are preserved.
+/// To achieve this, new ids that would belong to a different connector than the previous id are not
+/// [accepted](SessionStoreConnector::accepts_session_id), such that the session store generates a different id
+/// without counting an id collision. The same holds for new sessions with ids that the routing does not
+/// [accept](Routing::accepts_new_session_id).
+/// Writes with such ids are still rejected as id collisions, in case the session store did not check them.
+#[derive(Debug, Clone)]
+pub struct RoutedStore<Router, SessionStoreConnection> {
+    pub(crate) routing: Router,
+    pub(crate) connections: Vec<SessionStoreConnection>,
+}
+
+impl<Router: Routing, SessionStoreConnection> RoutedStore<Router, SessionStoreConnection> {
+    fn connection_mut<InnerError>(
+        &mut self,
+        id: &SessionId,
+    ) -> Result<&mut SessionStoreConnection, Error<InnerError>> {
+        let index = self.routing.route(id).ok_or(Error::NoAvailableRegion)?;
+        Ok(&mut self.connections[index])
+    }
+
+    /// Group the given ids by their connector, keeping their positions in `ids`.
+    fn group_by_connection<InnerError>(
+        &self,
+        ids: &[SessionId],
+    ) -> Result<Groups, Error<InnerError>> {
+        let mut groups = vec![(Vec::new(), Vec::new()); self.connections.len()];
+        for (position, id) in ids.iter().enumerate() {
+            let index = self.routing.route(id).ok_or(Error::NoAvailableRegion)?;
+            let (positions, ids) = &mut groups[index];
+            positions.push(position);
+            ids.push(id.clone());
+        }
+        Ok(groups)
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync,
+        Router: Routing + Send + Sync,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Sync + Send,
+    > SessionStoreConnector<SessionData> for RoutedStore<Router, SessionStoreConnection>
+{
+    type Error = SessionStoreConnection::Error;
+
+    /// Returns `None` if any connector has no limit, and otherwise the largest limit of all connectors.
+    fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        let mut maximum = 0u32;
+        for connection in &self.connections {
+            maximum = maximum.max(connection.maximum_retries_on_id_collision()?);
+        }
+        Some(maximum)
+    }
+
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        self.connections
+            .iter()
+            .any(|connection| connection.is_transient_error(error))
+    }
+
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::wrapping(
+            Router::NAME,
+            self.connections
+                .iter()
+                .map(|connection| connection.describe()),
+        )
+    }
+
+    /// Returns true if all connectors enforce expiry.
+    fn enforces_expiry(&self) -> bool {
+        self.connections
+            .iter()
+            .all(|connection| connection.enforces_expiry())
+    }
+
+    /// Accepts updated ids that belong to the same connector as the previous id,
+    /// and new ids that the routing [accepts](Routing::accepts_new_session_id),
+    /// if the connector they belong to accepts them as well.
+    fn accepts_session_id(&self, new_id: &SessionId, previous_id: Option<&SessionId>) -> bool {
+        let Some(index) = self.routing.route(new_id) else {
+            return true;
+        };
+        let routed = match previous_id {
+            Some(previous_id) => self.routing.route(previous_id) == Some(index),
+            None => self.routing.accepts_new_session_id(new_id),
+        };
+        routed && self.connections[index].accepts_session_id(new_id, previous_id)
+    }
+
+    async fn create_session(
+        &mut self,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        if !self.routing.accepts_new_session_id(current_id) {
+            return Ok(WriteSessionResult::SessionIdExists);
+        }
+
+        let connection = self.connection_mut(current_id)?;
+        connection
+            .create_session(current_id, expiry, metadata, data)
+            .await
+    }
+
+    async fn read_session(
+        &mut self,
+        id: SessionId,
+    ) -> Result<Option<Session<SessionData>>, Error<Self::Error>> {
+        let connection = self.connection_mut(&id)?;
+        connection.read_session(id).await
+    }
+
+    async fn read_sessions(
+        &mut self,
+        ids: &[SessionId],
+    ) -> Result<Vec<Option<Session<SessionData>>>, Error<Self::Error>>
+    where
+        SessionData: Send,
+    {
+        let mut sessions: Vec<_> = ids.iter().map(|_| None).collect();
+        let groups = self.group_by_connection(ids)?;
+        for (connection, (positions, ids)) in self.connections.iter_mut().zip(groups) {
+            if ids.is_empty() {
+                continue;
+            }
+            let read = connection.read_sessions(&ids).await?;
+            for (position, session) in positions.into_iter().zip(read) {
+                sessions[position] = session;
+            }
+        }
+        Ok(sessions)
+    }
+
+    async fn update_session(
+        &mut self,
+        current_id: &SessionId,
+        previous_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        if self.routing.route(current_id) != self.routing.route(previous_id) {
+            return Ok(WriteSessionResult::SessionIdExists);
+        }
+
+        let connection = self.connection_mut(previous_id)?;
+        connection
+            .update_session(current_id, previous_id, expiry, metadata, data)
+            .await
+    }
+
+    async fn delete_session(&mut self, id: &SessionId) -> Result<(), Error<Self::Error>> {
+        let connection = self.connection_mut(id)?;
+        connection.delete_session(id).await
+    }
+
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        let groups = self.group_by_connection(ids)?;
+        for (connection, (_, ids)) in self.connections.iter_mut().zip(groups) {
+            if !ids.is_empty() {
+                connection.delete_sessions(&ids).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        let connection = self.connection_mut(id)?;
+        connection.create_tombstone(id, expiry).await
+    }
+
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        let connection = self.connection_mut(id)?;
+        connection.is_tombstone(id, now).await
+    }
+
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        let connection = self.connection_mut(id)?;
+        connection.is_session_valid(id, now).await
+    }
+
+    /// Returns the time of the first available connector.
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        let index = (0..self.connections.len())
+            .find(|index| self.routing.is_available(*index))
+            .ok_or(Error::NoAvailableRegion)?;
+        self.connections[index].now().await
+    }
+
+    fn set_request_context(&mut self, context: Option<&RequestContext>) {
+        for connection in &mut self.connections {
+            connection.set_request_context(context);
+        }
+    }
+
+    async fn preload_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        let groups = self.group_by_connection(ids)?;
+        for (connection, (_, ids)) in self.connections.iter_mut().zip(groups) {
+            if !ids.is_empty() {
+                connection.preload_sessions(&ids).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the sum of the counts of all connectors, including unavailable ones.
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        let mut count = 0;
+        for connection in &mut self.connections {
+            count += connection.count_sessions().await?;
+        }
+        Ok(count)
+    }
+
+    /// Deletes the expired sessions of all connectors, including unavailable ones.
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        let mut deleted = 0;
+        for connection in &mut self.connections {
+            deleted += connection.delete_expired_sessions(before).await?;
+        }
+        Ok(deleted)
+    }
+
+    /// Clears all connectors, including unavailable ones.
+    async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
+        for connection in &mut self.connections {
+            connection.clear().await?;
+        }
+        Ok(())
+    }
+
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        for connection in &mut self.connections {
+            connection.clear_namespace(namespace).await?;
+        }
+        Ok(())
+    }
+}
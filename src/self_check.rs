@@ -0,0 +1,89 @@
+use crate::error::SelfCheckError;
+use crate::session_store::cookie_generator::{
+    DefaultSessionCookieGenerator, SessionCookieGenerator,
+};
+use crate::{CookieValue, SessionId};
+
+/// Verify the security-relevant computations of this crate against embedded test vectors.
+///
+/// This checks the blake3 implementation, the derivation of session ids from cookie values with and without key prefix,
+/// and that the [`DefaultSessionCookieGenerator`] produces distinct valid cookies with enough entropy.
+/// Security-sensitive deployments can call this at startup to assert that the cryptographic path
+/// was not broken by a dependency upgrade or an unusual target.
+///
+/// # Example
+///
+/// ```rust
+/// typed_session::self_check().expect("the session id derivation is broken");
+/// ```
+pub fn self_check() -> Result<(), SelfCheckError> {
+    check_blake3()?;
+    check_session_id_derivation()?;
+    check_cookie_generator()
+}
+
+/// The minimum number of bits of entropy of cookies generated by the [`DefaultSessionCookieGenerator`].
+const MINIMUM_COOKIE_ENTROPY_BITS: f64 = 128.0;
+
+fn check_blake3() -> Result<(), SelfCheckError> {
+    // From the official blake3 test vectors.
+    let vectors: [(&[u8], &str); 2] = [
+        (
+            b"",
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+        ),
+        (
+            b"abc",
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+        ),
+    ];
+    for (input, expected) in vectors {
+        if blake3::hash(input).to_hex().as_str() != expected {
+            return Err(SelfCheckError::Blake3);
+        }
+    }
+    Ok(())
+}
+
+fn check_session_id_derivation() -> Result<(), SelfCheckError> {
+    let cookie_value = CookieValue::from("0123456789abcdefghijklmnopqrstuv");
+    let vectors = [
+        (
+            SessionId::from_cookie_value(&cookie_value),
+            "a082a1594b22fa4f838fc819e1918b45a4f0727badaa701b6d521211ec992e03",
+        ),
+        (
+            SessionId::from_prefixed_cookie_value("auth:", &cookie_value),
+            "910be175aa4a179684dc5b7a3c02b889203fd5615f71d4414ebc5a3ac1cc0e31",
+        ),
+    ];
+    for (id, expected) in vectors {
+        let id = blake3::Hash::from(<[u8; blake3::OUT_LEN]>::try_from(id.as_ref()).unwrap());
+        if id.to_hex().as_str() != expected {
+            return Err(SelfCheckError::SessionIdDerivation);
+        }
+    }
+    Ok(())
+}
+
+fn check_cookie_generator() -> Result<(), SelfCheckError> {
+    // The default generator draws each character uniformly from 26 + 26 + 10 alphanumeric characters.
+    let entropy_bits =
+        DefaultSessionCookieGenerator::COOKIE_LENGTH as f64 * f64::from(26 + 26 + 10).log2();
+    if entropy_bits < MINIMUM_COOKIE_ENTROPY_BITS {
+        return Err(SelfCheckError::CookieGenerator);
+    }
+
+    let generator = DefaultSessionCookieGenerator;
+    let first = generator.generate_cookie();
+    let second = generator.generate_cookie();
+    if first == second
+        || [&first, &second].into_iter().any(|cookie| {
+            !generator.is_valid_cookie(cookie)
+                || !cookie.bytes().all(|byte| byte.is_ascii_alphanumeric())
+        })
+    {
+        return Err(SelfCheckError::CookieGenerator);
+    }
+    Ok(())
+}
@@ -1,7 +1,9 @@
+use crate::{ChildSession, DurationOutOfRange};
 use chrono::{DateTime, Duration, Utc};
-use secure_string::SecureArray;
-use std::fmt::Debug;
+use secure_string::{SecureArray, SecureString};
+use std::fmt::{Debug, Display, Formatter};
 use std::mem;
+use std::sync::Mutex;
 
 /// A session with a client.
 /// This type handles the creation, updating and deletion of sessions.
@@ -16,6 +18,39 @@ use std::mem;
 #[must_use]
 pub struct Session<SessionData, const COOKIE_LENGTH: usize = 32> {
     pub(crate) state: SessionState<SessionData>,
+    pub(crate) metadata: SessionMetadata,
+    audit: Option<AccessAudit>,
+    /// The cookie of this session if it should be emitted again when the session is stored without changes,
+    /// e.g. because the cookie settings changed since it was emitted.
+    pub(crate) reemitted_cookie: Option<CookieValue>,
+}
+
+/// An access to a session, as recorded by [`Session::enable_access_audit`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum SessionAccess {
+    /// The data was read with [`Session::data`].
+    Data,
+    /// The data was accessed mutably with [`Session::data_mut`], which marks the session as changed.
+    DataMut,
+    /// The expiry was changed, e.g. with [`Session::set_expiry`] or by the session renewal strategy.
+    ExpiryMut,
+    /// The metadata was changed, e.g. with [`Session::raise_assurance`], which marks the session as changed.
+    MetadataMut,
+    /// A new id was forced with [`Session::regenerate`].
+    Regenerate,
+    /// The session was marked for deletion with [`Session::delete`].
+    Delete,
+}
+
+/// The recorded accesses to a session.
+#[derive(Debug, Default)]
+struct AccessAudit(Mutex<Vec<SessionAccess>>);
+
+impl Clone for AccessAudit {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.0.lock().unwrap().clone()))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -53,9 +88,37 @@ pub(crate) enum SessionState<SessionData> {
     Invalid,
 }
 
+/// The kind of state a session is in, see [`Session::state_kind`].
+///
+/// This allows e.g. middleware to skip storing sessions that are [`Unchanged`](SessionStateKind::Unchanged).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum SessionStateKind {
+    /// The session was newly generated for this request, and at most the expiry was written to.
+    /// Storing it does nothing.
+    NewUnchanged,
+    /// The session was newly generated for this request, and the data was written to.
+    /// Storing it creates it in the session store and sets a cookie.
+    NewChanged,
+    /// The session was loaded from the session store, and was not changed.
+    /// Storing it does nothing.
+    Unchanged,
+    /// The session was loaded from the session store, and its data or expiry was changed.
+    /// Storing it updates it in the session store and sets a new cookie.
+    Changed,
+    /// The session was loaded from the session store and marked for deletion.
+    /// Storing it deletes it from the session store and deletes the cookie.
+    Deleted,
+    /// The session was marked for deletion before it was ever stored.
+    /// Storing it does nothing.
+    NewDeleted,
+}
+
 /// The expiry of a session.
 /// Either a given date and time, or never.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SessionExpiry {
     /// The session expires at the given date and time.
     DateTime(DateTime<Utc>),
@@ -63,19 +126,244 @@ pub enum SessionExpiry {
     Never,
 }
 
+impl SessionExpiry {
+    pub(crate) fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        match self {
+            SessionExpiry::DateTime(expiry) => *expiry < now,
+            SessionExpiry::Never => false,
+        }
+    }
+
+    /// Returns the value of the `Expires` attribute of a cookie with this expiry, formatted as HTTP date,
+    /// or `None` if the session never expires.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::SessionExpiry;
+    /// # use chrono::{TimeZone, Utc};
+    /// let expiry = SessionExpiry::DateTime(Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap());
+    /// assert_eq!(expiry.cookie_expires().unwrap(), "Wed, 21 Oct 2015 07:28:00 GMT");
+    /// assert_eq!(SessionExpiry::Never.cookie_expires(), None);
+    /// ```
+    pub fn cookie_expires(&self) -> Option<String> {
+        match self {
+            SessionExpiry::DateTime(expiry) => {
+                Some(expiry.format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+            }
+            SessionExpiry::Never => None,
+        }
+    }
+
+    /// Returns the value of the `Max-Age` attribute of a cookie with this expiry when sent at time `now`,
+    /// or `None` if the session never expires.
+    ///
+    /// The time is passed explicitly instead of reading the system clock, such that header values are deterministic,
+    /// e.g. when using the time of the [`ClockAuthority`](crate::ClockAuthority).
+    /// The remaining time is rounded up to whole seconds, such that the cookie never expires before the session,
+    /// and is zero if the session is already expired.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::SessionExpiry;
+    /// # use chrono::{Duration, TimeZone, Utc};
+    /// let now = Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+    /// let expiry = SessionExpiry::DateTime(now + Duration::milliseconds(1500));
+    /// assert_eq!(expiry.cookie_max_age(now), Some(2));
+    /// assert_eq!(expiry.cookie_max_age(now + Duration::hours(1)), Some(0));
+    /// assert_eq!(SessionExpiry::Never.cookie_max_age(now), None);
+    /// ```
+    pub fn cookie_max_age(&self, now: DateTime<Utc>) -> Option<u64> {
+        match self {
+            SessionExpiry::DateTime(expiry) => {
+                let remaining = (*expiry - now).max(Duration::zero());
+                let seconds = remaining.num_seconds();
+                let rounding = i64::from(remaining > Duration::seconds(seconds));
+                Some((seconds + rounding).unsigned_abs())
+            }
+            SessionExpiry::Never => None,
+        }
+    }
+}
+
+/// Returns `now + duration`, saturating at the earliest or latest representable date and time.
+pub(crate) fn saturating_add(now: DateTime<Utc>, duration: Duration) -> DateTime<Utc> {
+    now.checked_add_signed(duration)
+        .unwrap_or(if duration < Duration::zero() {
+            DateTime::<Utc>::MIN_UTC
+        } else {
+            DateTime::<Utc>::MAX_UTC
+        })
+}
+
+/// Returns `now + duration`, or an error if the result is not representable.
+pub(crate) fn checked_add_std(
+    now: DateTime<Utc>,
+    duration: std::time::Duration,
+) -> Result<DateTime<Utc>, DurationOutOfRange> {
+    Duration::from_std(duration)
+        .ok()
+        .and_then(|duration| now.checked_add_signed(duration))
+        .ok_or(DurationOutOfRange { now, duration })
+}
+
+/// Metadata of a session that is managed by the session store, as opposed to the session data that is managed by the user.
+///
+/// Session store connectors must persist the metadata together with the session.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionMetadata {
+    /// The assurance level of the session, see [`Session::raise_assurance`].
+    pub assurance: Option<Assurance>,
+    /// The user the session belongs to, see [`Session::set_user_id`].
+    ///
+    /// Connectors that implement [`UserIndexedSessionStoreConnector`](crate::UserIndexedSessionStoreConnector)
+    /// must index sessions by this field.
+    pub user_id: Option<String>,
+    /// The time the session was first stored, or `None` if it was stored by a version of this crate that did not record it.
+    pub created_at: Option<DateTime<Utc>>,
+    /// The blake3 hash of the channel binding value of the session, see [`Session::bind_to_channel`].
+    pub channel_binding: Option<[u8; blake3::OUT_LEN]>,
+    /// The namespace of the session store that created the session, i.e. its [key prefix](crate::SessionStore::set_key_prefix).
+    ///
+    /// Connectors must be able to delete all sessions of a namespace, see [`SessionStoreConnector::clear_namespace`](crate::SessionStoreConnector::clear_namespace).
+    pub namespace: Option<String>,
+    /// The fingerprint of the schema version of the session data when the session was last written,
+    /// see [`SessionStore::set_schema_version`](crate::SessionStore::set_schema_version).
+    pub schema_fingerprint: Option<[u8; blake3::OUT_LEN]>,
+    /// The [version of the cookie settings](crate::SessionStoreConfig::cookie_settings_version) when the session was last written,
+    /// or `None` if it was written by a version of this crate that did not record it.
+    pub cookie_settings_version: Option<u32>,
+    /// The priority of the session when it was last written,
+    /// see [`SessionStore::set_priority_classifier`](crate::SessionStore::set_priority_classifier).
+    pub priority: Option<SessionPriority>,
+    /// The number of times the expiry of the session was renewed by the
+    /// [renewal strategy](crate::SessionRenewalStrategy) when it was loaded,
+    /// or `None` if it was never renewed or only by a version of this crate that did not record it.
+    pub renewal_count: Option<u32>,
+    /// The number of times the session was updated, which stores it under a new id,
+    /// or `None` if it was never updated or only by a version of this crate that did not record it.
+    pub rotation_count: Option<u32>,
+    /// The time at which the session stops working, as scheduled with [`Session::delete_at`].
+    ///
+    /// The [renewal strategy](crate::SessionRenewalStrategy) never extends the expiry of the session beyond it.
+    pub deletion_deadline: Option<DateTime<Utc>>,
+}
+
+/// The assurance level of a session, i.e. how strongly the user of the session was authenticated.
+///
+/// Higher levels indicate stronger authentication.
+/// The meaning of each level is up to the user of this crate, e.g. `1` could mean that the user
+/// logged in with a password, and `2` that the user recently re-entered their password.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssuranceLevel(pub u8);
+
+/// The priority of a session, i.e. how valuable it is to keep the session under pressure.
+///
+/// Bounded connectors evict sessions with lower priority first.
+/// The meaning of each priority is up to the user of this crate, e.g. `0` could mean an anonymous session,
+/// and `1` an authenticated session.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionPriority(pub u8);
+
+/// An assurance level together with the time until which it is valid.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Assurance {
+    /// The assurance level.
+    pub level: AssuranceLevel,
+    /// The time until which the assurance level is valid.
+    pub valid_until: DateTime<Utc>,
+}
+
 /// The type of a session id.
 pub type SessionIdType = SecureArray<u8, { blake3::OUT_LEN }>;
 
+/// The value of a session cookie, i.e. the secret token that identifies a session to the client.
+///
+/// Unlike a [`SessionId`], which is derived from it and used by the backend, the cookie value is only ever
+/// known to the client and transmitted in cookies.
+/// It is zeroed when dropped, and its [`Debug`] output is redacted, such that it is not accidentally logged.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::CookieValue;
+/// let cookie_value = CookieValue::new("secret");
+/// assert_eq!(cookie_value.expose(), "secret");
+/// assert_eq!(format!("{cookie_value:?}"), "CookieValue(***SECRET***)");
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CookieValue(SecureString);
+
+impl CookieValue {
+    /// Wrap the given cookie value, e.g. as received from a client.
+    pub fn new(cookie_value: impl Into<String>) -> Self {
+        Self(cookie_value.into().into())
+    }
+
+    /// Returns the cookie value as a string, e.g. to send it to the client.
+    pub fn expose(&self) -> &str {
+        self.0.unsecure()
+    }
+
+    /// Returns the cookie value as a string, consuming it.
+    /// The returned string is not zeroed when dropped.
+    pub fn into_exposed(self) -> String {
+        self.0.into_unsecure()
+    }
+
+    /// Extract the value of the cookie with the given name from the values of `Cookie` headers.
+    #[cfg(any(feature = "tower", feature = "actix"))]
+    pub(crate) fn from_cookie_headers<'header>(
+        headers: impl IntoIterator<Item = &'header str>,
+        cookie_name: &str,
+    ) -> Option<Self> {
+        headers
+            .into_iter()
+            .flat_map(|header| header.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(name, _)| *name == cookie_name)
+            .map(|(_, value)| Self::new(value))
+    }
+}
+
+impl From<String> for CookieValue {
+    fn from(cookie_value: String) -> Self {
+        Self::new(cookie_value)
+    }
+}
+
+impl From<&str> for CookieValue {
+    fn from(cookie_value: &str) -> Self {
+        Self::new(cookie_value)
+    }
+}
+
 /// A session id.
+///
+/// The `Debug` output of a session id is redacted, so session ids never appear in formatted error values
+/// or in formatted logs like the [`Operation`](crate::Operation)s of a memory store.
+/// The full id is only available explicitly via [`AsRef<[u8]>`](AsRef), and a [`RedactedSessionId`]
+/// obtained from [`redacted`](SessionId::redacted) can be logged to correlate events of the same session.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct SessionId(Box<SessionIdType>);
 
+/// A short fingerprint of a [`SessionId`] that is safe to log, see [`SessionId::redacted`].
+///
+/// Both `Display` and `Debug` print the fingerprint as hexadecimal string.
+#[derive(Clone, Copy, Eq, PartialEq, Hash)]
+pub struct RedactedSessionId([u8; 4]);
+
 impl<SessionData, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE_LENGTH> {
     /// Extract the optionally associated data and expiry while consuming the session.
     ///
     /// **This function is supposed to be used in tests only.**
     /// This loses the association of the data to the actual session, making it useless for most
-    /// purposes.
+    /// purposes. Use [`SessionSnapshot`] for a lossless conversion.
     pub fn into_data_expiry_pair(self) -> (Option<SessionData>, Option<SessionExpiry>) {
         self.state.into_data_expiry_pair()
     }
@@ -99,6 +387,9 @@ impl<SessionData: Default, const COOKIE_LENGTH: usize> Session<SessionData, COOK
     pub fn new() -> Self {
         Self {
             state: SessionState::new(),
+            metadata: Default::default(),
+            audit: None,
+            reemitted_cookie: None,
         }
     }
 }
@@ -121,6 +412,9 @@ impl<SessionData, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE_LENGTH
     pub fn new_with_data(data: SessionData) -> Self {
         Self {
             state: SessionState::new_with_data(data),
+            metadata: Default::default(),
+            audit: None,
+            reemitted_cookie: None,
         }
     }
 
@@ -131,13 +425,94 @@ impl<SessionData, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE_LENGTH
     pub fn new_from_session_store(
         current_id: SessionId,
         expiry: SessionExpiry,
+        metadata: SessionMetadata,
         data: SessionData,
     ) -> Self {
         Self {
             state: SessionState::new_from_session_store(current_id, expiry, data),
+            metadata,
+            audit: None,
+            reemitted_cookie: None,
+        }
+    }
+
+    /// Returns the metadata of this session.
+    pub fn metadata(&self) -> &SessionMetadata {
+        &self.metadata
+    }
+
+    /// Start recording which accessors are called on this session, see [`access_audit`](Session::access_audit).
+    /// This helps to debug why a session was unexpectedly marked as changed, or why a change was not persisted.
+    ///
+    /// The audit is not persisted, and calling this again clears the recorded accesses.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::{Session, SessionAccess};
+    /// let mut session: Session<i32> = Session::new();
+    /// assert_eq!(session.access_audit(), None);
+    /// session.enable_access_audit();
+    /// let _ = session.data();
+    /// *session.data_mut() += 1;
+    /// assert_eq!(
+    ///     session.access_audit(),
+    ///     Some(vec![SessionAccess::Data, SessionAccess::DataMut])
+    /// );
+    /// ```
+    pub fn enable_access_audit(&mut self) {
+        self.audit = Some(AccessAudit::default());
+    }
+
+    /// Returns the accesses recorded since [`enable_access_audit`](Session::enable_access_audit) was called,
+    /// or `None` if it was not called.
+    pub fn access_audit(&self) -> Option<Vec<SessionAccess>> {
+        self.audit
+            .as_ref()
+            .map(|audit| audit.0.lock().unwrap().clone())
+    }
+
+    fn record(&self, access: SessionAccess) {
+        if let Some(audit) = &self.audit {
+            audit.0.lock().unwrap().push(access);
+        }
+    }
+
+    /// Spawn a new child session with the given data from this session.
+    ///
+    /// The child session is deleted together with this session.
+    /// It needs to be stored with [`SessionStore::store_child_session`](crate::SessionStore::store_child_session),
+    /// which requires a [`ChildSessionStoreConnector`](crate::ChildSessionStoreConnector).
+    ///
+    /// Returns `None` if this session was not loaded from the session store or was marked for deletion,
+    /// since in this case it cannot be the parent of another session.
+    pub fn spawn_child(&self, data: SessionData) -> Option<ChildSession<SessionData>> {
+        match &self.state {
+            SessionState::Unchanged { current_id, .. }
+            | SessionState::Changed { current_id, .. } => {
+                Some(ChildSession::new(current_id.clone(), data))
+            }
+            _ => None,
         }
     }
 
+    /// Returns the kind of state this session is in.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::{Session, SessionStateKind};
+    /// let mut session: Session<()> = Session::new();
+    /// assert_eq!(session.state_kind(), SessionStateKind::NewUnchanged);
+    /// session.data_mut();
+    /// assert_eq!(session.state_kind(), SessionStateKind::NewChanged);
+    /// session.delete();
+    /// assert_eq!(session.state_kind(), SessionStateKind::NewDeleted);
+    /// ```
+    pub fn state_kind(&self) -> SessionStateKind {
+        self.state.kind()
+    }
+
     /// Returns true if this session is marked for destruction.
     ///
     /// # Example
@@ -192,6 +567,36 @@ impl<SessionData, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE_LENGTH
 }
 
 impl<SessionData: Debug, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE_LENGTH> {
+    /// Marks this session as changed without changing its data or expiry, such that it gets a new id when it is stored.
+    pub(crate) fn mark_changed(&mut self) {
+        self.state.change_expiry();
+    }
+
+    /// Makes the session store emit the given cookie of this session again when it is stored without changes.
+    /// Unlike [`mark_changed`](Session::mark_changed), this keeps the id of the session.
+    pub(crate) fn reemit_cookie(&mut self, cookie_value: CookieValue) {
+        self.reemitted_cookie = Some(cookie_value);
+    }
+
+    /// Returns the id this session is stored under, or `None` if it was not loaded from the session store.
+    pub(crate) fn current_id(&self) -> Option<&SessionId> {
+        match &self.state {
+            SessionState::Unchanged { current_id, .. }
+            | SessionState::Changed { current_id, .. } => Some(current_id),
+            _ => None,
+        }
+    }
+
+    fn expiry_mut(&mut self) -> &mut SessionExpiry {
+        self.record(SessionAccess::ExpiryMut);
+        self.state.expiry_mut()
+    }
+
+    fn change_metadata(&mut self) {
+        self.record(SessionAccess::MetadataMut);
+        self.state.change_data();
+    }
+
     /// Returns the expiry timestamp of this session, if there is one.
     ///
     /// # Example
@@ -214,6 +619,7 @@ impl<SessionData: Debug, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE
     /// Returns a reference to the data associated with this session.
     /// This does not mark the session as changed.
     pub fn data(&self) -> &SessionData {
+        self.record(SessionAccess::Data);
         self.state.data()
     }
 
@@ -224,6 +630,7 @@ impl<SessionData: Debug, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE
     ///
     /// **Panics** if the session was marked for deletion before.
     pub fn data_mut(&mut self) -> &mut SessionData {
+        self.record(SessionAccess::DataMut);
         self.state.data_mut()
     }
 
@@ -242,11 +649,13 @@ impl<SessionData: Debug, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE
     /// assert!(session.is_deleted());
     /// # Ok(()) }) }
     pub fn delete(&mut self) {
+        self.record(SessionAccess::Delete);
         self.state.delete();
     }
 
     /// Forces the generation of a new id and cookie for this session, unless the session is new and its data was not accessed mutably.
     pub fn regenerate(&mut self) {
+        self.record(SessionAccess::Regenerate);
         // Calling this marks the state as changed, unless it is new and its data was not accessed mutably.
         self.state.change_expiry();
     }
@@ -266,7 +675,7 @@ impl<SessionData: Debug, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE
     /// # Ok(()) }) }
     /// ```
     pub fn set_expiry(&mut self, expiry: DateTime<Utc>) {
-        *self.state.expiry_mut() = SessionExpiry::DateTime(expiry);
+        *self.expiry_mut() = SessionExpiry::DateTime(expiry);
     }
 
     /// Sets this session to never expire.
@@ -286,7 +695,7 @@ impl<SessionData: Debug, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE
     /// # Ok(()) }) }
     /// ```
     pub fn do_not_expire(&mut self) {
-        *self.state.expiry_mut() = SessionExpiry::Never;
+        *self.expiry_mut() = SessionExpiry::Never;
     }
 
     /// Sets this session to expire `ttl` time into the future.
@@ -304,8 +713,206 @@ impl<SessionData: Debug, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE
     /// assert!(matches!(session.expiry(), SessionExpiry::DateTime { .. }));
     /// # Ok(()) }) }
     /// ```
+    ///
+    /// If `now + ttl` is not representable, the session is set to expire at the latest representable date and time.
+    /// Use [`try_expire_in`](Session::try_expire_in) to detect this case.
     pub fn expire_in(&mut self, now: DateTime<Utc>, ttl: std::time::Duration) {
-        *self.state.expiry_mut() = SessionExpiry::DateTime(now + Duration::from_std(ttl).unwrap());
+        *self.expiry_mut() =
+            SessionExpiry::DateTime(checked_add_std(now, ttl).unwrap_or(DateTime::<Utc>::MAX_UTC));
+    }
+
+    /// Sets this session to expire `ttl` time into the future.
+    /// Returns an error and leaves the session unchanged if `now + ttl` is not representable.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::{Session, SessionExpiry};
+    /// # use chrono::Utc;
+    /// let mut session: Session<()> = Session::new();
+    /// assert!(session.try_expire_in(Utc::now(), std::time::Duration::MAX).is_err());
+    /// assert_eq!(&SessionExpiry::Never, session.expiry());
+    /// assert!(session.try_expire_in(Utc::now(), std::time::Duration::from_secs(1)).is_ok());
+    /// assert!(matches!(session.expiry(), SessionExpiry::DateTime { .. }));
+    /// ```
+    pub fn try_expire_in(
+        &mut self,
+        now: DateTime<Utc>,
+        ttl: std::time::Duration,
+    ) -> Result<(), DurationOutOfRange> {
+        *self.expiry_mut() = SessionExpiry::DateTime(checked_add_std(now, ttl)?);
+        Ok(())
+    }
+
+    /// Schedules this session to stop working at the given date and time.
+    ///
+    /// This shortens the expiry of the session to `deletion_time`, unless it already expires earlier.
+    /// In contrast to [`delete`](Session::delete), the session continues to work until then.
+    /// The deletion time is recorded as the [deletion deadline](SessionMetadata::deletion_deadline) of the session,
+    /// such that the [renewal strategy](crate::SessionRenewalStrategy) never extends the expiry beyond it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::Session;
+    /// # fn main() -> Result<(), typed_session::Error<()>> { use chrono::{Duration, Utc};
+    /// # use typed_session::SessionExpiry;
+    /// # async_std::task::block_on(async {
+    /// let now = Utc::now();
+    /// let mut session: Session<()> = Session::new();
+    /// session.delete_at(now + Duration::hours(1));
+    /// assert_eq!(&SessionExpiry::DateTime(now + Duration::hours(1)), session.expiry());
+    /// // Deletion is never postponed.
+    /// session.delete_at(now + Duration::hours(2));
+    /// assert_eq!(&SessionExpiry::DateTime(now + Duration::hours(1)), session.expiry());
+    /// # Ok(()) }) }
+    /// ```
+    pub fn delete_at(&mut self, deletion_time: DateTime<Utc>) {
+        if self
+            .metadata
+            .deletion_deadline
+            .is_some_and(|deadline| deadline <= deletion_time)
+        {
+            return;
+        }
+
+        let expiry = self.expiry_mut();
+        if SessionExpiry::DateTime(deletion_time) < *expiry {
+            *expiry = SessionExpiry::DateTime(deletion_time);
+        }
+        self.metadata.deletion_deadline = Some(deletion_time);
+    }
+
+    /// Schedules this session to stop working `ttl` time into the future.
+    /// See [`delete_at`](Session::delete_at) for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::Session;
+    /// # fn main() -> Result<(), typed_session::Error<()>> { use chrono::Utc;
+    /// # use typed_session::SessionExpiry;
+    /// # async_std::task::block_on(async {
+    /// let mut session: Session<()> = Session::new();
+    /// session.delete_after(Utc::now(), std::time::Duration::from_secs(3600));
+    /// assert!(matches!(session.expiry(), SessionExpiry::DateTime { .. }));
+    /// # Ok(()) }) }
+    /// ```
+    ///
+    /// If `now + ttl` is not representable, the session is left unchanged.
+    pub fn delete_after(&mut self, now: DateTime<Utc>, ttl: std::time::Duration) {
+        if let Ok(deletion_time) = checked_add_std(now, ttl) {
+            self.delete_at(deletion_time);
+        }
+    }
+
+    /// Raises the assurance level of this session to `level` for the time `valid_for`, e.g. after the user re-entered their password.
+    /// After that time, the assurance level decays, and the session has no assurance level anymore.
+    ///
+    /// Raising the assurance level marks the session as changed, such that it gets a new id when it is stored.
+    /// If `now + valid_for` is not representable, the assurance level never decays.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::{AssuranceLevel, Session};
+    /// # use chrono::{Duration, Utc};
+    /// let now = Utc::now();
+    /// let mut session: Session<()> = Session::new();
+    /// assert_eq!(None, session.assurance_level(now));
+    /// session.raise_assurance(AssuranceLevel(2), now, std::time::Duration::from_secs(300));
+    /// assert_eq!(Some(AssuranceLevel(2)), session.assurance_level(now));
+    /// assert!(session.has_assurance(AssuranceLevel(1), now));
+    /// assert_eq!(None, session.assurance_level(now + Duration::minutes(10)));
+    /// ```
+    pub fn raise_assurance(
+        &mut self,
+        level: AssuranceLevel,
+        now: DateTime<Utc>,
+        valid_for: std::time::Duration,
+    ) {
+        self.change_metadata();
+        self.metadata.assurance = Some(Assurance {
+            level,
+            valid_until: checked_add_std(now, valid_for).unwrap_or(DateTime::<Utc>::MAX_UTC),
+        });
+    }
+
+    /// Returns the assurance level of this session at time `now`, or `None` if it has none or it has decayed.
+    pub fn assurance_level(&self, now: DateTime<Utc>) -> Option<AssuranceLevel> {
+        self.metadata
+            .assurance
+            .filter(|assurance| assurance.valid_until > now)
+            .map(|assurance| assurance.level)
+    }
+
+    /// Returns true if the assurance level of this session at time `now` is at least `level`.
+    pub fn has_assurance(&self, level: AssuranceLevel, now: DateTime<Utc>) -> bool {
+        self.assurance_level(now)
+            .map(|current| current >= level)
+            .unwrap_or(false)
+    }
+
+    /// Sets the user this session belongs to, or `None` if it does not belong to a user.
+    /// This allows to find all sessions of a user, e.g. with [`SessionStore::erase_user_data`](crate::SessionStore::erase_user_data).
+    ///
+    /// Setting the user marks the session as changed, such that it gets a new id when it is stored.
+    /// This is usually desired anyways, since the user is typically set on login.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::Session;
+    /// let mut session: Session<()> = Session::new();
+    /// assert_eq!(None, session.user_id());
+    /// session.set_user_id(Some("alice"));
+    /// assert_eq!(Some("alice"), session.user_id());
+    /// ```
+    pub fn set_user_id(&mut self, user_id: Option<impl Into<String>>) {
+        self.change_metadata();
+        self.metadata.user_id = user_id.map(Into::into);
+    }
+
+    /// Returns the user this session belongs to, if any.
+    pub fn user_id(&self) -> Option<&str> {
+        self.metadata.user_id.as_deref()
+    }
+
+    /// Returns how many times the expiry of this session was renewed by the
+    /// [renewal strategy](crate::SessionRenewalStrategy), see [`SessionMetadata::renewal_count`].
+    pub fn renewal_count(&self) -> u32 {
+        self.metadata.renewal_count.unwrap_or(0)
+    }
+
+    /// Returns how many times this session was stored under a new id, see [`SessionMetadata::rotation_count`].
+    ///
+    /// This allows policies like requiring the user to authenticate again after a number of rotations.
+    pub fn rotation_count(&self) -> u32 {
+        self.metadata.rotation_count.unwrap_or(0)
+    }
+
+    /// Binds this session to a channel binding value, e.g. a TLS exporter.
+    /// Only a hash of the value is stored with the session.
+    ///
+    /// When the session is loaded, the channel binding value of the current connection must be supplied to
+    /// [`SessionStore::load_session_with_channel_binding`](crate::SessionStore::load_session_with_channel_binding),
+    /// otherwise the [`ChannelBindingPolicy`](crate::ChannelBindingPolicy) of the session store is applied.
+    ///
+    /// Binding the session marks it as changed, such that it gets a new id when it is stored.
+    pub fn bind_to_channel(&mut self, channel_binding: &[u8]) {
+        self.change_metadata();
+        self.metadata.channel_binding = Some(blake3::hash(channel_binding).into());
+    }
+
+    /// Returns true if this session is bound to the given channel binding value,
+    /// or if it is not bound to any channel.
+    pub fn matches_channel(&self, channel_binding: Option<&[u8]>) -> bool {
+        match (self.metadata.channel_binding, channel_binding) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            // Comparing blake3 hashes is constant-time.
+            (Some(expected), Some(actual)) => blake3::Hash::from(expected) == blake3::hash(actual),
+        }
     }
 
     /// Return true if the session is expired.
@@ -316,24 +923,19 @@ impl<SessionData: Debug, const COOKIE_LENGTH: usize> Session<SessionData, COOKIE
     /// ```rust
     /// # use typed_session::Session;
     /// # use std::time::Duration;
-    /// # use async_std::task;
     /// # fn main() -> Result<(), typed_session::Error<()>> { use chrono::Utc;
     /// # use typed_session::SessionExpiry;
-    /// # async_std::task::block_on(async {
+    /// let now = Utc::now();
     /// let mut session: Session<()> = Session::new();
     /// assert_eq!(&SessionExpiry::Never, session.expiry());
-    /// assert!(!session.is_expired(Utc::now()));
-    /// session.expire_in(Utc::now(), Duration::from_secs(1));
-    /// assert!(!session.is_expired(Utc::now()));
-    /// task::sleep(Duration::from_secs(2)).await;
-    /// assert!(session.is_expired(Utc::now()));
-    /// # Ok(()) }) }
+    /// assert!(!session.is_expired(now));
+    /// session.expire_in(now, Duration::from_secs(1));
+    /// assert!(!session.is_expired(now));
+    /// assert!(session.is_expired(now + chrono::Duration::seconds(2)));
+    /// # Ok(()) }
     /// ```
     pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
-        match self.state.expiry() {
-            SessionExpiry::DateTime(expiry) => *expiry < now,
-            SessionExpiry::Never => false,
-        }
+        self.state.expiry().is_expired(now)
     }
 
     /// Returns the duration from now to the expiry time of this session.
@@ -417,6 +1019,18 @@ impl<SessionData> SessionState<SessionData> {
         self.is_changed() || self.is_deleted()
     }
 
+    fn kind(&self) -> SessionStateKind {
+        match self {
+            Self::NewUnchanged { .. } => SessionStateKind::NewUnchanged,
+            Self::NewChanged { .. } => SessionStateKind::NewChanged,
+            Self::Unchanged { .. } => SessionStateKind::Unchanged,
+            Self::Changed { .. } => SessionStateKind::Changed,
+            Self::Deleted { .. } => SessionStateKind::Deleted,
+            Self::NewDeleted => SessionStateKind::NewDeleted,
+            Self::Invalid => unreachable!("Invalid state is used internally only"),
+        }
+    }
+
     fn into_data_expiry_pair(self) -> (Option<SessionData>, Option<SessionExpiry>) {
         match self {
             SessionState::NewUnchanged { data, expiry }
@@ -575,13 +1189,93 @@ impl SessionId {
     /// Applies a cryptographic hash function on a cookie value to obtain the session id for that cookie.
     ///
     /// This is automatically done by the [`SessionStore`](crate::SessionStore), and this function is only public for test purposes.
-    pub fn from_cookie_value(cookie_value: &str) -> Self {
+    pub fn from_cookie_value(cookie_value: &CookieValue) -> Self {
         // The original code used base64 encoded binary ids of length of a multiple of the blake3 block size.
         // We do the same, but instead of base64 encoding a binary ids, we use normal alphanumerical ids with a length multiple of the blake3 block size.
         // This gives less entropy, but still more than enough to be secure (see crate-level documentation).
-        let hash = blake3::hash(cookie_value.as_bytes());
+        let hash = blake3::hash(cookie_value.expose().as_bytes());
         Self(Box::new((<[u8; blake3::OUT_LEN]>::from(hash)).into()))
     }
+
+    /// Applies a cryptographic hash function on a key prefix and a cookie value to obtain the session id for that cookie.
+    /// Different key prefixes result in different session ids for the same cookie value.
+    ///
+    /// This is automatically done by the [`SessionStore`](crate::SessionStore) if it has a key prefix,
+    /// and this function is only public for test purposes.
+    pub fn from_prefixed_cookie_value(key_prefix: &str, cookie_value: &CookieValue) -> Self {
+        // Prepend the length of the prefix, such that the boundary between prefix and cookie value is unambiguous.
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&(key_prefix.len() as u64).to_le_bytes());
+        hasher.update(key_prefix.as_bytes());
+        hasher.update(cookie_value.expose().as_bytes());
+        Self(Box::new(
+            (<[u8; blake3::OUT_LEN]>::from(hasher.finalize())).into(),
+        ))
+    }
+
+    /// Derives the id under which a backend remembers the session created with the given idempotency `token`.
+    ///
+    /// The derivation is domain-separated from session ids, such that a token never identifies a session.
+    pub(crate) fn from_creation_token(key_prefix: Option<&str>, token: &str) -> Self {
+        let mut hasher =
+            blake3::Hasher::new_derive_key("typed-session idempotent session creation token");
+        let key_prefix = key_prefix.unwrap_or_default();
+        hasher.update(&(key_prefix.len() as u64).to_le_bytes());
+        hasher.update(key_prefix.as_bytes());
+        hasher.update(token.as_bytes());
+        Self(Box::new(
+            (<[u8; blake3::OUT_LEN]>::from(hasher.finalize())).into(),
+        ))
+    }
+
+    /// Returns a short fingerprint of this id, which can be logged e.g. to correlate error values of the same session.
+    ///
+    /// The fingerprint is derived with a hash function instead of taking a prefix of the id,
+    /// since under [`HashingPolicy::Never`](crate::HashingPolicy::Never) the id is the cookie value itself.
+    /// Different sessions may have the same fingerprint.
+    pub fn redacted(&self) -> RedactedSessionId {
+        let mut hasher = blake3::Hasher::new_derive_key("typed-session redacted session id");
+        hasher.update(self.as_ref());
+        let mut fingerprint = [0; 4];
+        fingerprint.copy_from_slice(&hasher.finalize().as_bytes()[..4]);
+        RedactedSessionId(fingerprint)
+    }
+
+    /// Uses the bytes of a cookie value directly as session id, without hashing them.
+    ///
+    /// This is done by the [`SessionStore`](crate::SessionStore) if it is configured with
+    /// [`HashingPolicy::Never`](crate::HashingPolicy::Never), and this function is only public for test purposes.
+    ///
+    /// **Panics** if the cookie value is not exactly [`blake3::OUT_LEN`] bytes long.
+    pub fn from_unhashed_cookie_value(cookie_value: &CookieValue) -> Self {
+        let cookie_value = cookie_value.expose();
+        let id = <[u8; blake3::OUT_LEN]>::try_from(cookie_value.as_bytes()).unwrap_or_else(|_| {
+            panic!(
+                "unhashed cookie values must have length {}, but got length {}",
+                blake3::OUT_LEN,
+                cookie_value.len()
+            )
+        });
+        Self(Box::new(id.into()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SessionId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.as_ref())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SessionId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = <Vec<u8>>::deserialize(deserializer)?;
+        let id = <[u8; blake3::OUT_LEN]>::try_from(bytes.as_slice()).map_err(|_| {
+            serde::de::Error::invalid_length(bytes.len(), &"the length of a session id")
+        })?;
+        Ok(Self(Box::new(id.into())))
+    }
 }
 
 impl AsRef<[u8]> for SessionId {
@@ -590,8 +1284,196 @@ impl AsRef<[u8]> for SessionId {
     }
 }
 
+impl Display for RedactedSessionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for RedactedSessionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RedactedSessionId({self})")
+    }
+}
+
 impl From<SessionId> for SessionIdType {
     fn from(id: SessionId) -> Self {
         *id.0
     }
 }
+
+/// A plain representation of a [`Session`], including its state and id.
+///
+/// Unlike [`Session::into_data_expiry_pair`], the conversion from and to a [`Session`] is lossless.
+/// This allows to hand over in-flight sessions between processes, e.g. between workers with sticky sessions.
+/// With the `serde` feature, snapshots can be serialised.
+///
+/// **Security:** a snapshot of a session that was loaded from the session store contains its session id.
+/// Like the session store itself, serialised snapshots must therefore be protected from attackers.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{Session, SessionSnapshot, SessionStateKind};
+/// let mut session: Session<i32> = Session::new();
+/// *session.data_mut() = 4;
+/// let snapshot = SessionSnapshot::from(session);
+/// assert_eq!(snapshot.kind(), SessionStateKind::NewChanged);
+/// let session = Session::<i32>::from(snapshot);
+/// assert_eq!(session.state_kind(), SessionStateKind::NewChanged);
+/// assert_eq!(*session.data(), 4);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionSnapshot<SessionData> {
+    /// The state of the session.
+    pub state: SessionSnapshotState<SessionData>,
+    /// The metadata of the session.
+    pub metadata: SessionMetadata,
+}
+
+/// The state of a [`SessionSnapshot`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SessionSnapshotState<SessionData> {
+    /// See [`SessionStateKind::NewUnchanged`].
+    NewUnchanged {
+        /// The expiry of the session.
+        expiry: SessionExpiry,
+        /// The data of the session.
+        data: SessionData,
+    },
+    /// See [`SessionStateKind::NewChanged`].
+    NewChanged {
+        /// The expiry of the session.
+        expiry: SessionExpiry,
+        /// The data of the session.
+        data: SessionData,
+    },
+    /// See [`SessionStateKind::Unchanged`].
+    Unchanged {
+        /// The id under which the session is stored.
+        id: SessionId,
+        /// The expiry of the session.
+        expiry: SessionExpiry,
+        /// The data of the session.
+        data: SessionData,
+    },
+    /// See [`SessionStateKind::Changed`].
+    Changed {
+        /// The id under which the session is stored.
+        id: SessionId,
+        /// The expiry of the session.
+        expiry: SessionExpiry,
+        /// The data of the session.
+        data: SessionData,
+    },
+    /// See [`SessionStateKind::Deleted`].
+    Deleted {
+        /// The id under which the session is stored.
+        id: SessionId,
+    },
+    /// See [`SessionStateKind::NewDeleted`].
+    NewDeleted,
+}
+
+impl<SessionData> SessionSnapshot<SessionData> {
+    /// Returns the kind of state of the snapshotted session.
+    pub fn kind(&self) -> SessionStateKind {
+        match &self.state {
+            SessionSnapshotState::NewUnchanged { .. } => SessionStateKind::NewUnchanged,
+            SessionSnapshotState::NewChanged { .. } => SessionStateKind::NewChanged,
+            SessionSnapshotState::Unchanged { .. } => SessionStateKind::Unchanged,
+            SessionSnapshotState::Changed { .. } => SessionStateKind::Changed,
+            SessionSnapshotState::Deleted { .. } => SessionStateKind::Deleted,
+            SessionSnapshotState::NewDeleted => SessionStateKind::NewDeleted,
+        }
+    }
+}
+
+impl<SessionData, const COOKIE_LENGTH: usize> From<Session<SessionData, COOKIE_LENGTH>>
+    for SessionSnapshot<SessionData>
+{
+    fn from(session: Session<SessionData, COOKIE_LENGTH>) -> Self {
+        let state = match session.state {
+            SessionState::NewUnchanged { expiry, data } => {
+                SessionSnapshotState::NewUnchanged { expiry, data }
+            }
+            SessionState::NewChanged { expiry, data } => {
+                SessionSnapshotState::NewChanged { expiry, data }
+            }
+            SessionState::Unchanged {
+                current_id,
+                expiry,
+                data,
+            } => SessionSnapshotState::Unchanged {
+                id: current_id,
+                expiry,
+                data,
+            },
+            SessionState::Changed {
+                current_id,
+                expiry,
+                data,
+            } => SessionSnapshotState::Changed {
+                id: current_id,
+                expiry,
+                data,
+            },
+            SessionState::Deleted { current_id } => {
+                SessionSnapshotState::Deleted { id: current_id }
+            }
+            SessionState::NewDeleted => SessionSnapshotState::NewDeleted,
+            SessionState::Invalid => unreachable!("Invalid state is used internally only"),
+        };
+        Self {
+            state,
+            metadata: session.metadata,
+        }
+    }
+}
+
+impl<SessionData, const COOKIE_LENGTH: usize> From<SessionSnapshot<SessionData>>
+    for Session<SessionData, COOKIE_LENGTH>
+{
+    fn from(snapshot: SessionSnapshot<SessionData>) -> Self {
+        let state = match snapshot.state {
+            SessionSnapshotState::NewUnchanged { expiry, data } => {
+                SessionState::NewUnchanged { expiry, data }
+            }
+            SessionSnapshotState::NewChanged { expiry, data } => {
+                SessionState::NewChanged { expiry, data }
+            }
+            SessionSnapshotState::Unchanged { id, expiry, data } => SessionState::Unchanged {
+                current_id: id,
+                expiry,
+                data,
+            },
+            SessionSnapshotState::Changed { id, expiry, data } => SessionState::Changed {
+                current_id: id,
+                expiry,
+                data,
+            },
+            SessionSnapshotState::Deleted { id } => SessionState::Deleted { current_id: id },
+            SessionSnapshotState::NewDeleted => SessionState::NewDeleted,
+        };
+        Self {
+            state,
+            metadata: snapshot.metadata,
+            audit: None,
+            reemitted_cookie: None,
+        }
+    }
+}
+
+impl SessionMetadata {
+    /// Removes all parts of the metadata that are expired at time `now`.
+    pub(crate) fn decay(&mut self, now: DateTime<Utc>) {
+        if matches!(self.assurance, Some(assurance) if assurance.valid_until <= now) {
+            self.assurance = None;
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{Error, SessionStoreConnector, Sleeper, TokioSleeper};
+use chrono::Utc;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// A background job that periodically deletes expired sessions from the backend,
+/// available under the feature flag `cleaner`.
+///
+/// The session store never returns expired sessions, but it does not delete them either.
+/// The cleaner calls [`SessionStoreConnector::delete_expired_sessions`] with the current time of the backend
+/// if it has one, see [`SessionStoreConnector::now`], and with the system time otherwise.
+///
+/// The cleaner runs on the tokio runtime, or on any other runtime with [`run_with_sleeper`](SessionCleaner::run_with_sleeper).
+///
+/// # Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use typed_session::{MemoryStore, SessionCleaner};
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let connection: MemoryStore<u64, _> = MemoryStore::new();
+/// let cleaner = SessionCleaner::new(connection.clone());
+/// let handle = tokio::spawn(cleaner.run(Duration::from_secs(60 * 60)));
+/// # handle.abort();
+/// # }
+/// ```
+pub struct SessionCleaner<SessionData, SessionStoreConnection> {
+    connection: SessionStoreConnection,
+    data: PhantomData<fn() -> SessionData>,
+}
+
+impl<SessionData, SessionStoreConnection: SessionStoreConnector<SessionData> + Send>
+    SessionCleaner<SessionData, SessionStoreConnection>
+{
+    /// Create a new cleaner that deletes expired sessions through the given connection.
+    pub fn new(connection: SessionStoreConnection) -> Self {
+        Self {
+            connection,
+            data: PhantomData,
+        }
+    }
+
+    /// Delete all sessions that are expired now, and return the number of deleted sessions.
+    pub async fn clean(&mut self) -> Result<u64, Error<SessionStoreConnection::Error>> {
+        let now = self.connection.now().await?.unwrap_or_else(Utc::now);
+        self.connection.delete_expired_sessions(now).await
+    }
+
+    /// Delete expired sessions every `interval`, starting immediately. This future never completes.
+    ///
+    /// Errors are logged and do not stop the cleaner, such that it recovers from temporary backend outages.
+    /// If a run takes longer than `interval`, the next run is delayed accordingly.
+    pub async fn run(self, interval: Duration) {
+        self.run_with_sleeper(interval, TokioSleeper).await
+    }
+
+    /// Like [`run`](SessionCleaner::run), but waits between runs with the given [`Sleeper`],
+    /// such that the cleaner can run on any async runtime.
+    pub async fn run_with_sleeper(mut self, interval: Duration, sleeper: impl Sleeper) {
+        loop {
+            let start = Instant::now();
+            match self.clean().await {
+                Ok(deleted) => debug!("Deleted {deleted} expired sessions"),
+                Err(error) => warn!("Could not delete expired sessions: {error:?}"),
+            }
+            sleeper
+                .sleep(interval.saturating_sub(start.elapsed()))
+                .await;
+        }
+    }
+}
+
+impl<SessionData, SessionStoreConnection: Debug> Debug
+    for SessionCleaner<SessionData, SessionStoreConnection>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionCleaner")
+            .field("connection", &self.connection)
+            .finish()
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{
+    CookieValue, DefaultSessionCookieGenerator, Session, SessionCookieGenerator, SessionStore,
+    SessionStoreConnector,
+};
+use http::header::{COOKIE, SET_COOKIE};
+use http::{HeaderValue, Request, Response, StatusCode};
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::{debug, error};
+
+/// A [tower](https://crates.io/crates/tower) middleware layer that manages the session of each request,
+/// available under the feature flag `tower`.
+///
+/// For each request, the wrapped service:
+///  1. extracts the session cookie named in the [`CookieSettings`](crate::CookieSettings) of the session store,
+///  2. loads the session with [`SessionStore::load_session`], or creates a new session with default data if there is none,
+///  3. inserts the [`Session`] into the extensions of the request,
+///  4. calls the inner service, and
+///  5. stores the session with [`SessionStore::store_session`] and performs the returned
+///     [`SessionCookieCommand`](crate::SessionCookieCommand) by setting the `Set-Cookie` header of the response.
+///
+/// To modify the session, the handler takes it out of the request extensions and returns it in the extensions of the response,
+/// e.g. with axum's `Extension`.
+/// If the response carries no session, the session is stored as it was loaded, such that it is still renewed.
+///
+/// The connection is cloned for each request, so it should be a cheap handle like a connection pool.
+/// Malformed session cookies are treated like cookies of missing sessions, such that the new session overwrites them.
+/// If loading or storing the session fails otherwise, the error is logged and the response has status
+/// `500 Internal Server Error`.
+///
+/// # Example
+///
+/// ```rust
+/// # use axum::routing::get;
+/// # use axum::{Extension, Router};
+/// # use typed_session::{MemoryStore, Session, SessionLayer, SessionRenewalStrategy, SessionStore};
+/// async fn visit(Extension(mut session): Extension<Session<u64>>) -> (Extension<Session<u64>>, String) {
+///     *session.data_mut() += 1;
+///     let visits = session.data().to_string();
+///     (Extension(session), visits)
+/// }
+///
+/// let store: SessionStore<u64, _> = SessionStore::new(SessionRenewalStrategy::Ignore);
+/// let app: Router = Router::new()
+///     .route("/", get(visit))
+///     .layer(SessionLayer::new(store, MemoryStore::new()));
+/// ```
+pub struct SessionLayer<
+    SessionData,
+    SessionStoreConnection,
+    CookieGenerator = DefaultSessionCookieGenerator,
+> {
+    store: Arc<SessionStore<SessionData, SessionStoreConnection, CookieGenerator>>,
+    connection: SessionStoreConnection,
+}
+
+/// The service created by a [`SessionLayer`].
+pub struct SessionService<
+    Inner,
+    SessionData,
+    SessionStoreConnection,
+    CookieGenerator = DefaultSessionCookieGenerator,
+> {
+    inner: Inner,
+    store: Arc<SessionStore<SessionData, SessionStoreConnection, CookieGenerator>>,
+    connection: SessionStoreConnection,
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator>
+    SessionLayer<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Create a new layer that manages sessions with the given session store and connection.
+    pub fn new(
+        store: SessionStore<SessionData, SessionStoreConnection, CookieGenerator>,
+        connection: SessionStoreConnection,
+    ) -> Self {
+        Self {
+            store: Arc::new(store),
+            connection,
+        }
+    }
+}
+
+impl<Inner, SessionData, SessionStoreConnection: Clone, CookieGenerator> Layer<Inner>
+    for SessionLayer<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    type Service = SessionService<Inner, SessionData, SessionStoreConnection, CookieGenerator>;
+
+    fn layer(&self, inner: Inner) -> Self::Service {
+        SessionService {
+            inner,
+            store: self.store.clone(),
+            connection: self.connection.clone(),
+        }
+    }
+}
+
+impl<Inner, RequestBody, ResponseBody, SessionData, SessionStoreConnection, CookieGenerator>
+    Service<Request<RequestBody>>
+    for SessionService<Inner, SessionData, SessionStoreConnection, CookieGenerator>
+where
+    Inner:
+        Service<Request<RequestBody>, Response = Response<ResponseBody>> + Clone + Send + 'static,
+    Inner::Future: Send,
+    RequestBody: Send + 'static,
+    ResponseBody: Default + Send,
+    SessionData: Debug + Default + Clone + Send + Sync + 'static,
+    SessionStoreConnection: SessionStoreConnector<SessionData> + Clone + Send + Sync + 'static,
+    SessionStoreConnection::Error: Send,
+    CookieGenerator: SessionCookieGenerator + Send + Sync + 'static,
+{
+    type Response = Response<ResponseBody>;
+    type Error = Inner::Error;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, context: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(context)
+    }
+
+    fn call(&mut self, mut request: Request<RequestBody>) -> Self::Future {
+        // The inner service was driven to readiness, so use it and leave a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let store = self.store.clone();
+        let mut connection = self.connection.clone();
+
+        Box::pin(async move {
+            let cookie_value = CookieValue::from_cookie_headers(
+                request
+                    .headers()
+                    .get_all(COOKIE)
+                    .iter()
+                    .filter_map(|header| header.to_str().ok()),
+                &store.cookie_settings().name,
+            );
+            let session = match cookie_value {
+                Some(cookie_value) => {
+                    match store.load_session(&cookie_value, &mut connection).await {
+                        Ok(session) => session.unwrap_or_else(Session::new),
+                        Err(error) if error.is_malformed_cookie() => {
+                            debug!("Ignoring a malformed session cookie: {error:?}");
+                            Session::new()
+                        }
+                        Err(error) => {
+                            error!("Could not load session: {error:?}");
+                            return Ok(internal_server_error());
+                        }
+                    }
+                }
+                None => Session::new(),
+            };
+            request.extensions_mut().insert(session.clone());
+
+            let mut response = inner.call(request).await?;
+
+            let session = response
+                .extensions_mut()
+                .remove::<Session<SessionData>>()
+                .unwrap_or(session);
+            let command = match store.store_session(session, &mut connection).await {
+                Ok(command) => command,
+                Err(error) => {
+                    error!("Could not store session: {error:?}");
+                    return Ok(internal_server_error());
+                }
+            };
+            if let Some(set_cookie) = command.set_cookie_header() {
+                match HeaderValue::try_from(set_cookie) {
+                    Ok(set_cookie) => {
+                        response.headers_mut().append(SET_COOKIE, set_cookie);
+                    }
+                    Err(error) => {
+                        error!("Could not encode session cookie: {error:?}");
+                        return Ok(internal_server_error());
+                    }
+                }
+            }
+            Ok(response)
+        })
+    }
+}
+
+fn internal_server_error<ResponseBody: Default>() -> Response<ResponseBody> {
+    let mut response = Response::new(ResponseBody::default());
+    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    response
+}
+
+impl<SessionData, SessionStoreConnection: Clone, CookieGenerator> Clone
+    for SessionLayer<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            connection: self.connection.clone(),
+        }
+    }
+}
+
+impl<Inner: Clone, SessionData, SessionStoreConnection: Clone, CookieGenerator> Clone
+    for SessionService<Inner, SessionData, SessionStoreConnection, CookieGenerator>
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            store: self.store.clone(),
+            connection: self.connection.clone(),
+        }
+    }
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator> Debug
+    for SessionLayer<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionLayer").finish_non_exhaustive()
+    }
+}
+
+impl<Inner: Debug, SessionData, SessionStoreConnection, CookieGenerator> Debug
+    for SessionService<Inner, SessionData, SessionStoreConnection, CookieGenerator>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionService")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,13 +1,66 @@
-use crate::session::{SessionId, SessionState};
+use crate::error::{ConfigNotWritable, InvalidHashingConfiguration, InvalidRenewalStrategy};
+use crate::session::{saturating_add, CookieValue, SessionId, SessionState};
+use crate::session_store::anomaly::AnomalyScorer;
+use crate::session_store::clock::{Clock, UtcClock};
 use crate::session_store::cookie_generator::SessionCookieGenerator;
-use crate::{DefaultSessionCookieGenerator, Error, Session, SessionExpiry};
+use crate::session_store::cookie_upgrade::LegacyCookieFormat;
+use crate::session_store::description::ConnectorDescription;
+use crate::session_store::events::{SessionEvent, SessionEventListener};
+use crate::session_store::expiry_sanitization::ExpirySanitizationPolicy;
+use crate::session_store::interceptor::{ConnectorOperation, Interceptor};
+use crate::session_store::priority::SessionPriorityClassifier;
+use crate::session_store::request_context::RequestContext;
+use crate::session_store::retry::RetryPolicy;
+use crate::session_store::sampling::{SessionSampleSink, SessionSampler};
+use crate::session_store::schema::{SchemaCheck, SchemaMismatchPolicy};
+use crate::session_store::statistics::{RuntimeStatistics, RuntimeStatisticsRecorder};
+use crate::{
+    DefaultSessionCookieGenerator, Error, Session, SessionExpiry, SessionMetadata,
+    SessionStoreConfig, SessionStoreConfigHandle, Sleeper,
+};
 use async_trait::async_trait;
 use chrono::Utc;
 use chrono::{DateTime, Duration};
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::sync::Arc;
+use tracing::warn;
 
+pub(crate) mod analytics;
+pub(crate) mod anomaly;
+pub(crate) mod attachments;
+pub(crate) mod child_sessions;
+pub(crate) mod clock;
+pub(crate) mod config;
 pub(crate) mod cookie_generator;
+pub(crate) mod cookie_upgrade;
+pub(crate) mod description;
+pub(crate) mod device_link;
+pub(crate) mod enumeration;
+pub(crate) mod events;
+pub(crate) mod expiry_sanitization;
+pub(crate) mod external_token;
+pub(crate) mod idempotency;
+pub(crate) mod idempotent_create;
+#[cfg(feature = "metrics")]
+pub(crate) mod instrumentation;
+pub(crate) mod interceptor;
+pub(crate) mod locking;
+pub(crate) mod multi;
+pub(crate) mod nonces;
+pub(crate) mod priority;
+pub(crate) mod read_only_load;
+pub(crate) mod request_context;
+pub(crate) mod retry;
+pub(crate) mod sampling;
+pub(crate) mod schema;
+pub(crate) mod statistics;
+pub(crate) mod transaction;
+pub(crate) mod user_index;
+
+/// How often a new session id is generated if the connector does not
+/// [accept](SessionStoreConnector::accepts_session_id) it, before it is used anyway.
+const MAXIMUM_ID_ACCEPTANCE_TRIES: u32 = 1024;
 
 /// An async session store.
 ///
@@ -20,6 +73,9 @@ pub(crate) mod cookie_generator;
 /// `SessionData` is the data associated with a session.
 /// `SessionStoreConnection` is the connection to the backend session store.
 /// `CookieGenerator` is the type used to generate random session cookies.
+///
+/// All methods take `&self`, so the session store can be shared e.g. behind an [`Arc`](std::sync::Arc).
+/// Its runtime configuration can be changed through a [`SessionStoreConfigHandle`], see [`SessionStore::config_handle`].
 #[derive(Debug)]
 pub struct SessionStore<
     SessionData,
@@ -27,7 +83,29 @@ pub struct SessionStore<
     CookieGenerator = DefaultSessionCookieGenerator,
 > {
     cookie_generator: CookieGenerator,
-    session_renewal_strategy: SessionRenewalStrategy,
+    cookie_settings: CookieSettings,
+    config: SessionStoreConfigHandle,
+    hashing_policy: HashingPolicy,
+    maximum_retries_on_id_collision: Option<u32>,
+    maximum_sessions: Option<usize>,
+    clock_authority: ClockAuthority,
+    clock: Hook<dyn Clock>,
+    channel_binding_policy: ChannelBindingPolicy,
+    access_audit: bool,
+    key_prefix: Option<String>,
+    renewal_strategy_selector: Option<Hook<dyn SessionRenewalStrategySelector<SessionData>>>,
+    suppression_predicate: Option<Hook<SessionDataPredicate<SessionData>>>,
+    session_sampler: Option<Hook<SessionSampler<SessionData>>>,
+    runtime_statistics: Option<Hook<RuntimeStatisticsRecorder<SessionData>>>,
+    pre_expiry_hook: Option<(Duration, Hook<PreExpiryHook<SessionData>>)>,
+    schema_check: Option<Hook<SchemaCheck>>,
+    priority_classifier: Option<Hook<dyn SessionPriorityClassifier<SessionData>>>,
+    anomaly_scorer: Option<Hook<dyn AnomalyScorer>>,
+    event_listener: Option<Hook<dyn SessionEventListener>>,
+    interceptor: Option<Hook<dyn Interceptor>>,
+    retry_policy: Option<(Hook<dyn RetryPolicy>, Hook<dyn Sleeper>)>,
+    expiry_sanitization: Option<(Duration, ExpirySanitizationPolicy)>,
+    legacy_cookie_format: Option<LegacyCookieFormat>,
     data: PhantomData<SessionData>,
     connection: PhantomData<SessionStoreConnection>,
 }
@@ -48,6 +126,144 @@ pub enum SessionRenewalStrategy {
         /// The maximum remaining time-to-live to trigger a session renewal.
         maximum_remaining_time_to_live_for_renewal: Duration,
     },
+
+    /// Sessions expire after `idle_timeout` of inactivity, but never later than `absolute_timeout` after their creation,
+    /// as recorded in [`SessionMetadata::created_at`].
+    ///
+    /// Like with [`sliding`](SessionRenewalStrategy::sliding), a session is renewed once a tenth of the idle timeout has passed.
+    /// Sessions without a recorded creation time are treated as created when they are first renewed with this strategy.
+    IdleAndAbsoluteTimeout {
+        /// The time of inactivity after which a session expires.
+        idle_timeout: Duration,
+        /// The maximum lifetime of a session, measured from its creation.
+        absolute_timeout: Duration,
+    },
+}
+
+/// The way sessions are deleted from the session store.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DeletionMode {
+    /// Delete sessions immediately.
+    #[default]
+    Hard,
+
+    /// Replace deleted sessions by a tombstone that expires after the given time-to-live.
+    ///
+    /// Tombstones allow audit jobs and theft-detection to distinguish a session that was logged out from a
+    /// session that never existed.
+    /// When a client attempts to load a session that has a tombstone, this is logged as a warning,
+    /// since it may indicate the replay of a stolen session cookie.
+    ///
+    /// See [`SessionStoreConnector::create_tombstone`] for details.
+    Tombstone {
+        /// The time-to-live of the tombstone.
+        time_to_live: Duration,
+    },
+}
+
+/// The policy for deriving session ids from cookie values.
+///
+/// By default, the session store hashes each cookie value with blake3 to obtain the session id
+/// that is passed to the [`SessionStoreConnector`].
+/// This way, a leaked session store does not leak any valid session cookies.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum HashingPolicy {
+    /// Hash each cookie value with blake3 before using it as session id.
+    /// This is the default and the only policy that should be used unless you know exactly what you are doing.
+    #[default]
+    Always,
+
+    /// Use the cookie value directly as session id, without hashing it.
+    ///
+    /// **Security: this passes the plain session cookies to the session store connector!**
+    /// Anyone who can read the backend can hijack every session stored in it.
+    /// Only use this if the backend already stores nothing but opaque references to the
+    /// actual session secrets, e.g. a dedicated token service that hashes or encrypts the ids itself.
+    ///
+    /// This policy requires the cookie length of the cookie generator to be exactly [`blake3::OUT_LEN`].
+    Never,
+}
+
+/// The source of the current time that is used for expiry checks and new expiry times.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClockAuthority {
+    /// Use the clock of the application server, see [`SessionStore::set_clock`].
+    #[default]
+    Local,
+
+    /// Use the time supplied by [`SessionStoreConnector::now`], e.g. `SELECT now()` in Postgres or `TIME` in Redis.
+    /// This avoids bugs caused by skewed clocks between multiple application servers,
+    /// at the cost of an additional request to the backend for most operations.
+    ///
+    /// If the connector does not supply a time, the clock of the application server is used.
+    Backend,
+}
+
+/// What to do when a session that is bound to a channel is loaded with a different or no channel binding value,
+/// see [`Session::bind_to_channel`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelBindingPolicy {
+    /// Log a warning and treat the session as if it did not exist.
+    #[default]
+    Reject,
+
+    /// Log a warning, but return the session anyways.
+    /// This allows to monitor mismatches before enforcing channel binding.
+    Flag,
+
+    /// Return the session without logging.
+    Ignore,
+
+    /// Log the mismatch at info level, bind the session to the new channel, and return it.
+    /// The rebinding marks the session as changed, so storing it rotates its id and issues a fresh cookie,
+    /// after which the cookie for the previous channel is no longer valid.
+    ///
+    /// This balances usability and security e.g. for channel binding values derived from the network of the client
+    /// with [`ip_network_binding`](crate::ip_network_binding): a client that roams between networks keeps its session,
+    /// while a stolen cookie that is replayed from another network is invalidated once either party uses it.
+    ///
+    /// Sessions that are loaded without a channel binding value cannot be rebound, so they are rejected like with
+    /// [`Reject`](ChannelBindingPolicy::Reject).
+    Rotate,
+}
+
+/// Selects the renewal strategy of a session based on its data.
+///
+/// This allows e.g. to keep anonymous sessions short-lived, while sessions of logged-in users are renewed for weeks.
+/// It is implemented for all closures `Fn(&SessionData) -> Option<SessionRenewalStrategy>`.
+pub trait SessionRenewalStrategySelector<SessionData>: Send + Sync {
+    /// Returns the renewal strategy for a session with the given data,
+    /// or `None` to use the session renewal strategy configured in the session store.
+    fn select(&self, data: &SessionData) -> Option<SessionRenewalStrategy>;
+}
+
+impl<SessionData, F: Fn(&SessionData) -> Option<SessionRenewalStrategy> + Send + Sync>
+    SessionRenewalStrategySelector<SessionData> for F
+{
+    fn select(&self, data: &SessionData) -> Option<SessionRenewalStrategy> {
+        self(data)
+    }
+}
+
+type SessionDataPredicate<SessionData> = dyn Fn(&SessionData) -> bool + Send + Sync;
+
+type PreExpiryHook<SessionData> = dyn Fn(&mut Session<SessionData>, Duration) + Send + Sync;
+
+/// A shared hook into the session store that is printed opaquely when debugging.
+pub(crate) struct Hook<T: ?Sized>(Arc<T>);
+
+impl<T: ?Sized> Clone for Hook<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: ?Sized> Debug for Hook<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Hook")
+    }
 }
 
 impl<SessionData, SessionStoreConnection>
@@ -55,12 +271,7 @@ impl<SessionData, SessionStoreConnection>
 {
     /// Create a new session store with the given cookie generator and session renewal strategy.
     pub fn new(expiry_strategy: SessionRenewalStrategy) -> Self {
-        Self {
-            cookie_generator: Default::default(),
-            session_renewal_strategy: expiry_strategy,
-            data: Default::default(),
-            connection: Default::default(),
-        }
+        Self::new_with_cookie_generator(Default::default(), expiry_strategy)
     }
 }
 
@@ -71,29 +282,459 @@ impl<SessionData, SessionStoreConnection, CookieGenerator>
     pub fn new_with_cookie_generator(
         cookie_generator: CookieGenerator,
         session_renewal_strategy: SessionRenewalStrategy,
+    ) -> Self {
+        Self::new_with_config(
+            cookie_generator,
+            SessionStoreConfig::new(session_renewal_strategy),
+        )
+    }
+
+    /// Create a new session store with the given cookie generator and runtime configuration.
+    ///
+    /// The configuration may also be a [`SessionStoreConfigHandle`] obtained from another session store,
+    /// in which case both stores share their configuration.
+    pub fn new_with_config(
+        cookie_generator: CookieGenerator,
+        config: impl Into<SessionStoreConfigHandle>,
     ) -> Self {
         Self {
             cookie_generator,
-            session_renewal_strategy,
+            cookie_settings: Default::default(),
+            config: config.into(),
+            hashing_policy: Default::default(),
+            maximum_retries_on_id_collision: None,
+            maximum_sessions: None,
+            clock_authority: Default::default(),
+            clock: Hook(Arc::new(UtcClock)),
+            channel_binding_policy: Default::default(),
+            access_audit: false,
+            key_prefix: None,
+            renewal_strategy_selector: None,
+            suppression_predicate: None,
+            session_sampler: None,
+            runtime_statistics: None,
+            pre_expiry_hook: None,
+            schema_check: None,
+            priority_classifier: None,
+            anomaly_scorer: None,
+            event_listener: None,
+            interceptor: None,
+            retry_policy: None,
+            expiry_sanitization: None,
+            legacy_cookie_format: None,
             data: Default::default(),
             connection: Default::default(),
         }
     }
 
-    /// A reference to the session renewal strategy of this session store.
-    pub fn session_renewal_strategy(&self) -> &SessionRenewalStrategy {
-        &self.session_renewal_strategy
+    /// Returns a copy of the current runtime configuration of this session store.
+    pub fn config(&self) -> SessionStoreConfig {
+        self.config.get()
+    }
+
+    /// Returns a handle to the runtime configuration of this session store.
+    /// Changes made through the handle affect this session store and all its clones.
+    pub fn config_handle(&self) -> SessionStoreConfigHandle {
+        self.config.clone()
+    }
+
+    /// The current session renewal strategy of this session store.
+    pub fn session_renewal_strategy(&self) -> SessionRenewalStrategy {
+        self.config.get().session_renewal_strategy
+    }
+
+    /// Sets the session renewal strategy of this session store.
+    /// This does not require exclusive access, such that the strategy can be changed while the store is in use.
+    ///
+    /// Returns [`ConfigNotWritable`] if the configuration is received from a watch channel.
+    pub fn set_session_renewal_strategy(
+        &self,
+        session_renewal_strategy: SessionRenewalStrategy,
+    ) -> Result<(), ConfigNotWritable> {
+        self.config
+            .update(|config| config.session_renewal_strategy = session_renewal_strategy)
+    }
+
+    /// Returns true if this session store is in emergency read-only mode, see [`SessionStoreConfig::read_only`].
+    pub fn is_read_only(&self) -> bool {
+        self.config.get().read_only
+    }
+
+    /// Enables or disables the emergency read-only mode of this session store, see [`SessionStoreConfig::read_only`].
+    /// Like [`set_session_renewal_strategy`](SessionStore::set_session_renewal_strategy),
+    /// this does not require exclusive access, and affects all clones of this session store.
+    ///
+    /// Returns [`ConfigNotWritable`] if the configuration is received from a watch channel.
+    pub fn set_read_only(&self, read_only: bool) -> Result<(), ConfigNotWritable> {
+        self.config.update(|config| config.read_only = read_only)
+    }
+
+    /// Returns true if the given write must be suppressed, because this session store is in read-only mode.
+    /// All writes that are suppressed in read-only mode share this check, such that each of them is logged.
+    pub(crate) fn suppress_write_in_read_only_mode(&self, write: std::fmt::Arguments) -> bool {
+        if self.is_read_only() {
+            tracing::info!("Suppressed {write} in read-only mode");
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The hashing policy of this session store.
+    pub fn hashing_policy(&self) -> HashingPolicy {
+        self.hashing_policy
+    }
+
+    /// The settings of the session cookie, see [`CookieSettings`].
+    pub fn cookie_settings(&self) -> &CookieSettings {
+        &self.cookie_settings
+    }
+
+    /// Sets the settings of the session cookie, which are passed along with [`SessionCookieCommand::Delete`].
+    ///
+    /// To emit the cookies of existing sessions again with the new settings,
+    /// increment the [cookie settings version](SessionStore::set_cookie_settings_version).
+    pub fn set_cookie_settings(&mut self, cookie_settings: CookieSettings) {
+        self.cookie_settings = cookie_settings;
+    }
+
+    /// The version of the cookie settings, see [`SessionStoreConfig::cookie_settings_version`].
+    pub fn cookie_settings_version(&self) -> u32 {
+        self.config.get().cookie_settings_version
+    }
+
+    /// Sets the version of the cookie settings, see [`SessionStoreConfig::cookie_settings_version`].
+    /// Like [`set_read_only`](SessionStore::set_read_only), this does not require exclusive access,
+    /// and affects all clones of this session store.
+    ///
+    /// Returns [`ConfigNotWritable`] if the configuration is received from a watch channel.
+    pub fn set_cookie_settings_version(&self, version: u32) -> Result<(), ConfigNotWritable> {
+        self.config
+            .update(|config| config.cookie_settings_version = version)
+    }
+
+    /// The clock authority of this session store.
+    pub fn clock_authority(&self) -> ClockAuthority {
+        self.clock_authority
+    }
+
+    /// Sets the clock authority of this session store, i.e. where it gets the current time from.
+    pub fn set_clock_authority(&mut self, clock_authority: ClockAuthority) {
+        self.clock_authority = clock_authority;
+    }
+
+    /// The maximum number of retries when a generated session id collides with an existing one,
+    /// or `None` if the store defers to the connector, see
+    /// [`set_maximum_retries_on_id_collision`](SessionStore::set_maximum_retries_on_id_collision).
+    pub fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        self.maximum_retries_on_id_collision
+    }
+
+    /// Sets the maximum number of retries when a generated session id collides with an existing one.
+    ///
+    /// How many collisions are tolerated before giving up is a matter of risk tolerance, so it belongs to the session store,
+    /// such that swapping the backend does not silently change it.
+    /// The value of [`SessionStoreConnector::maximum_retries_on_id_collision`] still acts as a cap,
+    /// and is used alone if this is not set.
+    /// A [retry policy](SessionStore::set_retry_policy) may give up earlier.
+    pub fn set_maximum_retries_on_id_collision(&mut self, maximum_retries_on_id_collision: u32) {
+        self.maximum_retries_on_id_collision = Some(maximum_retries_on_id_collision);
+    }
+
+    /// The maximum number of sessions in the backend, or `None` if it is unbounded, see
+    /// [`set_maximum_sessions`](SessionStore::set_maximum_sessions).
+    pub fn maximum_sessions(&self) -> Option<usize> {
+        self.maximum_sessions
+    }
+
+    /// Sets the maximum number of sessions in the backend, protecting it from unbounded growth, e.g. during bot attacks.
+    ///
+    /// Before a new session is created, the stored sessions are counted with [`SessionStoreConnector::count_sessions`],
+    /// and if there are already `maximum_sessions`, then [`store_session`](SessionStore::store_session) returns
+    /// [`Error::StoreAtCapacity`] instead of creating the session.
+    /// Existing sessions can still be updated and deleted.
+    /// To make room for new sessions by evicting sessions of low [priority](crate::SessionPriority) instead,
+    /// use a bounded connector like a [`MemoryStore`](crate::MemoryStore) with a [capacity](crate::MemoryStore::set_capacity).
+    ///
+    /// This requires a connector that can count its sessions, otherwise creating sessions fails with
+    /// [`Error::UnsupportedOperation`].
+    pub fn set_maximum_sessions(&mut self, maximum_sessions: Option<usize>) {
+        self.maximum_sessions = maximum_sessions;
+    }
+
+    /// The policy for sessions whose channel binding does not match, see [`ChannelBindingPolicy`].
+    pub fn channel_binding_policy(&self) -> ChannelBindingPolicy {
+        self.channel_binding_policy
+    }
+
+    /// Sets the policy for sessions whose channel binding does not match, see [`ChannelBindingPolicy`].
+    pub fn set_channel_binding_policy(&mut self, channel_binding_policy: ChannelBindingPolicy) {
+        self.channel_binding_policy = channel_binding_policy;
+    }
+
+    /// Returns true if accesses to loaded sessions are recorded, see [`set_access_audit`](SessionStore::set_access_audit).
+    pub fn access_audit(&self) -> bool {
+        self.access_audit
+    }
+
+    /// If true, [`Session::enable_access_audit`] is called on all loaded sessions,
+    /// and the recorded accesses are logged at debug level when a session is stored.
+    /// This helps to debug why sessions are unexpectedly rotated, or why changes are not persisted.
+    pub fn set_access_audit(&mut self, access_audit: bool) {
+        self.access_audit = access_audit;
+    }
+
+    /// Sets a selector that chooses the renewal strategy of each session based on its data.
+    /// Sessions for which the selector returns `None` are renewed with the configured session renewal strategy.
+    pub fn set_renewal_strategy_selector(
+        &mut self,
+        selector: impl SessionRenewalStrategySelector<SessionData> + 'static,
+    ) {
+        self.renewal_strategy_selector = Some(Hook(Arc::new(selector)));
+    }
+
+    /// Sets a predicate that suppresses storing new sessions.
+    ///
+    /// New sessions whose data matches the predicate are never stored, even if their data was accessed mutably.
+    /// Instead, [`store_session`](SessionStore::store_session) returns [`SessionCookieCommand::DoNothing`] for them.
+    /// This prevents e.g. middleware that writes trivial default data from filling the session store with anonymous sessions.
+    ///
+    /// Sessions that were loaded from the session store are not affected, i.e. they are updated even if their data
+    /// matches the predicate.
+    pub fn set_suppression_predicate(
+        &mut self,
+        predicate: impl Fn(&SessionData) -> bool + Send + Sync + 'static,
+    ) {
+        self.suppression_predicate = Some(Hook(Arc::new(predicate)));
+    }
+
+    /// Sets a sampler that records the given `fraction` of all successful session writes into `sink`.
+    /// The session data of each sample is scrubbed with `redactor` before it is passed to the sink.
+    ///
+    /// This allows to analyse e.g. session sizes and churn in production without exposing the actual session data.
+    ///
+    /// **Panics** if `fraction` is not between `0.0` and `1.0`.
+    pub fn set_session_sampler(
+        &mut self,
+        fraction: f64,
+        redactor: impl Fn(&SessionData) -> SessionData + Send + Sync + 'static,
+        sink: impl SessionSampleSink<SessionData> + 'static,
+    ) {
+        self.session_sampler = Some(Hook(Arc::new(SessionSampler::new(
+            fraction, redactor, sink,
+        ))));
+    }
+
+    /// Enables recording [`RuntimeStatistics`] in-process, e.g. to right-size session expiry times and storage.
+    /// The size of session data is determined with `payload_size`, e.g. by serialising it.
+    ///
+    /// Clones of this session store share their statistics.
+    pub fn enable_runtime_statistics(
+        &mut self,
+        payload_size: impl Fn(&SessionData) -> usize + Send + Sync + 'static,
+    ) {
+        self.runtime_statistics =
+            Some(Hook(Arc::new(RuntimeStatisticsRecorder::new(payload_size))));
+    }
+
+    /// Sets a hook that is called by [`load_session`](SessionStore::load_session) for each loaded session
+    /// that expires within `window`, together with the remaining time until its expiry.
+    ///
+    /// The hook is called after the session renewal strategy was applied, so sessions that were just renewed
+    /// are only observed if their new expiry is still within `window`.
+    /// It may modify the session, e.g. to extend the sessions of active users with custom logic,
+    /// or mark it in its data such that the application can prompt for re-authentication.
+    pub fn set_pre_expiry_hook(
+        &mut self,
+        window: Duration,
+        hook: impl Fn(&mut Session<SessionData>, Duration) + Send + Sync + 'static,
+    ) {
+        self.pre_expiry_hook = Some((window, Hook(Arc::new(hook))));
+    }
+
+    /// Sets the schema version of the session data, e.g. a version string that is bumped on incompatible changes,
+    /// or a fingerprint derived from the type via serde introspection.
+    ///
+    /// A fingerprint of the version is stored with every written session.
+    /// When a session with a different fingerprint is loaded, e.g. because it was written by an incompatible deployment,
+    /// then `policy` is applied and the mismatch is counted, see [`schema_mismatches`](SessionStore::schema_mismatches).
+    /// Sessions that were written without a schema version are accepted.
+    ///
+    /// Clones of this session store share their mismatch counter.
+    pub fn set_schema_version(&mut self, version: &str, policy: SchemaMismatchPolicy) {
+        self.schema_check = Some(Hook(Arc::new(SchemaCheck::new(version, policy))));
+    }
+
+    /// The number of loaded sessions whose schema version did not match, see [`set_schema_version`](SessionStore::set_schema_version).
+    pub fn schema_mismatches(&self) -> u64 {
+        self.schema_check
+            .as_ref()
+            .map_or(0, |schema_check| schema_check.0.mismatches())
+    }
+
+    /// Returns a snapshot of the runtime statistics recorded so far,
+    /// or `None` if they were not enabled with [`enable_runtime_statistics`](SessionStore::enable_runtime_statistics).
+    pub fn runtime_statistics(&self) -> Option<RuntimeStatistics> {
+        self.runtime_statistics
+            .as_ref()
+            .map(|recorder| recorder.0.statistics())
+    }
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator: SessionCookieGenerator>
+    SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Sets the hashing policy of this session store.
+    /// See [`HashingPolicy::Never`] for the security implications of disabling hashing.
+    ///
+    /// Note that changing the hashing policy invalidates all existing sessions.
+    ///
+    /// Returns an error and leaves the hashing policy unchanged if the policy is [`HashingPolicy::Never`] and
+    /// the cookie length of the cookie generator is not [`blake3::OUT_LEN`],
+    /// or if a key prefix or a legacy cookie format is set.
+    pub fn set_hashing_policy(
+        &mut self,
+        hashing_policy: HashingPolicy,
+    ) -> Result<(), InvalidHashingConfiguration> {
+        if hashing_policy == HashingPolicy::Never {
+            if CookieGenerator::COOKIE_LENGTH != blake3::OUT_LEN {
+                return Err(InvalidHashingConfiguration::WrongCookieLength {
+                    expected: blake3::OUT_LEN,
+                    actual: CookieGenerator::COOKIE_LENGTH,
+                });
+            } else if self.key_prefix.is_some() {
+                return Err(InvalidHashingConfiguration::KeyPrefixWithoutHashing);
+            } else if self.legacy_cookie_format.is_some() {
+                return Err(InvalidHashingConfiguration::LegacyCookieFormatWithoutHashing);
+            }
+        }
+        self.hashing_policy = hashing_policy;
+        Ok(())
+    }
+
+    /// The key prefix of this session store, see [`set_key_prefix`](SessionStore::set_key_prefix).
+    pub fn key_prefix(&self) -> Option<&str> {
+        self.key_prefix.as_deref()
+    }
+
+    /// Sets a prefix that is folded into the derivation of session ids from cookie values.
+    /// This separates the sessions of multiple session stores that share a backend,
+    /// such that a cookie of one session store never identifies a session of another.
+    ///
+    /// Note that changing the key prefix invalidates all existing sessions.
+    ///
+    /// Returns an error and leaves the key prefix unchanged if the hashing policy is [`HashingPolicy::Never`].
+    pub fn set_key_prefix(
+        &mut self,
+        key_prefix: Option<impl Into<String>>,
+    ) -> Result<(), InvalidHashingConfiguration> {
+        if key_prefix.is_some() && self.hashing_policy != HashingPolicy::Always {
+            return Err(InvalidHashingConfiguration::KeyPrefixWithoutHashing);
+        }
+        self.key_prefix = key_prefix.map(Into::into);
+        Ok(())
+    }
+
+    /// The legacy cookie format of this session store, see [`set_legacy_cookie_format`](SessionStore::set_legacy_cookie_format).
+    pub fn legacy_cookie_format(&self) -> Option<&LegacyCookieFormat> {
+        self.legacy_cookie_format.as_ref()
+    }
+
+    /// Sets a previous format of session cookies that is still accepted for a transition window, in addition to
+    /// the cookies of the cookie generator.
+    /// Whether the transition window is over is decided with the clock of the application server.
+    ///
+    /// Sessions loaded with a legacy cookie are marked as changed, such that storing them issues a cookie of
+    /// the current format.
+    ///
+    /// Returns an error and leaves the legacy cookie format unchanged if the hashing policy is [`HashingPolicy::Never`].
+    pub fn set_legacy_cookie_format(
+        &mut self,
+        legacy_cookie_format: Option<LegacyCookieFormat>,
+    ) -> Result<(), InvalidHashingConfiguration> {
+        if legacy_cookie_format.is_some() && self.hashing_policy != HashingPolicy::Always {
+            return Err(InvalidHashingConfiguration::LegacyCookieFormatWithoutHashing);
+        }
+        self.legacy_cookie_format = legacy_cookie_format;
+        Ok(())
+    }
+
+    /// Returns true if the given cookie value was not generated by the cookie generator, but is accepted as legacy cookie.
+    pub(crate) fn is_legacy_cookie_value(&self, cookie_value: &CookieValue) -> bool {
+        let cookie_value = cookie_value.expose();
+        (cookie_value.len() != CookieGenerator::COOKIE_LENGTH
+            || !self.cookie_generator.is_valid_cookie(cookie_value))
+            && self
+                .legacy_cookie_format
+                .as_ref()
+                .is_some_and(|format| format.accepts(cookie_value, self.clock.0.now()))
+    }
+
+    /// Check that a cookie value received from a client may have been generated by the cookie generator,
+    /// or is accepted as legacy cookie.
+    pub(crate) fn check_cookie_value<SessionStoreConnectorError>(
+        &self,
+        cookie_value: &CookieValue,
+    ) -> Result<(), Error<SessionStoreConnectorError>> {
+        if self.is_legacy_cookie_value(cookie_value) {
+            return Ok(());
+        }
+        let cookie_value = cookie_value.expose();
+        if cookie_value.len() != CookieGenerator::COOKIE_LENGTH {
+            Err(Error::WrongCookieLength {
+                expected: CookieGenerator::COOKIE_LENGTH,
+                actual: cookie_value.len(),
+            })
+        } else if !self.cookie_generator.is_valid_cookie(cookie_value) {
+            Err(Error::InvalidCookieValue)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Generate a new cookie value, ensuring that it is valid.
+    pub(crate) fn generate_cookie_value<SessionStoreConnectorError>(
+        &self,
+    ) -> Result<CookieValue, Error<SessionStoreConnectorError>> {
+        let cookie_value = CookieValue::from(self.cookie_generator.generate_cookie());
+        if self.cookie_generator.is_valid_cookie(cookie_value.expose()) {
+            Ok(cookie_value)
+        } else {
+            Err(Error::InvalidCookieValue)
+        }
     }
 
-    /// A mutable reference to the session renewal strategy of this session store.
-    pub fn session_renewal_strategy_mut(&mut self) -> &mut SessionRenewalStrategy {
-        &mut self.session_renewal_strategy
+    /// Create the command that sets the session cookie to the given value, with its remaining lifetime at time `now`.
+    pub(crate) fn set_cookie_command(
+        &self,
+        cookie_value: CookieValue,
+        expiry: SessionExpiry,
+        now: DateTime<Utc>,
+    ) -> SessionCookieCommand {
+        SessionCookieCommand::Set {
+            cookie_value,
+            expiry,
+            max_age: expiry
+                .cookie_max_age(now)
+                .map(std::time::Duration::from_secs),
+            settings: self.cookie_settings.clone(),
+        }
+    }
+
+    fn session_id_from_cookie_value(&self, cookie_value: &CookieValue) -> SessionId {
+        match (self.hashing_policy, &self.key_prefix) {
+            (HashingPolicy::Always, None) => SessionId::from_cookie_value(cookie_value),
+            (HashingPolicy::Always, Some(key_prefix)) => {
+                SessionId::from_prefixed_cookie_value(key_prefix, cookie_value)
+            }
+            (HashingPolicy::Never, _) => SessionId::from_unhashed_cookie_value(cookie_value),
+        }
     }
 }
 
 impl<
         SessionData: Debug,
-        SessionStoreConnection: SessionStoreConnector<SessionData>,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
         CookieGenerator: SessionCookieGenerator,
     > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
 {
@@ -102,86 +743,313 @@ impl<
     ///
     /// If the session cookie requires to be updated, because the session data or expiry changed,
     /// then a [SessionCookieCommand] is returned.
+    ///
+    /// In [read-only mode](SessionStoreConfig::read_only), nothing is written and [`SessionCookieCommand::DoNothing`]
+    /// is returned.
     pub async fn store_session(
+        &self,
+        session: Session<SessionData>,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<SessionCookieCommand, Error<SessionStoreConnection::Error>> {
+        #[cfg(feature = "tracing-spans")]
+        let span = tracing::debug_span!(
+            "store_session",
+            session_id = session
+                .current_id()
+                .map(|id| tracing::field::display(id.redacted())),
+            state = ?session.state_kind(),
+        );
+        let store = self.store_session_untraced(session, connection);
+        #[cfg(feature = "tracing-spans")]
+        let store = tracing::Instrument::instrument(store, span);
+        store.await
+    }
+
+    async fn store_session_untraced(
         &self,
         mut session: Session<SessionData>,
         connection: &mut SessionStoreConnection,
     ) -> Result<SessionCookieCommand, Error<SessionStoreConnection::Error>> {
+        if let Some(accesses) = session.access_audit() {
+            tracing::debug!(
+                "Storing session in state {:?} after accesses {accesses:?}",
+                session.state_kind()
+            );
+        }
+        if self.is_suppressed(&session) {
+            return Ok(SessionCookieCommand::DoNothing);
+        }
+
         if matches!(
             &session.state,
             SessionState::NewChanged { .. }
                 | SessionState::Changed { .. }
                 | SessionState::Deleted { .. }
         ) {
-            // If we store a new session, we need to update its expiry.
-            // In all other cases, the expiry is updated when loading the session.
-            // This allows the user to see the current session expiry by inspecting the session.
-            if matches!(&session.state, SessionState::NewChanged { .. }) {
-                self.session_renewal_strategy
-                    .apply_to_session(&mut session, Utc::now());
+            if self.suppress_write_in_read_only_mode(format_args!(
+                "storing session in state {:?}",
+                session.state_kind()
+            )) {
+                return Ok(SessionCookieCommand::DoNothing);
             }
-
-            if let Some(maximum_retries_on_collision) = connection.maximum_retries_on_id_collision()
+            if let (SessionState::NewChanged { .. }, Some(maximum_sessions)) =
+                (&session.state, self.maximum_sessions)
             {
-                for _ in 0..maximum_retries_on_collision {
-                    match self.try_store_session(&session, connection).await? {
-                        WriteSessionResult::Ok(command) => return Ok(command),
-                        WriteSessionResult::SessionIdExists => { /* continue trying */ }
-                    }
+                if self
+                    .intercept(
+                        ConnectorOperation::CountSessions,
+                        connection.count_sessions(),
+                    )
+                    .await?
+                    >= maximum_sessions
+                {
+                    warn!("Refused to create a session, since the store holds the maximum of {maximum_sessions} sessions");
+                    return Err(Error::StoreAtCapacity { maximum_sessions });
                 }
+            }
 
-                Err(Error::MaximumSessionIdGenerationTriesReached {
-                    maximum: maximum_retries_on_collision,
-                })
-            } else {
-                loop {
-                    match self.try_store_session(&session, connection).await? {
-                        WriteSessionResult::Ok(command) => return Ok(command),
-                        WriteSessionResult::SessionIdExists => { /* continue trying */ }
-                    }
+            // If we store a new session, we need to update its expiry.
+            // In all other cases, the expiry is updated when loading the session.
+            // This allows the user to see the current session expiry by inspecting the session.
+            let now = self.now(connection).await?;
+            self.prepare_write(&mut session, now);
+
+            #[cfg(feature = "metrics")]
+            let start = std::time::Instant::now();
+            let mut retries = self.write_retries(connection);
+            loop {
+                retries.check_limit()?;
+                let result = self.try_store_session(&session, now, connection).await;
+                if let Some(command) = self.retry_write(&mut retries, result, connection).await? {
+                    self.after_write(&session, now);
+                    #[cfg(feature = "metrics")]
+                    instrumentation::record_store_duration(start.elapsed());
+                    return Ok(command);
                 }
             }
+        } else if let (SessionState::Unchanged { expiry, .. }, Some(cookie_value)) =
+            (&session.state, session.reemitted_cookie)
+        {
+            // The cookie keeps its value, so there is nothing to write.
+            let now = self.now(connection).await?;
+            Ok(self.set_cookie_command(cookie_value, *expiry, now))
         } else {
             Ok(SessionCookieCommand::DoNothing)
         }
     }
 
+    /// Store a session like [`store_session`](SessionStore::store_session), but additionally delete stale cookies.
+    ///
+    /// If `request_had_cookie` is true and the session is new, then the cookie of the request did not
+    /// belong to a valid session, e.g. because it was issued by a previous deployment.
+    /// If additionally no session is stored, e.g. because the new session was never changed, then this returns
+    /// [`SessionCookieCommand::Delete`] with reason [`CookieDeletionReason::StaleCookie`] instead of
+    /// [`SessionCookieCommand::DoNothing`], such that the client drops the stale cookie.
+    pub async fn store_session_clearing_stale_cookie(
+        &self,
+        session: Session<SessionData>,
+        request_had_cookie: bool,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<SessionCookieCommand, Error<SessionStoreConnection::Error>> {
+        let is_new = matches!(
+            &session.state,
+            SessionState::NewUnchanged { .. }
+                | SessionState::NewChanged { .. }
+                | SessionState::NewDeleted
+        );
+        match self.store_session(session, connection).await? {
+            SessionCookieCommand::DoNothing if request_had_cookie && is_new => {
+                Ok(SessionCookieCommand::Delete {
+                    settings: self.cookie_settings.clone(),
+                    reason: Some(CookieDeletionReason::StaleCookie),
+                })
+            }
+            command => Ok(command),
+        }
+    }
+
+    /// Returns true if the given session is new and matches the suppression predicate, such that it is not stored.
+    pub(crate) fn is_suppressed(&self, session: &Session<SessionData>) -> bool {
+        if let (SessionState::NewChanged { data, .. }, Some(predicate)) =
+            (&session.state, &self.suppression_predicate)
+        {
+            if (predicate.0)(data) {
+                // The data is not logged, since it may contain personal data or secrets.
+                tracing::trace!("Suppressed storing a new session");
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Update the metadata and, for new sessions, the expiry of the given session before it is written.
+    pub(crate) fn prepare_write(&self, session: &mut Session<SessionData>, now: DateTime<Utc>) {
+        if let Some(schema_check) = &self.schema_check {
+            session.metadata.schema_fingerprint = Some(schema_check.0.fingerprint());
+        }
+        session.metadata.cookie_settings_version = Some(self.cookie_settings_version());
+        self.classify_priority(session);
+        if matches!(&session.state, SessionState::Changed { .. }) {
+            session.metadata.rotation_count = Some(
+                session
+                    .metadata
+                    .rotation_count
+                    .unwrap_or(0)
+                    .saturating_add(1),
+            );
+        }
+        if matches!(&session.state, SessionState::NewChanged { .. }) {
+            session.metadata.created_at = Some(now);
+            session.metadata.namespace = self.key_prefix.clone();
+            self.renewal_strategy_for(session)
+                .apply_to_session(session, now);
+        }
+    }
+
+    /// Update the priority of the given session, if a [priority classifier](SessionStore::set_priority_classifier) is set.
+    pub(crate) fn classify_priority(&self, session: &mut Session<SessionData>) {
+        if let (
+            SessionState::NewChanged { data, .. } | SessionState::Changed { data, .. },
+            Some(classifier),
+        ) = (&session.state, &self.priority_classifier)
+        {
+            session.metadata.priority = Some(classifier.0.classify(data));
+        }
+    }
+
+    /// Record the write of the given session, which must have been stored successfully.
+    pub(crate) fn after_write(&self, session: &Session<SessionData>, now: DateTime<Utc>) {
+        if let Some(sampler) = &self.session_sampler {
+            sampler.0.sample(session);
+        }
+        if let Some(recorder) = &self.runtime_statistics {
+            recorder.0.record_write(session, now);
+        }
+        self.emit_write_event(session);
+        #[cfg(feature = "metrics")]
+        instrumentation::record_write(&session.state);
+    }
+
+    /// Generate a new cookie value and the id of the session stored under it,
+    /// where `previous_id` is the id of the session before the write, or `None` if the session is new.
+    ///
+    /// Ids that the connector does not [accept](SessionStoreConnector::accepts_session_id) are regenerated
+    /// without counting them as id collisions. After [`MAXIMUM_ID_ACCEPTANCE_TRIES`] tries, the last id is used anyway,
+    /// such that a connector that never accepts an id is limited by the retries on id collisions instead.
+    pub(crate) fn generate_session_id(
+        &self,
+        previous_id: Option<&SessionId>,
+        connection: &SessionStoreConnection,
+    ) -> Result<(CookieValue, SessionId), Error<SessionStoreConnection::Error>> {
+        let mut tries = 0;
+        loop {
+            let cookie_value = self.generate_cookie_value()?;
+            let id = self.session_id_from_cookie_value(&cookie_value);
+            tries += 1;
+            if tries >= MAXIMUM_ID_ACCEPTANCE_TRIES
+                || connection.accepts_session_id(&id, previous_id)
+            {
+                return Ok((cookie_value, id));
+            }
+        }
+    }
+
+    /// The maximum number of retries on id collisions, combining the value of this store with the cap of the connector.
+    pub(crate) fn effective_maximum_retries_on_id_collision(
+        &self,
+        connection: &SessionStoreConnection,
+    ) -> Option<u32> {
+        match (
+            self.maximum_retries_on_id_collision,
+            connection.maximum_retries_on_id_collision(),
+        ) {
+            (Some(store), Some(connector)) => Some(store.min(connector)),
+            (store, connector) => store.or(connector),
+        }
+    }
+
+    /// The current time, according to the configured [`ClockAuthority`].
+    pub(crate) async fn now(
+        &self,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<DateTime<Utc>, Error<SessionStoreConnection::Error>> {
+        match self.clock_authority {
+            ClockAuthority::Local => Ok(self.clock.0.now()),
+            ClockAuthority::Backend => Ok(self
+                .intercept(ConnectorOperation::Now, connection.now())
+                .await?
+                .unwrap_or_else(|| self.clock.0.now())),
+        }
+    }
+
+    fn renewal_strategy_for(&self, session: &Session<SessionData>) -> SessionRenewalStrategy {
+        self.renewal_strategy_selector
+            .as_ref()
+            .and_then(|selector| selector.0.select(session.data()))
+            .unwrap_or_else(|| self.session_renewal_strategy())
+    }
+
     async fn try_store_session(
         &self,
         session: &Session<SessionData>,
+        now: DateTime<Utc>,
         connection: &mut SessionStoreConnection,
     ) -> Result<WriteSessionResult<SessionCookieCommand>, Error<SessionStoreConnection::Error>>
     {
         match &session.state {
             SessionState::NewChanged { expiry, data } => {
-                let cookie_value = self.cookie_generator.generate_cookie();
-                let id = SessionId::from_cookie_value(&cookie_value);
-                Ok(connection
-                    .create_session(&id, expiry, data)
+                let (cookie_value, id) = self.generate_session_id(None, connection)?;
+                Ok(self
+                    .intercept(
+                        ConnectorOperation::CreateSession,
+                        connection.create_session(&id, expiry, &session.metadata, data),
+                    )
                     .await?
-                    .map(|()| SessionCookieCommand::Set {
-                        cookie_value,
-                        expiry: *expiry,
-                    }))
+                    .map(|()| self.set_cookie_command(cookie_value, *expiry, now)))
             }
             SessionState::Changed {
                 current_id: previous_id,
                 expiry,
                 data,
             } => {
-                let cookie_value = self.cookie_generator.generate_cookie();
-                let current_id = SessionId::from_cookie_value(&cookie_value);
-                Ok(connection
-                    .update_session(&current_id, previous_id, expiry, data)
+                let (cookie_value, current_id) =
+                    self.generate_session_id(Some(previous_id), connection)?;
+                Ok(self
+                    .intercept(
+                        ConnectorOperation::UpdateSession,
+                        connection.update_session(
+                            &current_id,
+                            previous_id,
+                            expiry,
+                            &session.metadata,
+                            data,
+                        ),
+                    )
                     .await?
-                    .map(|()| SessionCookieCommand::Set {
-                        cookie_value,
-                        expiry: *expiry,
-                    }))
+                    .map(|()| self.set_cookie_command(cookie_value, *expiry, now)))
             }
             SessionState::Deleted { current_id } => {
-                connection.delete_session(current_id).await?;
-                Ok(WriteSessionResult::Ok(SessionCookieCommand::Delete))
+                match self.config.get().deletion_mode {
+                    DeletionMode::Hard => {
+                        self.intercept(
+                            ConnectorOperation::DeleteSession,
+                            connection.delete_session(current_id),
+                        )
+                        .await?
+                    }
+                    DeletionMode::Tombstone { time_to_live } => {
+                        self.intercept(
+                            ConnectorOperation::CreateTombstone,
+                            connection
+                                .create_tombstone(current_id, saturating_add(now, time_to_live)),
+                        )
+                        .await?
+                    }
+                }
+                Ok(WriteSessionResult::Ok(SessionCookieCommand::Delete {
+                    settings: self.cookie_settings.clone(),
+                    reason: Some(CookieDeletionReason::SessionDeleted),
+                }))
             }
             SessionState::NewUnchanged { .. }
             | SessionState::Unchanged { .. }
@@ -190,12 +1058,47 @@ impl<
         }
     }
 
-    /// Empties the entire store, deleting all sessions.
+    /// Empties the store, deleting all sessions.
+    ///
+    /// If this session store has a [key prefix](SessionStore::set_key_prefix), then only the sessions created
+    /// by session stores with the same key prefix are deleted, see [`SessionStoreConnector::clear_namespace`].
+    /// Otherwise, the entire backend is cleared, including sessions of other applications that share it.
     pub async fn clear_store(
         &self,
         connection: &mut SessionStoreConnection,
     ) -> Result<(), Error<SessionStoreConnection::Error>> {
-        connection.clear().await
+        if let Some(key_prefix) = &self.key_prefix {
+            self.intercept(
+                ConnectorOperation::ClearNamespace,
+                connection.clear_namespace(key_prefix),
+            )
+            .await
+        } else {
+            self.intercept(ConnectorOperation::Clear, connection.clear())
+                .await
+        }
+    }
+
+    /// Prepare the sessions identified by the given cookie values for fast access,
+    /// if the connector supports this e.g. via a cache.
+    /// This is useful to achieve predictable latency after restarts, or before batch jobs that touch a known set of sessions.
+    ///
+    /// Invalid cookie values are ignored.
+    pub async fn preload<'cookie_value>(
+        &self,
+        cookie_values: impl IntoIterator<Item = &'cookie_value CookieValue>,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<(), Error<SessionStoreConnection::Error>> {
+        let ids: Vec<_> = cookie_values
+            .into_iter()
+            .filter(|cookie_value| self.cookie_generator.is_valid_cookie(cookie_value.expose()))
+            .map(|cookie_value| self.session_id_from_cookie_value(cookie_value))
+            .collect();
+        self.intercept(
+            ConnectorOperation::PreloadSessions,
+            connection.preload_sessions(&ids),
+        )
+        .await
     }
 
     /// Get a session from the storage backend.
@@ -204,45 +1107,330 @@ impl<
     ///
     /// The return value is `Ok(Some(_))` if there is a session identified by the given cookie that is not expired,
     /// or `Ok(None)` if there is no such session that is not expired.
+    /// Cookies with a wrong length or invalid characters result in [`Error::WrongCookieLength`] or
+    /// [`Error::InvalidCookieValue`] respectively.
+    ///
+    /// Sessions that are bound to a channel are treated according to the [`ChannelBindingPolicy`],
+    /// use [`load_session_with_channel_binding`](SessionStore::load_session_with_channel_binding) to load them.
     pub async fn load_session(
         &self,
-        cookie_value: impl AsRef<str>,
+        cookie_value: &CookieValue,
         connection: &mut SessionStoreConnection,
     ) -> Result<Option<Session<SessionData>>, Error<SessionStoreConnection::Error>> {
-        if cookie_value.as_ref().as_bytes().len() != CookieGenerator::COOKIE_LENGTH {
-            return Err(Error::WrongCookieLength {
-                expected: CookieGenerator::COOKIE_LENGTH,
-                actual: cookie_value.as_ref().as_bytes().len(),
+        self.load_session_checked(cookie_value, None, connection)
+            .await
+    }
+
+    /// Like [`load_session`](SessionStore::load_session), but with the channel binding value of the current connection,
+    /// e.g. a TLS exporter.
+    /// If the session is bound to a different channel, the [`ChannelBindingPolicy`] of this session store is applied,
+    /// see [`Session::bind_to_channel`].
+    pub async fn load_session_with_channel_binding(
+        &self,
+        cookie_value: &CookieValue,
+        channel_binding: &[u8],
+        connection: &mut SessionStoreConnection,
+    ) -> Result<Option<Session<SessionData>>, Error<SessionStoreConnection::Error>> {
+        self.load_session_checked(cookie_value, Some(channel_binding), connection)
+            .await
+    }
+
+    async fn load_session_checked(
+        &self,
+        cookie_value: &CookieValue,
+        channel_binding: Option<&[u8]>,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<Option<Session<SessionData>>, Error<SessionStoreConnection::Error>> {
+        self.check_cookie_value(cookie_value)?;
+
+        let session_id = self.session_id_from_cookie_value(cookie_value);
+        #[cfg(feature = "tracing-spans")]
+        let span = tracing::debug_span!(
+            "load_session",
+            session_id = %session_id.redacted(),
+            found = tracing::field::Empty,
+        );
+        let load = async {
+            let session = self
+                .intercept(
+                    ConnectorOperation::ReadSession,
+                    connection.read_session(session_id.clone()),
+                )
+                .await?;
+            self.check_loaded_session(
+                cookie_value,
+                session_id,
+                session,
+                channel_binding,
+                connection,
+            )
+            .await
+        };
+        #[cfg(feature = "tracing-spans")]
+        let load = tracing::Instrument::instrument(load, span.clone());
+        let session = load.await?;
+        #[cfg(feature = "tracing-spans")]
+        span.record("found", session.is_some());
+        #[cfg(feature = "metrics")]
+        instrumentation::record_load(session.is_some());
+        Ok(session)
+    }
+
+    /// Load the sessions identified by the given cookie values, like [`load_session`](SessionStore::load_session)
+    /// does for each of them.
+    ///
+    /// The sessions are read with a single call to [`SessionStoreConnector::read_sessions`],
+    /// so backends that support batch reads need only one round-trip, e.g. for jobs that process many queued sessions.
+    /// The result contains one entry per cookie value, in the same order.
+    /// If any of the cookie values is invalid, an error is returned and no session is read.
+    pub async fn load_sessions<'cookie_value>(
+        &self,
+        cookie_values: impl IntoIterator<Item = &'cookie_value CookieValue>,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<Vec<Option<Session<SessionData>>>, Error<SessionStoreConnection::Error>>
+    where
+        SessionData: Send,
+    {
+        let cookie_values: Vec<_> = cookie_values.into_iter().collect();
+        for cookie_value in &cookie_values {
+            self.check_cookie_value(cookie_value)?;
+        }
+
+        let ids: Vec<_> = cookie_values
+            .iter()
+            .map(|cookie_value| self.session_id_from_cookie_value(cookie_value))
+            .collect();
+        let sessions = self
+            .intercept(
+                ConnectorOperation::ReadSessions,
+                connection.read_sessions(&ids),
+            )
+            .await?;
+        if sessions.len() != ids.len() {
+            return Err(Error::BatchReadLengthMismatch {
+                expected: ids.len(),
+                actual: sessions.len(),
             });
         }
 
-        let session_id = SessionId::from_cookie_value(cookie_value.as_ref());
-        if let Some(mut session) = connection.read_session(session_id).await? {
-            let now = Utc::now();
-            if session.is_expired(now) {
+        let mut result = Vec::with_capacity(ids.len());
+        for ((cookie_value, session_id), session) in
+            cookie_values.into_iter().zip(ids).zip(sessions)
+        {
+            let session = self
+                .check_loaded_session(cookie_value, session_id, session, None, connection)
+                .await?;
+            #[cfg(feature = "metrics")]
+            instrumentation::record_load(session.is_some());
+            result.push(session);
+        }
+        Ok(result)
+    }
+
+    async fn check_loaded_session(
+        &self,
+        cookie_value: &CookieValue,
+        session_id: SessionId,
+        session: Option<Session<SessionData>>,
+        channel_binding: Option<&[u8]>,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<Option<Session<SessionData>>, Error<SessionStoreConnection::Error>> {
+        if let Some(mut session) = session {
+            if self.access_audit {
+                session.enable_access_audit();
+            }
+            let channel_mismatch = !session.matches_channel(channel_binding);
+            if channel_mismatch {
+                match (self.channel_binding_policy, channel_binding) {
+                    (ChannelBindingPolicy::Rotate, Some(channel_binding)) => {
+                        tracing::info!("Rotating a session that was loaded over a different channel than it is bound to");
+                        session.bind_to_channel(channel_binding);
+                    }
+                    (ChannelBindingPolicy::Reject | ChannelBindingPolicy::Rotate, _) => {
+                        warn!("Rejected a session that was loaded over a different channel than it is bound to, which may indicate a stolen session cookie");
+                        return Ok(None);
+                    }
+                    (ChannelBindingPolicy::Flag, _) => {
+                        warn!("A session was loaded over a different channel than it is bound to, which may indicate a stolen session cookie");
+                    }
+                    (ChannelBindingPolicy::Ignore, _) => {}
+                }
+            }
+            if let Some(schema_check) = &self.schema_check {
+                if schema_check.0.discard(session.metadata.schema_fingerprint) {
+                    return Ok(None);
+                }
+            }
+            if session.metadata.cookie_settings_version.unwrap_or(0)
+                != self.cookie_settings_version()
+            {
+                // Storing the session emits the same cookie again, with the new settings.
+                session.reemit_cookie(cookie_value.clone());
+            }
+            if self.is_legacy_cookie_value(cookie_value) {
+                // Storing the session issues a cookie of the current format.
+                session.mark_changed();
+            }
+
+            let enforces_expiry = connection.enforces_expiry();
+            let renewal_strategy = self.renewal_strategy_for(&session);
+            if enforces_expiry
+                && matches!(renewal_strategy, SessionRenewalStrategy::Ignore)
+                && session.metadata.assurance.is_none()
+                && self.pre_expiry_hook.is_none()
+                && self.anomaly_scorer.is_none()
+                && self.expiry_sanitization.is_none()
+            {
+                // The backend does not return expired sessions, and there is nothing to renew, decay or notify,
+                // so we do not even need to know the current time.
+                return Ok(Some(session));
+            }
+
+            let now = self.now(connection).await?;
+            if !self.sanitize_expiry(&mut session, now)?
+                || (!enforces_expiry && session.is_expired(now))
+            {
+                if let Some(recorder) = &self.runtime_statistics {
+                    recorder.0.record_expiry(&session);
+                }
+                self.emit_event(
+                    SessionEvent::ExpiredOnLoad {
+                        expiry: *session.expiry(),
+                    },
+                    &session.metadata,
+                );
+
                 // We could delete expired sessions here, but that does not make sense:
                 // the client will not purposefully send us an expired session cookie, so only in the unlikely
                 // event that the session expires while being transmitted this will actually be triggered.
                 return Ok(None);
             }
 
-            self.session_renewal_strategy
-                .apply_to_session(&mut session, now);
+            session.metadata.decay(now);
+            let previous_expiry = *session.expiry();
+            renewal_strategy.apply_to_session(&mut session, now);
+            if *session.expiry() != previous_expiry {
+                self.emit_event(
+                    SessionEvent::Renewed {
+                        previous_expiry,
+                        expiry: *session.expiry(),
+                    },
+                    &session.metadata,
+                );
+                session.metadata.renewal_count = Some(
+                    session
+                        .metadata
+                        .renewal_count
+                        .unwrap_or(0)
+                        .saturating_add(1),
+                );
+            }
 
-            Ok(Some(session))
+            if let Some((window, hook)) = &self.pre_expiry_hook {
+                if let SessionExpiry::DateTime(expiry) = *session.expiry() {
+                    let remaining = expiry - now;
+                    if remaining <= *window {
+                        hook.0(&mut session, remaining);
+                    }
+                }
+            }
+
+            self.score_anomalies(session, &session_id, channel_mismatch, now, connection)
+                .await
         } else {
+            if matches!(
+                self.config.get().deletion_mode,
+                DeletionMode::Tombstone { .. }
+            ) {
+                let now = self.now(connection).await?;
+                if self
+                    .intercept(
+                        ConnectorOperation::IsTombstone,
+                        connection.is_tombstone(&session_id, now),
+                    )
+                    .await?
+                {
+                    warn!("A client attempted to load a deleted session, which may indicate a replay of a stolen session cookie");
+                    self.score_retired_id();
+                }
+            }
             Ok(None)
         }
     }
+
+    /// Apply the session renewal strategy to the given session, like [`load_session`](SessionStore::load_session) does.
+    ///
+    /// The strategy is selected like for loaded sessions, see [`set_renewal_strategy_selector`](SessionStore::set_renewal_strategy_selector),
+    /// and the current time is determined according to the [`ClockAuthority`].
+    /// This allows custom load paths, e.g. batch loads or websocket revalidation, to reuse the exact renewal logic.
+    pub async fn apply_renewal(
+        &self,
+        session: &mut Session<SessionData>,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<(), Error<SessionStoreConnection::Error>> {
+        let renewal_strategy = self.renewal_strategy_for(session);
+        if !matches!(renewal_strategy, SessionRenewalStrategy::Ignore) {
+            let now = self.now(connection).await?;
+            renewal_strategy.apply_to_session(session, now);
+        }
+        Ok(())
+    }
+
+    /// Check if the session identified by the given cookie value still exists and is not expired.
+    ///
+    /// This is meant for long-lived connections like websockets, which should be terminated when the
+    /// session is deleted or expires while they are open.
+    /// Unlike [`load_session`](SessionStore::load_session), this does not apply the session renewal strategy,
+    /// and depending on the backend it may not need to read the session data.
+    ///
+    /// Note that a session whose id was rotated by another request is no longer valid under its old cookie value.
+    pub async fn is_still_valid(
+        &self,
+        cookie_value: &CookieValue,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<bool, Error<SessionStoreConnection::Error>> {
+        self.check_cookie_value(cookie_value)?;
+
+        let session_id = self.session_id_from_cookie_value(cookie_value);
+        let now = self.now(connection).await?;
+        self.intercept(
+            ConnectorOperation::IsSessionValid,
+            connection.is_session_valid(&session_id, now),
+        )
+        .await
+    }
 }
 
+/// Clones share their runtime configuration with the original session store.
 impl<SessionData, SessionStoreConnection, CookieGenerator: Clone> Clone
     for SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
 {
     fn clone(&self) -> Self {
         Self {
             cookie_generator: self.cookie_generator.clone(),
-            session_renewal_strategy: self.session_renewal_strategy,
+            cookie_settings: self.cookie_settings.clone(),
+            config: self.config.clone(),
+            hashing_policy: self.hashing_policy,
+            maximum_retries_on_id_collision: self.maximum_retries_on_id_collision,
+            maximum_sessions: self.maximum_sessions,
+            clock_authority: self.clock_authority,
+            clock: self.clock.clone(),
+            channel_binding_policy: self.channel_binding_policy,
+            access_audit: self.access_audit,
+            key_prefix: self.key_prefix.clone(),
+            renewal_strategy_selector: self.renewal_strategy_selector.clone(),
+            suppression_predicate: self.suppression_predicate.clone(),
+            session_sampler: self.session_sampler.clone(),
+            runtime_statistics: self.runtime_statistics.clone(),
+            pre_expiry_hook: self.pre_expiry_hook.clone(),
+            schema_check: self.schema_check.clone(),
+            priority_classifier: self.priority_classifier.clone(),
+            anomaly_scorer: self.anomaly_scorer.clone(),
+            event_listener: self.event_listener.clone(),
+            interceptor: self.interceptor.clone(),
+            retry_policy: self.retry_policy.clone(),
+            expiry_sanitization: self.expiry_sanitization,
+            legacy_cookie_format: self.legacy_cookie_format.clone(),
             data: self.data,
             connection: self.connection,
         }
@@ -264,13 +1452,63 @@ pub trait SessionStoreConnector<SessionData> {
     /// Writing a session may fail if the session id already exists.
     /// This constant indicates how often the caller should retry with different randomly generated ids until it should give up.
     /// The value `None` indicates that the caller should never give up, possibly looping infinitely.
+    ///
+    /// The session store treats this as a cap on its own
+    /// [retry policy](SessionStore::set_maximum_retries_on_id_collision), if set.
     fn maximum_retries_on_id_collision(&self) -> Option<u32>;
 
-    /// Create a session with the given `current_id`, `expiry` and `data`.
+    /// Returns true if the given error is transient, e.g. a refused connection,
+    /// such that the failed operation may succeed if it is retried.
+    /// The session store retries writes that fail with transient errors according to its
+    /// [retry policy](SessionStore::set_retry_policy).
+    /// Writes are not idempotent, so errors after which the operation may have been applied anyway,
+    /// like a timeout while waiting for the response, should not be reported as transient.
+    ///
+    /// The default implementation returns false.
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        let _ = error;
+        false
+    }
+
+    /// Returns true if the backend natively expires sessions, e.g. via Redis TTLs,
+    /// such that [`read_session`](SessionStoreConnector::read_session) never returns expired sessions.
+    /// In this case, the session store skips its own expiry check when loading sessions.
+    ///
+    /// Only return true if expiry is exact. Backends like DynamoDB, which delete expired records only eventually,
+    /// must return false.
+    ///
+    /// The default implementation returns false.
+    fn enforces_expiry(&self) -> bool {
+        false
+    }
+
+    /// Returns true if a session may be stored under the newly generated id `new_id`,
+    /// where `previous_id` is the id of the session before the write, or `None` if the session is new.
+    ///
+    /// If this returns false, the session store generates a different id before writing,
+    /// without counting this as an id collision or consulting its [retry policy](SessionStore::set_retry_policy).
+    /// This allows connectors that route sessions by their id to keep a session on the same backend.
+    ///
+    /// The default implementation returns true.
+    fn accepts_session_id(&self, new_id: &SessionId, previous_id: Option<&SessionId>) -> bool {
+        let _ = (new_id, previous_id);
+        true
+    }
+
+    /// Describe this connector and the connectors it wraps, see [`SessionStore::describe`].
+    ///
+    /// The default implementation returns the type name of the connector.
+    /// Wrappers should override this to include the descriptions of the connectors they wrap.
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::new(std::any::type_name::<Self>())
+    }
+
+    /// Create a session with the given `current_id`, `expiry`, `metadata` and `data`.
     async fn create_session(
         &mut self,
         current_id: &SessionId,
         expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
         data: &SessionData,
     ) -> Result<WriteSessionResult, Error<Self::Error>>;
 
@@ -280,12 +1518,12 @@ pub trait SessionStoreConnector<SessionData> {
         id: SessionId,
     ) -> Result<Option<Session<SessionData>>, Error<Self::Error>>;
 
-    /// Update a session with new ids, data and expiry.
+    /// Update a session with new ids, data, metadata and expiry.
     ///
     /// This method must be implemented as follows:
     ///  1. Find the session `A` identified by the given `previous_id`.
     ///  2. Remap `A` to be identified by `current_id` instead of `previous_id`.
-    ///  3. Set `A.expiry = expiry` and `A.data = data`.
+    ///  3. Set `A.expiry = expiry`, `A.metadata = metadata` and `A.data = data`.
     ///
     /// **Security:** To avoid race conditions, this method must not allow concurrent updates of a session id.
     /// It must never happen that by updating a session id `X` concurrently, there are suddenly two different session ids `Y` and `Z`, both stemming from `X`.
@@ -295,14 +1533,161 @@ pub trait SessionStoreConnector<SessionData> {
         current_id: &SessionId,
         previous_id: &SessionId,
         expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
         data: &SessionData,
     ) -> Result<WriteSessionResult, Error<Self::Error>>;
 
     /// Delete the session with the given `id`.
     async fn delete_session(&mut self, id: &SessionId) -> Result<(), Error<Self::Error>>;
 
+    /// Delete the sessions with the given `ids`.
+    /// Ids of sessions that do not exist are ignored.
+    ///
+    /// The default implementation calls [`delete_session`](SessionStoreConnector::delete_session) for each id.
+    /// Backends that can delete multiple sessions in a single call should override this, such that mass revocations
+    /// need only a few backend calls.
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        for id in ids {
+            self.delete_session(id).await?;
+        }
+        Ok(())
+    }
+
+    /// Replace the session with the given `id` by a tombstone that expires at `expiry`.
+    /// This is used instead of [`delete_session`](SessionStoreConnector::delete_session) if the session store
+    /// is configured with [`DeletionMode::Tombstone`].
+    ///
+    /// A tombstone must never be returned by [`read_session`](SessionStoreConnector::read_session),
+    /// and it must block its id from being reused until it expires.
+    /// Expired tombstones can be deleted together with expired sessions.
+    ///
+    /// The default implementation deletes the session without leaving a tombstone.
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        let _ = expiry;
+        self.delete_session(id).await
+    }
+
+    /// Returns true if there is a tombstone for the given `id` that is not expired at `now`.
+    ///
+    /// The default implementation always returns false.
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        let _ = (id, now);
+        Ok(false)
+    }
+
+    /// Returns true if a session with the given `id` exists and is not expired at `now`.
+    ///
+    /// The default implementation reads the session.
+    /// Backends that can check for existence more cheaply should override this.
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        Ok(self
+            .read_session(id.clone())
+            .await?
+            .and_then(|session| session.into_data_expiry_pair().1)
+            .is_some_and(|expiry| !expiry.is_expired(now)))
+    }
+
+    /// Returns the current time according to the backend, if it has an authoritative clock.
+    /// This is used if the session store is configured with [`ClockAuthority::Backend`].
+    ///
+    /// The default implementation returns `None`.
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        Ok(None)
+    }
+
+    /// Called with the context of the request on whose behalf the following operations are executed,
+    /// and with `None` once they are done.
+    /// See [`SessionStore::load_session_with_context`] and [`SessionStore::store_session_with_context`].
+    ///
+    /// The default implementation ignores the context.
+    fn set_request_context(&mut self, context: Option<&RequestContext>) {
+        let _ = context;
+    }
+
+    /// Read the sessions with the given `ids`, like [`read_session`](SessionStoreConnector::read_session) does for each of them.
+    /// The result must contain one entry per id, in the same order.
+    ///
+    /// The default implementation calls `read_session` for each id.
+    /// Backends that can read multiple sessions in a single call should override this,
+    /// such that [`SessionStore::load_sessions`] needs only one round-trip.
+    async fn read_sessions(
+        &mut self,
+        ids: &[SessionId],
+    ) -> Result<Vec<Option<Session<SessionData>>>, Error<Self::Error>>
+    where
+        SessionData: Send,
+    {
+        let mut sessions = Vec::with_capacity(ids.len());
+        for id in ids {
+            sessions.push(self.read_session(id.clone()).await?);
+        }
+        Ok(sessions)
+    }
+
+    /// Prepare the sessions with the given `ids` for fast access, e.g. by loading them into a cache.
+    /// Ids of sessions that do not exist are ignored.
+    ///
+    /// The default implementation does nothing, which is correct for all connectors without a cache.
+    async fn preload_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        let _ = ids;
+        Ok(())
+    }
+
+    /// Return the number of stored sessions, including child sessions but excluding tombstones.
+    /// Expired sessions that were not yet deleted may be counted.
+    /// This is called before each creation of a session if the session store has a
+    /// [maximum number of sessions](SessionStore::set_maximum_sessions), so it should be cheap.
+    ///
+    /// The default implementation returns [`Error::UnsupportedOperation`].
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        Err(Error::UnsupportedOperation {
+            operation: "count_sessions",
+        })
+    }
+
     /// Delete all sessions in the store.
     async fn clear(&mut self) -> Result<(), Error<Self::Error>>;
+
+    /// Delete all sessions whose [`SessionMetadata::namespace`] is `namespace`, including their child sessions.
+    /// Sessions of other namespaces must not be touched.
+    ///
+    /// The default implementation returns [`Error::UnsupportedOperation`],
+    /// since falling back to [`clear`](SessionStoreConnector::clear) would delete the sessions of other namespaces.
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        let _ = namespace;
+        Err(Error::UnsupportedOperation {
+            operation: "clear_namespace",
+        })
+    }
+
+    /// Delete all sessions and tombstones that expired before `before`, and return the number of deleted sessions.
+    ///
+    /// Expired sessions are never returned by the session store, but they still occupy space in the backend
+    /// until they are deleted. This is called periodically by a [`SessionCleaner`](crate::SessionCleaner).
+    /// Backends that expire sessions natively, e.g. via Redis TTLs, can return zero.
+    ///
+    /// The default implementation returns [`Error::UnsupportedOperation`].
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        let _ = before;
+        Err(Error::UnsupportedOperation {
+            operation: "delete_expired_sessions",
+        })
+    }
 }
 
 /// The result of writing a session, indicating if the session could be written, or if the id collided.
@@ -337,34 +1722,306 @@ pub enum SessionCookieCommand {
     /// Set or update the session cookie.
     Set {
         /// The value of the session cookie.
-        cookie_value: String,
+        cookie_value: CookieValue,
         /// The expiry time of the session cookie.
+        /// See [`SessionExpiry::cookie_expires`] and [`SessionExpiry::cookie_max_age`] for the cookie attributes.
         expiry: SessionExpiry,
+        /// The remaining lifetime of the session cookie in whole seconds, rounded up,
+        /// or `None` if the session never expires.
+        /// It is computed with the clock of the session store when the command is created,
+        /// see [`ClockAuthority`], such that middleware does not need to compare the expiry with its own clock.
+        max_age: Option<std::time::Duration>,
+        /// The settings of the session cookie, see [`SessionStore::set_cookie_settings`].
+        settings: CookieSettings,
     },
     /// Delete the session cookie.
-    Delete,
+    ///
+    /// The cookie must be deleted with the same name, path and domain as it was set with,
+    /// otherwise the browser keeps the original cookie.
+    Delete {
+        /// The settings of the session cookie, see [`SessionStore::set_cookie_settings`].
+        settings: CookieSettings,
+        /// The reason for deleting the cookie, if known.
+        reason: Option<CookieDeletionReason>,
+    },
     /// Do not inform the client about any updates to the session cookie.
     /// This means that the cookie stayed the same.
     DoNothing,
 }
 
+impl SessionCookieCommand {
+    /// Returns the value of the `Set-Cookie` header that executes this command,
+    /// or `None` if the cookie does not need to be updated.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::{CookieSettings, CookieValue, SessionCookieCommand, SessionExpiry};
+    /// let command = SessionCookieCommand::Set {
+    ///     cookie_value: CookieValue::new("abc"),
+    ///     expiry: SessionExpiry::Never,
+    ///     max_age: None,
+    ///     settings: CookieSettings::default(),
+    /// };
+    /// assert_eq!(
+    ///     command.set_cookie_header().unwrap(),
+    ///     "session=abc; Path=/; Secure; HttpOnly; SameSite=Lax",
+    /// );
+    /// assert_eq!(SessionCookieCommand::DoNothing.set_cookie_header(), None);
+    /// ```
+    pub fn set_cookie_header(&self) -> Option<String> {
+        match self {
+            SessionCookieCommand::Set {
+                cookie_value,
+                expiry,
+                max_age,
+                settings,
+            } => {
+                let mut header = format!("{}={}", settings.name, cookie_value.expose());
+                if let Some(expires) = expiry.cookie_expires() {
+                    header.push_str("; Expires=");
+                    header.push_str(&expires);
+                }
+                if let Some(max_age) = max_age {
+                    header.push_str(&format!("; Max-Age={}", max_age.as_secs()));
+                }
+                settings.push_attributes(&mut header);
+                Some(header)
+            }
+            SessionCookieCommand::Delete { settings, .. } => {
+                let mut header = format!("{}=; Max-Age=0", settings.name);
+                settings.push_attributes(&mut header);
+                Some(header)
+            }
+            SessionCookieCommand::DoNothing => None,
+        }
+    }
+}
+
+/// The name, scope and attributes of the session cookie, which the web framework needs to set and delete the cookie.
+///
+/// The session store does not set cookies itself, but passes these settings along with
+/// [`SessionCookieCommand::Set`] and [`SessionCookieCommand::Delete`], such that middleware can build a complete
+/// `Set-Cookie` header from the command alone, see [`SessionCookieCommand::set_cookie_header`].
+/// This also ensures that middleware deletes exactly the cookie that was set, even if multiple session cookies are in use.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CookieSettings {
+    /// The name of the cookie.
+    pub name: String,
+    /// The `Path` attribute of the cookie, if any.
+    pub path: Option<String>,
+    /// The `Domain` attribute of the cookie, if any.
+    pub domain: Option<String>,
+    /// The `SameSite` attribute of the cookie, if any.
+    pub same_site: Option<SameSite>,
+    /// True if the cookie has the `Secure` attribute, i.e. it is only sent over HTTPS.
+    pub secure: bool,
+    /// True if the cookie has the `HttpOnly` attribute, i.e. it is not accessible to scripts.
+    pub http_only: bool,
+}
+
+impl CookieSettings {
+    /// Append the `Path`, `Domain`, `Secure`, `HttpOnly` and `SameSite` attributes to the given `Set-Cookie` header.
+    fn push_attributes(&self, header: &mut String) {
+        if let Some(path) = &self.path {
+            header.push_str("; Path=");
+            header.push_str(path);
+        }
+        if let Some(domain) = &self.domain {
+            header.push_str("; Domain=");
+            header.push_str(domain);
+        }
+        if self.secure {
+            header.push_str("; Secure");
+        }
+        if self.http_only {
+            header.push_str("; HttpOnly");
+        }
+        if let Some(same_site) = self.same_site {
+            header.push_str("; SameSite=");
+            header.push_str(match same_site {
+                SameSite::Strict => "Strict",
+                SameSite::Lax => "Lax",
+                SameSite::None => "None",
+            });
+        }
+    }
+}
+
+impl Default for CookieSettings {
+    /// A cookie named `session` with path `/`, no domain, `Secure`, `HttpOnly` and `SameSite=Lax`.
+    ///
+    /// The version of the cookie settings is not part of them, but of the runtime configuration,
+    /// see [`SessionStoreConfig::cookie_settings_version`](crate::SessionStoreConfig::cookie_settings_version).
+    /// It starts at 0, and incrementing it re-emits the cookies of all active sessions with the current settings
+    /// when they are next used.
+    fn default() -> Self {
+        Self {
+            name: "session".to_string(),
+            path: Some("/".to_string()),
+            domain: None,
+            same_site: Some(SameSite::Lax),
+            secure: true,
+            http_only: true,
+        }
+    }
+}
+
+/// The `SameSite` attribute of a cookie, see [`CookieSettings::same_site`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum SameSite {
+    /// The cookie is only sent with requests that originate from the site that set it.
+    Strict,
+    /// The cookie is additionally sent when navigating to the site from another site.
+    Lax,
+    /// The cookie is sent with all requests, which requires the `Secure` attribute in modern browsers.
+    None,
+}
+
+/// The reason why a session cookie is deleted, see [`SessionCookieCommand::Delete`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum CookieDeletionReason {
+    /// The session was deleted with [`Session::delete`].
+    SessionDeleted,
+    /// The client sent a cookie that does not belong to a valid session, and no new session was stored,
+    /// see [`SessionStore::store_session_clearing_stale_cookie`].
+    StaleCookie,
+}
+
 impl SessionRenewalStrategy {
-    fn apply_to_session<SessionData: Debug>(
+    /// Create an [`AutomaticRenewal`](SessionRenewalStrategy::AutomaticRenewal) strategy,
+    /// returning an error if the combination of durations is nonsensical.
+    ///
+    /// The `time_to_live` must be positive, and `maximum_remaining_time_to_live_for_renewal` must be
+    /// non-negative and smaller than `time_to_live`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::{InvalidRenewalStrategy, SessionRenewalStrategy};
+    /// # use chrono::Duration;
+    /// assert!(SessionRenewalStrategy::automatic_renewal(Duration::days(7), Duration::days(6)).is_ok());
+    /// assert!(matches!(
+    ///     SessionRenewalStrategy::automatic_renewal(Duration::days(7), Duration::days(7)),
+    ///     Err(InvalidRenewalStrategy::RenewalThresholdNotBelowTimeToLive { .. }),
+    /// ));
+    /// ```
+    pub fn automatic_renewal(
+        time_to_live: Duration,
+        maximum_remaining_time_to_live_for_renewal: Duration,
+    ) -> Result<Self, InvalidRenewalStrategy> {
+        if time_to_live <= Duration::zero() {
+            Err(InvalidRenewalStrategy::NonPositiveTimeToLive { time_to_live })
+        } else if maximum_remaining_time_to_live_for_renewal < Duration::zero() {
+            Err(InvalidRenewalStrategy::NegativeRenewalThreshold {
+                maximum_remaining_time_to_live_for_renewal,
+            })
+        } else if maximum_remaining_time_to_live_for_renewal >= time_to_live {
+            Err(InvalidRenewalStrategy::RenewalThresholdNotBelowTimeToLive {
+                time_to_live,
+                maximum_remaining_time_to_live_for_renewal,
+            })
+        } else {
+            Ok(Self::AutomaticRenewal {
+                time_to_live,
+                maximum_remaining_time_to_live_for_renewal,
+            })
+        }
+    }
+
+    /// Create an [`IdleAndAbsoluteTimeout`](SessionRenewalStrategy::IdleAndAbsoluteTimeout) strategy,
+    /// returning an error if the combination of durations is nonsensical.
+    ///
+    /// The `idle_timeout` must be positive, and `absolute_timeout` must not be smaller than `idle_timeout`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::{InvalidRenewalStrategy, SessionRenewalStrategy};
+    /// # use chrono::Duration;
+    /// assert!(SessionRenewalStrategy::idle_and_absolute_timeout(Duration::minutes(30), Duration::hours(12)).is_ok());
+    /// assert!(matches!(
+    ///     SessionRenewalStrategy::idle_and_absolute_timeout(Duration::hours(1), Duration::minutes(30)),
+    ///     Err(InvalidRenewalStrategy::AbsoluteTimeoutBelowIdleTimeout { .. }),
+    /// ));
+    /// ```
+    pub fn idle_and_absolute_timeout(
+        idle_timeout: Duration,
+        absolute_timeout: Duration,
+    ) -> Result<Self, InvalidRenewalStrategy> {
+        if idle_timeout <= Duration::zero() {
+            Err(InvalidRenewalStrategy::NonPositiveTimeToLive {
+                time_to_live: idle_timeout,
+            })
+        } else if absolute_timeout < idle_timeout {
+            Err(InvalidRenewalStrategy::AbsoluteTimeoutBelowIdleTimeout {
+                idle_timeout,
+                absolute_timeout,
+            })
+        } else {
+            Ok(Self::IdleAndAbsoluteTimeout {
+                idle_timeout,
+                absolute_timeout,
+            })
+        }
+    }
+
+    /// Create an [`AutomaticRenewal`](SessionRenewalStrategy::AutomaticRenewal) strategy that implements a sliding expiry:
+    /// sessions expire after `time_to_live` of inactivity.
+    ///
+    /// A session is renewed once a tenth of its time-to-live has passed,
+    /// which bounds the number of writes while keeping the expiry within 10% of the exact sliding expiry.
+    ///
+    /// Returns an error if `time_to_live` is not positive.
+    pub fn sliding(time_to_live: Duration) -> Result<Self, InvalidRenewalStrategy> {
+        let renewal_interval = (time_to_live / 10).max(Duration::nanoseconds(1));
+        Self::automatic_renewal(time_to_live, time_to_live - renewal_interval)
+    }
+
+    /// Apply this renewal strategy to the given session, assuming that the current time is `now`.
+    ///
+    /// This is what [`SessionStore::load_session`] does with every loaded session.
+    /// It is public such that custom load paths can reuse the exact renewal logic,
+    /// see also [`SessionStore::apply_renewal`] to select the strategy and current time like the session store.
+    ///
+    /// The expiry is never extended beyond the [deletion deadline](crate::SessionMetadata::deletion_deadline)
+    /// of the session, see [`Session::delete_at`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::{Session, SessionExpiry, SessionRenewalStrategy};
+    /// # use chrono::{Duration, Utc};
+    /// let now = Utc::now();
+    /// let mut session = Session::new_with_data(4);
+    /// SessionRenewalStrategy::sliding(Duration::hours(1))?.apply_to_session(&mut session, now);
+    /// assert_eq!(session.expiry(), &SessionExpiry::DateTime(now + Duration::hours(1)));
+    /// # Ok::<(), typed_session::InvalidRenewalStrategy>(())
+    /// ```
+    pub fn apply_to_session<SessionData: Debug>(
         &self,
         session: &mut Session<SessionData>,
         now: DateTime<Utc>,
     ) {
+        // Renewal never postpones a scheduled deletion.
+        let deletion_deadline = session
+            .metadata
+            .deletion_deadline
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
         match self {
             SessionRenewalStrategy::Ignore => { /* do nothing */ }
             SessionRenewalStrategy::AutomaticRenewal {
                 time_to_live,
                 maximum_remaining_time_to_live_for_renewal,
             } => {
-                let new_expiry = now + *time_to_live;
+                let new_expiry = saturating_add(now, *time_to_live).min(deletion_deadline);
                 match *session.expiry() {
                     SessionExpiry::DateTime(old_expiry) => {
                         // Renew only if within maximum remaining time.
-                        if old_expiry - now <= *maximum_remaining_time_to_live_for_renewal {
+                        if old_expiry - now <= *maximum_remaining_time_to_live_for_renewal
+                            && new_expiry > old_expiry
+                        {
                             session.set_expiry(new_expiry);
                         }
                     }
@@ -372,6 +2029,27 @@ impl SessionRenewalStrategy {
                     SessionExpiry::Never => session.set_expiry(new_expiry),
                 }
             }
+            SessionRenewalStrategy::IdleAndAbsoluteTimeout {
+                idle_timeout,
+                absolute_timeout,
+            } => {
+                let created_at = session.metadata.created_at.unwrap_or(now);
+                let absolute_expiry = saturating_add(created_at, *absolute_timeout);
+                let new_expiry = saturating_add(now, *idle_timeout)
+                    .min(absolute_expiry)
+                    .min(deletion_deadline);
+                let renewal_interval = (*idle_timeout / 10).max(Duration::nanoseconds(1));
+                let renew = match *session.expiry() {
+                    SessionExpiry::DateTime(old_expiry) => {
+                        old_expiry > absolute_expiry || new_expiry - old_expiry >= renewal_interval
+                    }
+                    SessionExpiry::Never => true,
+                };
+                if renew {
+                    session.metadata.created_at = Some(created_at);
+                    session.set_expiry(new_expiry);
+                }
+            }
         }
     }
 }
@@ -0,0 +1,128 @@
+use crate::{
+    ConnectorOperation, EnumerableSessionStoreConnector, Error, Histogram, SessionCookieGenerator,
+    SessionExpiry, SessionStore,
+};
+use rand::distributions::Open01;
+use rand::Rng;
+use std::fmt::Debug;
+
+/// The number of counts of a [`SessionAnalytics`] that a single session contributes to:
+/// the total, its expiry class or remaining time-to-live bucket, and its payload size bucket.
+const SENSITIVITY: f64 = 3.0;
+
+/// Aggregated statistics about all stored sessions, see [`SessionStore::export_session_analytics`].
+///
+/// It contains only counts, and no ids or data of individual sessions.
+/// Before handing it to an analytics pipeline, noise can be added with
+/// [`with_laplace_noise`](SessionAnalytics::with_laplace_noise), such that the presence of any single session cannot be inferred.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SessionAnalytics {
+    /// The number of stored sessions, including child sessions.
+    pub sessions: u64,
+    /// The number of stored sessions that are expired, but were not yet deleted.
+    pub expired_sessions: u64,
+    /// The number of stored sessions that never expire.
+    pub non_expiring_sessions: u64,
+    /// The remaining time-to-live in seconds of all other sessions.
+    pub remaining_time_to_live: Histogram,
+    /// The size of the session data of all sessions, as determined by the payload size function.
+    pub payload_size: Histogram,
+}
+
+impl SessionAnalytics {
+    /// Returns a copy of these statistics with Laplace noise added to every count,
+    /// such that releasing them is `epsilon`-differentially private with respect to adding or removing a single session.
+    ///
+    /// Smaller values of `epsilon` give stronger privacy, but noisier counts.
+    /// Noisy counts are rounded and clamped at zero, so they are not necessarily consistent with each other,
+    /// e.g. the histograms may not sum up to the number of sessions.
+    ///
+    /// **Panics** if `epsilon` is not positive and finite.
+    pub fn with_laplace_noise(&self, epsilon: f64) -> Self {
+        assert!(
+            epsilon > 0.0 && epsilon.is_finite(),
+            "epsilon must be positive and finite, but is {epsilon}"
+        );
+        let scale = SENSITIVITY / epsilon;
+        let mut rng = rand::thread_rng();
+        let mut noisy = |count: u64| {
+            // Inverse transform sampling of the Laplace distribution.
+            let uniform = rng.sample::<f64, _>(Open01) - 0.5;
+            let noise = -scale * uniform.signum() * (1.0 - 2.0 * uniform.abs()).ln();
+            (count as f64 + noise).round().max(0.0) as u64
+        };
+
+        Self {
+            sessions: noisy(self.sessions),
+            expired_sessions: noisy(self.expired_sessions),
+            non_expiring_sessions: noisy(self.non_expiring_sessions),
+            remaining_time_to_live: self.remaining_time_to_live.map_counts(&mut noisy),
+            payload_size: self.payload_size.map_counts(&mut noisy),
+        }
+    }
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: EnumerableSessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Aggregate statistics about all stored sessions, enumerating sessions in batches of `batch_size`.
+    /// The size of the session data is determined by `payload_size`, e.g. the length of its serialisation.
+    ///
+    /// The sessions are aggregated inside the session store, such that no ids or data leave it,
+    /// which makes this suitable to feed analytics pipelines about the health of the session store.
+    /// Consider adding noise with [`SessionAnalytics::with_laplace_noise`] before exporting the result.
+    ///
+    /// **Panics** if `batch_size` is zero.
+    pub async fn export_session_analytics(
+        &self,
+        batch_size: usize,
+        connection: &mut SessionStoreConnection,
+        payload_size: impl Fn(&SessionData) -> usize,
+    ) -> Result<SessionAnalytics, Error<SessionStoreConnection::Error>> {
+        assert!(batch_size > 0, "the batch size must not be zero");
+        let now = self.now(connection).await?;
+        let mut analytics = SessionAnalytics::default();
+        let mut after = None;
+
+        loop {
+            let ids = self
+                .intercept(
+                    ConnectorOperation::ListSessionIds,
+                    connection.list_session_ids(after.as_ref(), batch_size),
+                )
+                .await?;
+            let Some(last_id) = ids.last().cloned() else {
+                break;
+            };
+
+            for id in ids {
+                let Some(session) = self
+                    .intercept(ConnectorOperation::ReadSession, connection.read_session(id))
+                    .await?
+                else {
+                    continue;
+                };
+                analytics.sessions += 1;
+                match *session.expiry() {
+                    SessionExpiry::Never => analytics.non_expiring_sessions += 1,
+                    SessionExpiry::DateTime(expiry) if expiry <= now => {
+                        analytics.expired_sessions += 1
+                    }
+                    SessionExpiry::DateTime(expiry) => analytics
+                        .remaining_time_to_live
+                        .record((expiry - now).num_seconds().max(0) as u64),
+                }
+                analytics
+                    .payload_size
+                    .record(payload_size(session.data()).try_into().unwrap_or(u64::MAX));
+            }
+
+            after = Some(last_id);
+        }
+
+        Ok(analytics)
+    }
+}
@@ -0,0 +1,152 @@
+use crate::session::saturating_add;
+use crate::session_store::Hook;
+use crate::{
+    ConnectorOperation, DeletionMode, Error, Session, SessionCookieGenerator, SessionEvent,
+    SessionId, SessionMetadata, SessionStore, SessionStoreConnector,
+};
+use chrono::{DateTime, Duration, Utc};
+use std::fmt::Debug;
+use std::sync::Arc;
+use tracing::warn;
+
+/// The signals of a session load that are passed to an [`AnomalyScorer`].
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct AnomalySignals<'a> {
+    /// The metadata of the loaded session, or `None` if no session was loaded.
+    pub metadata: Option<&'a SessionMetadata>,
+    /// The time since the session was created, if known.
+    pub age: Option<Duration>,
+    /// The number of times the session was renewed, see [`SessionMetadata::renewal_count`].
+    pub renewal_count: u32,
+    /// The number of times the session was stored under a new id, see [`SessionMetadata::rotation_count`].
+    pub rotation_count: u32,
+    /// True if the session was loaded over a different channel than it is bound to,
+    /// see [`Session::bind_to_channel`].
+    pub channel_mismatch: bool,
+    /// True if the cookie belongs to a session that was deleted and replaced by a tombstone,
+    /// see [`DeletionMode::Tombstone`].
+    /// This may indicate the replay of a stolen session cookie.
+    pub retired_id: bool,
+}
+
+/// The action that [`SessionStore::load_session`] takes on a session, as decided by an [`AnomalyScorer`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum AnomalyAction {
+    /// Return the session as usual.
+    Allow,
+    /// Return the session, but drop its [assurance](Session::raise_assurance),
+    /// such that the user has to authenticate again before performing sensitive operations.
+    Challenge,
+    /// Delete the session according to the [`DeletionMode`], and return no session.
+    Revoke,
+}
+
+/// Scores the risk of each session load, see [`SessionStore::set_anomaly_scorer`].
+pub trait AnomalyScorer: Send + Sync {
+    /// Decide what to do with a session load with the given signals.
+    fn score(&self, signals: &AnomalySignals<'_>) -> AnomalyAction;
+}
+
+impl<F: Fn(&AnomalySignals<'_>) -> AnomalyAction + Send + Sync> AnomalyScorer for F {
+    fn score(&self, signals: &AnomalySignals<'_>) -> AnomalyAction {
+        self(signals)
+    }
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator: SessionCookieGenerator>
+    SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Sets a scorer that is called by [`load_session`](SessionStore::load_session) with the signals of each load,
+    /// e.g. the age of the session and whether its channel binding matches.
+    /// This gives risk engines a single integration point to allow, challenge or revoke sessions.
+    ///
+    /// The scorer is also called when a client presents the cookie of a session that was replaced by a tombstone.
+    /// Since no session is loaded in that case, its action has no effect, but the scorer can e.g. block the client.
+    pub fn set_anomaly_scorer(&mut self, scorer: impl AnomalyScorer + 'static) {
+        self.anomaly_scorer = Some(Hook(Arc::new(scorer)));
+    }
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Apply the [anomaly scorer](SessionStore::set_anomaly_scorer) to the given loaded session, if set.
+    pub(crate) async fn score_anomalies(
+        &self,
+        mut session: Session<SessionData>,
+        session_id: &SessionId,
+        channel_mismatch: bool,
+        now: DateTime<Utc>,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<Option<Session<SessionData>>, Error<SessionStoreConnection::Error>> {
+        let Some(scorer) = &self.anomaly_scorer else {
+            return Ok(Some(session));
+        };
+        let signals = AnomalySignals {
+            metadata: Some(&session.metadata),
+            age: session
+                .metadata
+                .created_at
+                .map(|created_at| now - created_at),
+            renewal_count: session.renewal_count(),
+            rotation_count: session.rotation_count(),
+            channel_mismatch,
+            retired_id: false,
+        };
+
+        match scorer.0.score(&signals) {
+            AnomalyAction::Allow => Ok(Some(session)),
+            AnomalyAction::Challenge => {
+                warn!("The anomaly scorer challenged a session");
+                if session.metadata.assurance.take().is_some() {
+                    session.mark_changed();
+                }
+                Ok(Some(session))
+            }
+            AnomalyAction::Revoke => {
+                warn!("The anomaly scorer revoked a session");
+                let config = self.config.get();
+                match config.deletion_mode {
+                    // The session is still rejected, but it stays in the backend until writes are allowed again.
+                    _ if config.read_only => return Ok(None),
+                    DeletionMode::Hard => {
+                        self.intercept(
+                            ConnectorOperation::DeleteSession,
+                            connection.delete_session(session_id),
+                        )
+                        .await?
+                    }
+                    DeletionMode::Tombstone { time_to_live } => {
+                        self.intercept(
+                            ConnectorOperation::CreateTombstone,
+                            connection
+                                .create_tombstone(session_id, saturating_add(now, time_to_live)),
+                        )
+                        .await?
+                    }
+                }
+                self.emit_event(SessionEvent::Deleted, &session.metadata);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Inform the [anomaly scorer](SessionStore::set_anomaly_scorer) about a load of a session that was replaced
+    /// by a tombstone, if set.
+    pub(crate) fn score_retired_id(&self) {
+        if let Some(scorer) = &self.anomaly_scorer {
+            let _ = scorer.0.score(&AnomalySignals {
+                metadata: None,
+                age: None,
+                renewal_count: 0,
+                rotation_count: 0,
+                channel_mismatch: false,
+                retired_id: true,
+            });
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::session::SessionState;
+use crate::{
+    ConnectorOperation, Error, Session, SessionCookieGenerator, SessionId, SessionStore,
+    SessionStoreConnector,
+};
+use async_trait::async_trait;
+use std::fmt::Debug;
+
+/// An extension of [`SessionStoreConnector`] for backends that can store binary attachments alongside a session.
+///
+/// Attachments are meant for occasional large blobs that should not be loaded with every request,
+/// e.g. an uploaded avatar that is pending confirmation.
+///
+/// Implementations must uphold the following in addition to the requirements of [`SessionStoreConnector`]:
+///  * [`delete_session`](SessionStoreConnector::delete_session) and
+///    [`create_tombstone`](SessionStoreConnector::create_tombstone) must also delete all attachments of the session,
+///    and so must the deletion of expired sessions.
+///  * [`update_session`](SessionStoreConnector::update_session) must keep the attachments of a session, even though its id changes.
+#[async_trait]
+pub trait AttachmentStoreConnector<SessionData>: SessionStoreConnector<SessionData> {
+    /// Store `bytes` under `key` for the session identified by `session_id`, replacing any previous attachment under `key`.
+    ///
+    /// Returns [`Error::UpdatedSessionDoesNotExist`] if the session does not exist.
+    async fn put_attachment(
+        &mut self,
+        session_id: &SessionId,
+        key: &str,
+        bytes: &[u8],
+    ) -> Result<(), Error<Self::Error>>;
+
+    /// Read the attachment under `key` of the session identified by `session_id`.
+    ///
+    /// Returns `None` if the session or the attachment does not exist.
+    async fn get_attachment(
+        &mut self,
+        session_id: &SessionId,
+        key: &str,
+    ) -> Result<Option<Vec<u8>>, Error<Self::Error>>;
+
+    /// Delete the attachment under `key` of the session identified by `session_id`.
+    ///
+    /// Returns true if the attachment existed.
+    async fn delete_attachment(
+        &mut self,
+        session_id: &SessionId,
+        key: &str,
+    ) -> Result<bool, Error<Self::Error>>;
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: AttachmentStoreConnector<SessionData>,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Store `bytes` under `key` alongside the given session.
+    ///
+    /// The attachment is not loaded together with the session, but has to be read with
+    /// [`get_attachment`](SessionStore::get_attachment). It is deleted together with the session.
+    /// The session must have been loaded from the session store, otherwise [`Error::SessionNotStored`] is returned.
+    pub async fn put_attachment(
+        &self,
+        session: &Session<SessionData>,
+        key: &str,
+        bytes: &[u8],
+        connection: &mut SessionStoreConnection,
+    ) -> Result<(), Error<SessionStoreConnection::Error>> {
+        let (SessionState::Unchanged { current_id, .. } | SessionState::Changed { current_id, .. }) =
+            &session.state
+        else {
+            return Err(Error::SessionNotStored);
+        };
+        self.intercept(
+            ConnectorOperation::PutAttachment,
+            connection.put_attachment(current_id, key, bytes),
+        )
+        .await
+    }
+
+    /// Read the attachment under `key` of the given session, see [`put_attachment`](SessionStore::put_attachment).
+    ///
+    /// Returns `None` if there is no such attachment, or if the session was not loaded from the session store.
+    pub async fn get_attachment(
+        &self,
+        session: &Session<SessionData>,
+        key: &str,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<Option<Vec<u8>>, Error<SessionStoreConnection::Error>> {
+        let (SessionState::Unchanged { current_id, .. } | SessionState::Changed { current_id, .. }) =
+            &session.state
+        else {
+            return Ok(None);
+        };
+        self.intercept(
+            ConnectorOperation::GetAttachment,
+            connection.get_attachment(current_id, key),
+        )
+        .await
+    }
+
+    /// Delete the attachment under `key` of the given session, see [`put_attachment`](SessionStore::put_attachment).
+    ///
+    /// Returns true if the attachment existed.
+    pub async fn delete_attachment(
+        &self,
+        session: &Session<SessionData>,
+        key: &str,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<bool, Error<SessionStoreConnection::Error>> {
+        let (SessionState::Unchanged { current_id, .. } | SessionState::Changed { current_id, .. }) =
+            &session.state
+        else {
+            return Ok(false);
+        };
+        self.intercept(
+            ConnectorOperation::DeleteAttachment,
+            connection.delete_attachment(current_id, key),
+        )
+        .await
+    }
+}
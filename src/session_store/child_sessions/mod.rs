@@ -0,0 +1,123 @@
+use crate::session::SessionState;
+use crate::{
+    ConnectorOperation, Error, Session, SessionCookieCommand, SessionCookieGenerator,
+    SessionExpiry, SessionId, SessionMetadata, SessionStore, SessionStoreConnector,
+    WriteSessionResult,
+};
+use async_trait::async_trait;
+use std::fmt::Debug;
+
+/// An extension of [`SessionStoreConnector`] for backends that support child sessions.
+///
+/// A child session is a normal session that is linked to a parent session.
+/// It is typically used for limited-scope tokens derived from a session, such as a token for a single file download.
+/// Child sessions are loaded and updated like any other session, but they are deleted together with their parent.
+///
+/// Implementations must uphold the following in addition to the requirements of [`SessionStoreConnector`]:
+///  * [`delete_session`](SessionStoreConnector::delete_session) and
+///    [`create_tombstone`](SessionStoreConnector::create_tombstone) must also delete all children of the session,
+///    recursively.
+///  * [`update_session`](SessionStoreConnector::update_session) must keep the links of a session to its parent and
+///    its children, even though its id changes.
+#[async_trait]
+pub trait ChildSessionStoreConnector<SessionData>: SessionStoreConnector<SessionData> {
+    /// Create a session with the given `current_id`, `expiry`, `metadata` and `data` as child of the session identified by `parent_id`.
+    ///
+    /// Returns [`Error::ParentSessionDoesNotExist`] if there is no session identified by `parent_id`.
+    async fn create_child_session(
+        &mut self,
+        parent_id: &SessionId,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>>;
+}
+
+/// A new child session that was spawned from a parent session with [`Session::spawn_child`].
+///
+/// It needs to be stored with [`SessionStore::store_child_session`].
+/// Afterwards, it behaves like any other session, except that it gets deleted when its parent gets deleted.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct ChildSession<SessionData> {
+    parent_id: SessionId,
+    session: Session<SessionData>,
+}
+
+impl<SessionData> ChildSession<SessionData> {
+    pub(crate) fn new(parent_id: SessionId, data: SessionData) -> Self {
+        Self {
+            parent_id,
+            session: Session::new_with_data(data),
+        }
+    }
+
+    /// A reference to the new child session.
+    pub fn session(&self) -> &Session<SessionData> {
+        &self.session
+    }
+
+    /// A mutable reference to the new child session, e.g. to set its expiry.
+    pub fn session_mut(&mut self) -> &mut Session<SessionData> {
+        &mut self.session
+    }
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: ChildSessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Store a new child session in the storage backend.
+    ///
+    /// The parent session must still be stored under the id it had when the child was spawned.
+    /// Hence, if the parent session was changed, the child needs to be stored before the parent.
+    ///
+    /// If the child session was deleted before it was stored, or the session store is in
+    /// [read-only mode](crate::SessionStoreConfig::read_only), then [`SessionCookieCommand::DoNothing`] is returned.
+    /// Otherwise, a [`SessionCookieCommand::Set`] for the cookie of the child session is returned.
+    pub async fn store_child_session(
+        &self,
+        child_session: ChildSession<SessionData>,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<SessionCookieCommand, Error<SessionStoreConnection::Error>> {
+        let ChildSession {
+            parent_id,
+            mut session,
+        } = child_session;
+        if session.is_deleted()
+            || self.suppress_write_in_read_only_mode(format_args!("storing child session"))
+        {
+            return Ok(SessionCookieCommand::DoNothing);
+        }
+        let now = self.now(connection).await?;
+        self.prepare_write(&mut session, now);
+        let SessionState::NewChanged { expiry, data } = &session.state else {
+            unreachable!("child sessions are always new and changed")
+        };
+
+        let mut retries = self.write_retries(connection);
+        loop {
+            retries.check_limit()?;
+            let (cookie_value, current_id) = self.generate_session_id(None, connection)?;
+            let result = self
+                .intercept(
+                    ConnectorOperation::CreateChildSession,
+                    connection.create_child_session(
+                        &parent_id,
+                        &current_id,
+                        expiry,
+                        &session.metadata,
+                        data,
+                    ),
+                )
+                .await;
+            if let Some(()) = self.retry_write(&mut retries, result, connection).await? {
+                self.after_write(&session, now);
+                return Ok(self.set_cookie_command(cookie_value, *expiry, now));
+            }
+        }
+    }
+}
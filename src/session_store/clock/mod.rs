@@ -0,0 +1,41 @@
+use crate::session_store::Hook;
+use crate::{SessionCookieGenerator, SessionStore};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+/// A source of the current time, see [`SessionStore::set_clock`].
+///
+/// Replacing the clock allows to test expiry and renewal logic deterministically, without sleeping,
+/// e.g. with the `MockClock` of the testkit.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+impl<F: Fn() -> DateTime<Utc> + Send + Sync> Clock for F {
+    fn now(&self) -> DateTime<Utc> {
+        self()
+    }
+}
+
+/// The default [`Clock`], which returns the system time via [`Utc::now`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UtcClock;
+
+impl Clock for UtcClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator: SessionCookieGenerator>
+    SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Sets the clock of this session store, which defaults to [`UtcClock`].
+    ///
+    /// The clock is used for all expiry checks and new expiry times, unless the [`ClockAuthority`](crate::ClockAuthority)
+    /// is [`Backend`](crate::ClockAuthority::Backend) and the connector supplies the time.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Hook(Arc::new(clock));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{ConfigNotWritable, DeletionMode, SessionRenewalStrategy};
+use std::sync::{Arc, RwLock};
+
+/// The runtime configuration of a [`SessionStore`](crate::SessionStore).
+///
+/// In contrast to e.g. the cookie generator, this configuration can be changed while the session store
+/// is in use, even if it is shared behind an [`Arc`], via a [`SessionStoreConfigHandle`].
+#[derive(Clone, Debug)]
+#[allow(missing_copy_implementations)]
+pub struct SessionStoreConfig {
+    /// The strategy to renew sessions.
+    pub session_renewal_strategy: SessionRenewalStrategy,
+    /// The way sessions are deleted from the session store.
+    pub deletion_mode: DeletionMode,
+    /// If true, the session store is in emergency read-only mode:
+    /// sessions are still loaded, but [`store_session`](crate::SessionStore::store_session) does not write anything
+    /// and returns [`SessionCookieCommand::DoNothing`](crate::SessionCookieCommand::DoNothing).
+    /// The same holds for the other ways of storing sessions, i.e.
+    /// [`store_child_session`](crate::SessionStore::store_child_session),
+    /// [`store_session_idempotently`](crate::SessionStore::store_session_idempotently) and
+    /// [`adopt_external_token`](crate::SessionStore::adopt_external_token),
+    /// and nonces are neither [issued](crate::SessionStore::issue_nonce)
+    /// nor [consumed](crate::SessionStore::consume_nonce).
+    /// This freezes session mutation e.g. during incident response or maintenance windows.
+    ///
+    /// Operations that write explicitly, like [`clear_store`](crate::SessionStore::clear_store), are not affected.
+    pub read_only: bool,
+    /// The version of the [cookie settings](crate::SessionStore::cookie_settings).
+    ///
+    /// Increment it after changing the attributes of the cookie, e.g. its `SameSite` policy.
+    /// Then the cookies of all active sessions are emitted again with the current settings when they are next used,
+    /// instead of keeping their old attributes until their session data changes.
+    /// Emitting a cookie again keeps its value, so the session keeps its id and is not written.
+    /// Its cookie is emitted on every use until the session is written for another reason, e.g. a renewal,
+    /// which records the current version in its [metadata](crate::SessionMetadata::cookie_settings_version).
+    ///
+    /// Since this is part of the runtime configuration, it can be incremented e.g. once all instances of
+    /// a rolling deployment use the new cookie settings.
+    pub cookie_settings_version: u32,
+}
+
+/// A handle to the runtime configuration of a [`SessionStore`](crate::SessionStore).
+///
+/// The handle can be cloned and sent to other threads, e.g. to a task that reloads the configuration
+/// from a file. All changes made through the handle are visible to all session stores sharing it.
+///
+/// Under the feature flag `watch-config`, the handle can alternatively be constructed from a
+/// `tokio::sync::watch::Receiver`, such that the configuration is pushed by e.g. a config service.
+#[derive(Clone, Debug)]
+pub struct SessionStoreConfigHandle {
+    config: ConfigSource,
+}
+
+#[derive(Clone, Debug)]
+enum ConfigSource {
+    Shared(Arc<RwLock<SessionStoreConfig>>),
+    #[cfg(feature = "watch-config")]
+    Watch(tokio::sync::watch::Receiver<SessionStoreConfig>),
+}
+
+impl SessionStoreConfig {
+    /// Create a new configuration with the given session renewal strategy.
+    pub fn new(session_renewal_strategy: SessionRenewalStrategy) -> Self {
+        Self {
+            session_renewal_strategy,
+            deletion_mode: Default::default(),
+            read_only: false,
+            cookie_settings_version: 0,
+        }
+    }
+}
+
+impl Default for SessionStoreConfig {
+    fn default() -> Self {
+        Self::new(SessionRenewalStrategy::Ignore)
+    }
+}
+
+impl SessionStoreConfigHandle {
+    /// Create a new handle with the given initial configuration.
+    pub fn new(config: SessionStoreConfig) -> Self {
+        Self {
+            config: ConfigSource::Shared(Arc::new(RwLock::new(config))),
+        }
+    }
+
+    /// Create a new handle that always returns the latest configuration sent through the watch channel.
+    #[cfg(feature = "watch-config")]
+    pub fn from_watch(receiver: tokio::sync::watch::Receiver<SessionStoreConfig>) -> Self {
+        Self {
+            config: ConfigSource::Watch(receiver),
+        }
+    }
+
+    /// Returns a copy of the current configuration.
+    pub fn get(&self) -> SessionStoreConfig {
+        match &self.config {
+            ConfigSource::Shared(config) => config.read().unwrap().clone(),
+            #[cfg(feature = "watch-config")]
+            ConfigSource::Watch(receiver) => receiver.borrow().clone(),
+        }
+    }
+
+    /// Returns true if the configuration can be changed through this handle,
+    /// i.e. if the handle was not constructed from a watch channel.
+    pub fn is_writable(&self) -> bool {
+        match &self.config {
+            ConfigSource::Shared(_) => true,
+            #[cfg(feature = "watch-config")]
+            ConfigSource::Watch(_) => false,
+        }
+    }
+
+    /// Replaces the current configuration.
+    ///
+    /// Returns [`ConfigNotWritable`] if the handle was constructed from a watch channel.
+    /// In this case, the configuration must be updated through the sender of the channel.
+    pub fn set(&self, config: SessionStoreConfig) -> Result<(), ConfigNotWritable> {
+        self.update(|current| *current = config)
+    }
+
+    /// Modifies the current configuration in place.
+    ///
+    /// Returns [`ConfigNotWritable`] if the handle was constructed from a watch channel.
+    /// In this case, the configuration must be updated through the sender of the channel.
+    pub fn update(&self, f: impl FnOnce(&mut SessionStoreConfig)) -> Result<(), ConfigNotWritable> {
+        match &self.config {
+            ConfigSource::Shared(config) => {
+                f(&mut config.write().unwrap());
+                Ok(())
+            }
+            #[cfg(feature = "watch-config")]
+            ConfigSource::Watch(_) => Err(ConfigNotWritable),
+        }
+    }
+}
+
+impl From<SessionStoreConfig> for SessionStoreConfigHandle {
+    fn from(config: SessionStoreConfig) -> Self {
+        Self::new(config)
+    }
+}
+
+#[cfg(feature = "watch-config")]
+impl From<tokio::sync::watch::Receiver<SessionStoreConfig>> for SessionStoreConfigHandle {
+    fn from(receiver: tokio::sync::watch::Receiver<SessionStoreConfig>) -> Self {
+        Self::from_watch(receiver)
+    }
+}
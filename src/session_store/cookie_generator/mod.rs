@@ -1,5 +1,5 @@
 use rand::distributions::{Alphanumeric, DistString};
-use std::fmt::Write;
+use std::fmt::{Debug, Write};
 use std::sync::Mutex;
 use tracing::warn;
 
@@ -16,7 +16,63 @@ pub trait SessionCookieGenerator {
     const COOKIE_LENGTH: usize;
 
     /// Generate a cookie, i.e. a string that is a valid HTTP cookie value.
+    ///
+    /// The cookie must be accepted by [`is_valid_cookie`](SessionCookieGenerator::is_valid_cookie),
+    /// otherwise storing sessions fails with [`Error::InvalidCookieValue`](crate::Error::InvalidCookieValue).
+    /// Generators that produce arbitrary strings can use [`percent_encode_cookie_value`] to make them valid,
+    /// taking into account that this may change their length.
     fn generate_cookie(&self) -> String;
+
+    /// Returns true if the given cookie may have been generated by this generator.
+    /// Both generated cookies and cookies received from clients are checked with this.
+    ///
+    /// The default implementation accepts all cookies of length [`COOKIE_LENGTH`](SessionCookieGenerator::COOKIE_LENGTH)
+    /// that are valid cookie values according to [`is_valid_cookie_value`].
+    fn is_valid_cookie(&self, cookie: &str) -> bool {
+        cookie.len() == Self::COOKIE_LENGTH && is_valid_cookie_value(cookie)
+    }
+}
+
+/// Returns true if the given string consists only of `cookie-octet`s as defined in [RFC 6265](https://www.rfc-editor.org/rfc/rfc6265#section-4.1.1),
+/// i.e. it can safely be used as the value of a `Set-Cookie` header without quoting.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::is_valid_cookie_value;
+/// assert!(is_valid_cookie_value("abc123-_."));
+/// assert!(!is_valid_cookie_value("a b"));
+/// assert!(!is_valid_cookie_value("a;b"));
+/// ```
+pub fn is_valid_cookie_value(value: &str) -> bool {
+    value.bytes().all(is_cookie_octet)
+}
+
+/// Percent-encode all bytes of the given string that are not `cookie-octet`s as defined in [RFC 6265](https://www.rfc-editor.org/rfc/rfc6265#section-4.1.1),
+/// as well as the percent sign itself.
+/// The result is always a valid cookie value according to [`is_valid_cookie_value`].
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{is_valid_cookie_value, percent_encode_cookie_value};
+/// assert_eq!(percent_encode_cookie_value("a b;c%"), "a%20b%3Bc%25");
+/// assert!(is_valid_cookie_value(&percent_encode_cookie_value("ä \"\\")));
+/// ```
+pub fn percent_encode_cookie_value(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if is_cookie_octet(byte) && byte != b'%' {
+            result.push(char::from(byte));
+        } else {
+            write!(&mut result, "%{byte:02X}").unwrap();
+        }
+    }
+    result
+}
+
+fn is_cookie_octet(byte: u8) -> bool {
+    matches!(byte, 0x21 | 0x23..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E)
 }
 
 /// The default cookie generator with focus on security.
@@ -35,6 +91,213 @@ impl SessionCookieGenerator for DefaultSessionCookieGenerator {
     }
 }
 
+/// A cookie generator that prepends a short non-secret tag to the cookies of another generator,
+/// e.g. to identify the environment or deployment that issued a cookie.
+///
+/// The tag has length `TAG_LENGTH` and does not add any entropy,
+/// so the security of the cookies depends only on the wrapped generator.
+/// Cookies are only accepted if they carry the tag of this generator,
+/// such that cookies of other environments are rejected early.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{DefaultSessionCookieGenerator, SessionCookieGenerator, TaggedCookieGenerator};
+/// let generator = TaggedCookieGenerator::<_, 6>::new("prod1.", DefaultSessionCookieGenerator);
+/// let cookie = generator.generate_cookie();
+/// assert!(cookie.starts_with("prod1."));
+/// assert_eq!(cookie.len(), 6 + DefaultSessionCookieGenerator::COOKIE_LENGTH);
+/// assert!(generator.is_valid_cookie(&cookie));
+/// assert!(!generator.is_valid_cookie(&cookie.replace("prod1.", "test1.")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TaggedCookieGenerator<CookieGenerator, const TAG_LENGTH: usize> {
+    tag: String,
+    inner: CookieGenerator,
+}
+
+impl<CookieGenerator, const TAG_LENGTH: usize> TaggedCookieGenerator<CookieGenerator, TAG_LENGTH> {
+    /// Create a generator that prepends `tag` to the cookies of `inner`.
+    ///
+    /// **Panics** if `tag` does not have length `TAG_LENGTH` or is not a valid cookie value according to [`is_valid_cookie_value`].
+    pub fn new(tag: impl Into<String>, inner: CookieGenerator) -> Self {
+        let tag = tag.into();
+        assert_eq!(
+            tag.len(),
+            TAG_LENGTH,
+            "the tag {tag:?} does not have length {TAG_LENGTH}"
+        );
+        assert!(
+            is_valid_cookie_value(&tag),
+            "the tag {tag:?} is not a valid cookie value"
+        );
+        Self { tag, inner }
+    }
+
+    /// The tag of this generator.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// The wrapped generator.
+    pub fn inner(&self) -> &CookieGenerator {
+        &self.inner
+    }
+}
+
+impl<CookieGenerator: SessionCookieGenerator, const TAG_LENGTH: usize> SessionCookieGenerator
+    for TaggedCookieGenerator<CookieGenerator, TAG_LENGTH>
+{
+    const COOKIE_LENGTH: usize = TAG_LENGTH + CookieGenerator::COOKIE_LENGTH;
+
+    fn generate_cookie(&self) -> String {
+        let mut cookie = self.tag.clone();
+        cookie.push_str(&self.inner.generate_cookie());
+        cookie
+    }
+
+    fn is_valid_cookie(&self, cookie: &str) -> bool {
+        cookie
+            .strip_prefix(self.tag.as_str())
+            .is_some_and(|cookie| self.inner.is_valid_cookie(cookie))
+    }
+}
+
+/// The length of the signature of a [`SignedCookieGenerator`], in hexadecimal characters.
+const SIGNATURE_LENGTH: usize = 32;
+
+/// A cookie generator that appends a keyed signature to the cookies of another generator,
+/// such that forged cookies are rejected before the session store is accessed.
+///
+/// Cookies have the format `random_id.signature`, where the signature is the hex-encoded truncated
+/// keyed blake3 hash of the random id under a key derived from a secret.
+/// Since [`SessionStore::load_session`](crate::SessionStore::load_session) validates cookies with
+/// [`is_valid_cookie`](SessionCookieGenerator::is_valid_cookie) first, cookies with invalid signatures are
+/// rejected with [`Error::InvalidCookieValue`](crate::Error::InvalidCookieValue) without a database round-trip.
+///
+/// The signature does not add entropy to the session id, it only allows to drop guessed cookies cheaply.
+/// To rotate the secret, construct the generator with the new secret and add the old one with
+/// [`add_verification_secret`](SignedCookieGenerator::add_verification_secret) until all old cookies have expired.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{DefaultSessionCookieGenerator, SessionCookieGenerator, SignedCookieGenerator};
+/// let generator = SignedCookieGenerator::new(DefaultSessionCookieGenerator, "a secret of sufficient length");
+/// let cookie = generator.generate_cookie();
+/// assert_eq!(cookie.len(), SignedCookieGenerator::<DefaultSessionCookieGenerator>::COOKIE_LENGTH);
+/// assert!(generator.is_valid_cookie(&cookie));
+/// let (random_id, _) = cookie.split_once('.').unwrap();
+/// assert!(!generator.is_valid_cookie(&format!("{random_id}.{}", "0".repeat(32))));
+/// ```
+#[derive(Clone)]
+pub struct SignedCookieGenerator<CookieGenerator> {
+    inner: CookieGenerator,
+    keys: Vec<[u8; blake3::KEY_LEN]>,
+}
+
+impl<CookieGenerator> SignedCookieGenerator<CookieGenerator> {
+    /// Create a generator that signs the cookies of `inner` with a key derived from `secret`.
+    ///
+    /// The secret should have at least 256 bits of entropy.
+    pub fn new(inner: CookieGenerator, secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            inner,
+            keys: vec![signature_key(secret.as_ref())],
+        }
+    }
+
+    /// Additionally accept cookies signed with a key derived from `secret`, e.g. a secret that was replaced in a rotation.
+    /// New cookies are always signed with the secret given to [`new`](SignedCookieGenerator::new).
+    pub fn add_verification_secret(&mut self, secret: impl AsRef<[u8]>) {
+        self.keys.push(signature_key(secret.as_ref()));
+    }
+
+    /// The wrapped generator.
+    pub fn inner(&self) -> &CookieGenerator {
+        &self.inner
+    }
+}
+
+fn signature_key(secret: &[u8]) -> [u8; blake3::KEY_LEN] {
+    blake3::derive_key("typed-session 2024-01-01 SignedCookieGenerator key", secret)
+}
+
+fn signature(key: &[u8; blake3::KEY_LEN], random_id: &str) -> [u8; SIGNATURE_LENGTH / 2] {
+    let hash = blake3::keyed_hash(key, random_id.as_bytes());
+    let mut signature = [0; SIGNATURE_LENGTH / 2];
+    signature.copy_from_slice(&hash.as_bytes()[..SIGNATURE_LENGTH / 2]);
+    signature
+}
+
+impl<CookieGenerator: SessionCookieGenerator> SessionCookieGenerator
+    for SignedCookieGenerator<CookieGenerator>
+{
+    const COOKIE_LENGTH: usize = CookieGenerator::COOKIE_LENGTH + 1 + SIGNATURE_LENGTH;
+
+    fn generate_cookie(&self) -> String {
+        let mut cookie = self.inner.generate_cookie();
+        let signature = signature(&self.keys[0], &cookie);
+        cookie.push('.');
+        for byte in signature {
+            write!(&mut cookie, "{byte:02x}").unwrap();
+        }
+        cookie
+    }
+
+    fn is_valid_cookie(&self, cookie: &str) -> bool {
+        let Some((random_id, encoded_signature)) = cookie.rsplit_once('.') else {
+            return false;
+        };
+        if encoded_signature.len() != SIGNATURE_LENGTH || !self.inner.is_valid_cookie(random_id) {
+            return false;
+        }
+        let mut given_signature = [0; SIGNATURE_LENGTH / 2];
+        for (byte, hex) in given_signature
+            .iter_mut()
+            .zip(encoded_signature.as_bytes().chunks(2))
+        {
+            let (Some(high), Some(low)) = (decode_hex_digit(hex[0]), decode_hex_digit(hex[1]))
+            else {
+                return false;
+            };
+            *byte = (high << 4) | low;
+        }
+
+        self.keys.iter().any(|key| {
+            // Compare in constant time, such that the signature cannot be guessed byte by byte.
+            signature(key, random_id)
+                .iter()
+                .zip(given_signature)
+                .fold(0, |difference, (expected, given)| {
+                    difference | (expected ^ given)
+                })
+                == 0
+        })
+    }
+}
+
+/// Decode a lowercase hexadecimal digit, as written by [`SignedCookieGenerator::generate_cookie`].
+///
+/// Other encodings of the same value, e.g. uppercase digits or a leading `+`, are rejected,
+/// such that each signature has exactly one valid encoding.
+fn decode_hex_digit(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        _ => None,
+    }
+}
+
+impl<CookieGenerator: Debug> Debug for SignedCookieGenerator<CookieGenerator> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignedCookieGenerator")
+            .field("inner", &self.inner)
+            .field("keys", &self.keys.len())
+            .finish()
+    }
+}
+
 /// A debug cookie generator that generates an ascending sequence of integers, formatted as strings padded with zeroes.
 #[derive(Debug, Default)]
 pub struct DebugSessionCookieGenerator {
@@ -0,0 +1,76 @@
+use crate::is_valid_cookie_value;
+use crate::session_store::Hook;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+type CookieValidator = dyn Fn(&str) -> bool + Send + Sync;
+
+/// A previous format of session cookies that is still accepted for a transition window,
+/// see [`SessionStore::set_legacy_cookie_format`](crate::SessionStore::set_legacy_cookie_format).
+///
+/// This allows to strengthen session cookies, e.g. by switching to a longer cookie generator,
+/// without invalidating the sessions of existing cookies.
+/// New cookies are always generated in the current format.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::LegacyCookieFormat;
+/// # use chrono::{Duration, Utc};
+/// let now = Utc::now();
+/// let format = LegacyCookieFormat::new(16, now + Duration::days(30))
+///     .with_validator(|cookie| cookie.bytes().all(|byte| byte.is_ascii_alphanumeric()));
+/// assert!(format.accepts("0123456789abcdef", now));
+/// assert!(!format.accepts("0123456789abcde-", now));
+/// assert!(!format.accepts("0123456789abcdef", now + Duration::days(31)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LegacyCookieFormat {
+    length: usize,
+    accept_until: DateTime<Utc>,
+    validator: Option<Hook<CookieValidator>>,
+}
+
+impl LegacyCookieFormat {
+    /// Accept cookies of the given `length` until `accept_until`.
+    ///
+    /// By default, all cookies of that length that are valid according to [`is_valid_cookie_value`] are accepted.
+    pub fn new(length: usize, accept_until: DateTime<Utc>) -> Self {
+        Self {
+            length,
+            accept_until,
+            validator: None,
+        }
+    }
+
+    /// Accept only cookies for which `validator` returns true, e.g. to check the alphabet of the legacy format.
+    /// The validator is only called with cookies of the legacy length that are valid according to [`is_valid_cookie_value`].
+    pub fn with_validator(
+        mut self,
+        validator: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.validator = Some(Hook(Arc::new(validator)));
+        self
+    }
+
+    /// The length of legacy cookies.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// The time until which legacy cookies are accepted.
+    pub fn accept_until(&self) -> DateTime<Utc> {
+        self.accept_until
+    }
+
+    /// Returns true if the given cookie is a legacy cookie that is accepted at time `now`.
+    pub fn accepts(&self, cookie: &str, now: DateTime<Utc>) -> bool {
+        now <= self.accept_until
+            && cookie.len() == self.length
+            && is_valid_cookie_value(cookie)
+            && self
+                .validator
+                .as_ref()
+                .is_none_or(|validator| validator.0(cookie))
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{
+    ChannelBindingPolicy, ClockAuthority, CookieSettings, HashingPolicy, SessionCookieGenerator,
+    SessionStore, SessionStoreConfig, SessionStoreConnector,
+};
+
+/// A description of the effective configuration of a [`SessionStore`], see [`SessionStore::describe`].
+///
+/// It is meant to be logged at startup, e.g. for audits, and does not contain any secrets.
+#[derive(Debug, Clone)]
+pub struct SessionStoreDescription {
+    /// The type name of the cookie generator.
+    pub cookie_generator: &'static str,
+    /// The length of the cookies of the cookie generator.
+    pub cookie_length: usize,
+    /// The settings of the session cookie.
+    pub cookie_settings: CookieSettings,
+    /// The hashing policy, see [`SessionStore::set_hashing_policy`].
+    pub hashing_policy: HashingPolicy,
+    /// The key prefix, see [`SessionStore::set_key_prefix`].
+    pub key_prefix: Option<String>,
+    /// The clock authority, see [`SessionStore::set_clock_authority`].
+    pub clock_authority: ClockAuthority,
+    /// The channel binding policy, see [`SessionStore::set_channel_binding_policy`].
+    pub channel_binding_policy: ChannelBindingPolicy,
+    /// The current runtime configuration, which contains the renewal strategy and deletion mode.
+    pub config: SessionStoreConfig,
+    /// The optional features that are enabled.
+    pub features: Vec<SessionStoreFeature>,
+    /// The stack of connectors that was passed to [`SessionStore::describe`].
+    pub connector: ConnectorDescription,
+}
+
+/// An optional feature of a [`SessionStore`] that is enabled by a setter.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum SessionStoreFeature {
+    /// See [`SessionStore::set_renewal_strategy_selector`].
+    RenewalStrategySelector,
+    /// See [`SessionStore::set_suppression_predicate`].
+    SuppressionPredicate,
+    /// See [`SessionStore::set_session_sampler`].
+    SessionSampler,
+    /// See [`SessionStore::enable_runtime_statistics`].
+    RuntimeStatistics,
+    /// See [`SessionStore::set_pre_expiry_hook`].
+    PreExpiryHook,
+    /// See [`SessionStore::set_schema_version`].
+    SchemaVersion,
+    /// See [`SessionStore::set_legacy_cookie_format`].
+    LegacyCookieFormat,
+    /// See [`SessionStore::set_access_audit`].
+    AccessAudit,
+    /// See [`SessionStore::set_priority_classifier`].
+    PriorityClassifier,
+    /// See [`SessionStore::set_anomaly_scorer`].
+    AnomalyScorer,
+    /// See [`SessionStore::set_expiry_sanitization`].
+    ExpirySanitization,
+}
+
+/// A description of a session store connector and the connectors it wraps,
+/// see [`SessionStoreConnector::describe`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ConnectorDescription {
+    /// The name of the connector.
+    pub name: String,
+    /// The descriptions of the wrapped connectors, if any.
+    pub inner: Vec<ConnectorDescription>,
+}
+
+impl ConnectorDescription {
+    /// Describe a connector that does not wrap other connectors.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            inner: Vec::new(),
+        }
+    }
+
+    /// Describe a connector that wraps the given connectors.
+    pub fn wrapping(
+        name: impl Into<String>,
+        inner: impl IntoIterator<Item = ConnectorDescription>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            inner: inner.into_iter().collect(),
+        }
+    }
+}
+
+impl<
+        SessionData,
+        SessionStoreConnection: SessionStoreConnector<SessionData>,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Describe the effective configuration of this session store, together with the given connector.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "memory-store")] {
+    /// # use typed_session::{MemoryStore, ReadOnlyMode, ReadOnlyStore, SessionRenewalStrategy, SessionStore};
+    /// let store: SessionStore<(), _> = SessionStore::new(SessionRenewalStrategy::Ignore);
+    /// let connection = ReadOnlyStore::new(MemoryStore::new(), ReadOnlyMode::Simulate);
+    /// let description = store.describe(&connection);
+    /// assert_eq!(description.cookie_length, 32);
+    /// assert_eq!(description.connector.name, "ReadOnlyStore");
+    /// assert_eq!(description.connector.inner[0].name, "MemoryStore");
+    /// # }
+    /// ```
+    pub fn describe(&self, connection: &SessionStoreConnection) -> SessionStoreDescription {
+        let features = [
+            (
+                self.renewal_strategy_selector.is_some(),
+                SessionStoreFeature::RenewalStrategySelector,
+            ),
+            (
+                self.suppression_predicate.is_some(),
+                SessionStoreFeature::SuppressionPredicate,
+            ),
+            (
+                self.session_sampler.is_some(),
+                SessionStoreFeature::SessionSampler,
+            ),
+            (
+                self.runtime_statistics.is_some(),
+                SessionStoreFeature::RuntimeStatistics,
+            ),
+            (
+                self.pre_expiry_hook.is_some(),
+                SessionStoreFeature::PreExpiryHook,
+            ),
+            (
+                self.schema_check.is_some(),
+                SessionStoreFeature::SchemaVersion,
+            ),
+            (
+                self.legacy_cookie_format.is_some(),
+                SessionStoreFeature::LegacyCookieFormat,
+            ),
+            (self.access_audit, SessionStoreFeature::AccessAudit),
+            (
+                self.priority_classifier.is_some(),
+                SessionStoreFeature::PriorityClassifier,
+            ),
+            (
+                self.anomaly_scorer.is_some(),
+                SessionStoreFeature::AnomalyScorer,
+            ),
+            (
+                self.expiry_sanitization.is_some(),
+                SessionStoreFeature::ExpirySanitization,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, feature)| enabled.then_some(feature))
+        .collect();
+
+        SessionStoreDescription {
+            cookie_generator: std::any::type_name::<CookieGenerator>(),
+            cookie_length: CookieGenerator::COOKIE_LENGTH,
+            cookie_settings: self.cookie_settings.clone(),
+            hashing_policy: self.hashing_policy,
+            key_prefix: self.key_prefix.clone(),
+            clock_authority: self.clock_authority,
+            channel_binding_policy: self.channel_binding_policy,
+            config: self.config.get(),
+            features,
+            connector: connection.describe(),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{
+    CookieValue, Error, Session, SessionCookieGenerator, SessionStore, SessionStoreConnector,
+};
+use std::fmt::Debug;
+
+impl<
+        SessionData: Debug + Clone,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Create a new session seeded with a copy of the data of the session identified by `source_cookie`,
+    /// e.g. to sign in a second device that scanned a QR code shown by the first one.
+    ///
+    /// The source session is loaded like with [`load_session_read_only`](SessionStore::load_session_read_only),
+    /// so it is not modified, and `None` is returned if it does not exist or is expired.
+    ///
+    /// The returned session is new and independent of the source session: when it is stored with
+    /// [`store_session`](SessionStore::store_session), it receives a freshly generated cookie and a fresh expiry.
+    /// It belongs to the same user as the source session, such that e.g.
+    /// [`invalidate_user_sessions`](SessionStore::invalidate_user_sessions) signs out both devices.
+    /// The assurance level and the channel binding are not copied, since they belong to the device that established them.
+    pub async fn clone_session_data(
+        &self,
+        source_cookie: &CookieValue,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<Option<Session<SessionData>>, Error<SessionStoreConnection::Error>> {
+        let Some(source) = self
+            .load_session_read_only(source_cookie, connection)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let mut session = Session::new_with_data(source.data().clone());
+        session.set_user_id(source.user_id());
+        Ok(Some(session))
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    ConnectorOperation, Error, Session, SessionCookieGenerator, SessionExpiry, SessionId,
+    SessionMetadata, SessionStore, SessionStoreConnector,
+};
+use async_trait::async_trait;
+use std::fmt::Debug;
+use std::future::Future;
+
+/// An extension of [`SessionStoreConnector`] for backends that can enumerate all stored sessions.
+#[async_trait]
+pub trait EnumerableSessionStoreConnector<SessionData>: SessionStoreConnector<SessionData> {
+    /// Return the ids of up to `limit` stored sessions, including child sessions, in ascending order.
+    /// If `after` is given, then only ids greater than `after` are returned.
+    ///
+    /// Tombstones are not returned.
+    async fn list_session_ids(
+        &mut self,
+        after: Option<&SessionId>,
+        limit: usize,
+    ) -> Result<Vec<SessionId>, Error<Self::Error>>;
+
+    /// Write the given expiry, metadata and data to the session identified by `id`, without changing its id.
+    /// This allows to re-encode a session in place, e.g. after changing an encryption key or codec.
+    ///
+    /// Returns `false` if the session does not exist anymore.
+    async fn rewrite_session(
+        &mut self,
+        id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<bool, Error<Self::Error>>;
+}
+
+/// The progress of [`SessionStore::rewrite_all_sessions`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct RewriteProgress {
+    /// The number of sessions that were rewritten so far.
+    pub rewritten_sessions: usize,
+    /// The number of sessions that were deleted concurrently before they could be rewritten.
+    pub vanished_sessions: usize,
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: EnumerableSessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Re-read and re-write every stored session in batches of `batch_size`,
+    /// e.g. to re-encrypt all sessions after a key rotation without logging out all users.
+    ///
+    /// After each batch, `progress` is called and awaited.
+    /// It can be used to report progress and to limit the rate of the rewrite, e.g. by sleeping.
+    /// The ids of the sessions stay the same, so sessions that are updated concurrently by requests
+    /// are either rewritten or written by the request.
+    ///
+    /// **Panics** if `batch_size` is zero.
+    pub async fn rewrite_all_sessions<Progress: Future<Output = ()>>(
+        &self,
+        batch_size: usize,
+        connection: &mut SessionStoreConnection,
+        mut progress: impl FnMut(RewriteProgress) -> Progress,
+    ) -> Result<RewriteProgress, Error<SessionStoreConnection::Error>> {
+        assert!(batch_size > 0, "the batch size must not be zero");
+        let mut total = RewriteProgress::default();
+        let mut after = None;
+
+        loop {
+            let ids = self
+                .intercept(
+                    ConnectorOperation::ListSessionIds,
+                    connection.list_session_ids(after.as_ref(), batch_size),
+                )
+                .await?;
+            let Some(last_id) = ids.last().cloned() else {
+                break;
+            };
+
+            for id in ids {
+                let rewritten = match self
+                    .intercept(
+                        ConnectorOperation::ReadSession,
+                        connection.read_session(id.clone()),
+                    )
+                    .await?
+                {
+                    Some(session) => {
+                        let metadata = session.metadata.clone();
+                        let (Some(data), Some(expiry)) = session.into_data_expiry_pair() else {
+                            unreachable!("sessions read from the store are never deleted");
+                        };
+                        self.intercept(
+                            ConnectorOperation::RewriteSession,
+                            connection.rewrite_session(&id, &expiry, &metadata, &data),
+                        )
+                        .await?
+                    }
+                    None => false,
+                };
+                if rewritten {
+                    total.rewritten_sessions += 1;
+                } else {
+                    total.vanished_sessions += 1;
+                }
+            }
+
+            progress(total).await;
+            after = Some(last_id);
+        }
+
+        Ok(total)
+    }
+    /// Delete all stored sessions for which `predicate` returns true, enumerating sessions in batches of `batch_size`.
+    /// The matching sessions of each batch are deleted with a single call of
+    /// [`delete_sessions`](SessionStoreConnector::delete_sessions).
+    ///
+    /// Sessions are deleted without leaving tombstones, regardless of the [`DeletionMode`](crate::DeletionMode).
+    /// Returns the number of deleted sessions.
+    ///
+    /// **Panics** if `batch_size` is zero.
+    pub async fn delete_sessions_where(
+        &self,
+        batch_size: usize,
+        connection: &mut SessionStoreConnection,
+        mut predicate: impl FnMut(&Session<SessionData>) -> bool,
+    ) -> Result<usize, Error<SessionStoreConnection::Error>> {
+        assert!(batch_size > 0, "the batch size must not be zero");
+        let mut deleted_sessions = 0;
+        let mut after = None;
+
+        loop {
+            let ids = self
+                .intercept(
+                    ConnectorOperation::ListSessionIds,
+                    connection.list_session_ids(after.as_ref(), batch_size),
+                )
+                .await?;
+            let Some(last_id) = ids.last().cloned() else {
+                break;
+            };
+
+            let mut matching_ids = Vec::new();
+            for id in ids {
+                if let Some(session) = self
+                    .intercept(
+                        ConnectorOperation::ReadSession,
+                        connection.read_session(id.clone()),
+                    )
+                    .await?
+                {
+                    if predicate(&session) {
+                        matching_ids.push(id);
+                    }
+                }
+            }
+            if !matching_ids.is_empty() {
+                self.intercept(
+                    ConnectorOperation::DeleteSessions,
+                    connection.delete_sessions(&matching_ids),
+                )
+                .await?;
+                deleted_sessions += matching_ids.len();
+            }
+
+            after = Some(last_id);
+        }
+
+        Ok(deleted_sessions)
+    }
+}
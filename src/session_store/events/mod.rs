@@ -0,0 +1,85 @@
+use crate::session::SessionState;
+use crate::session_store::Hook;
+use crate::{Session, SessionCookieGenerator, SessionExpiry, SessionMetadata, SessionStore};
+use std::sync::Arc;
+
+/// A change of a session, reported to a [`SessionEventListener`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SessionEvent {
+    /// A new session was stored.
+    Created {
+        /// The expiry of the new session.
+        expiry: SessionExpiry,
+    },
+    /// The expiry of a loaded session was extended by the [renewal strategy](crate::SessionRenewalStrategy).
+    /// The renewed expiry is written once the session is stored.
+    Renewed {
+        /// The expiry of the session before the renewal.
+        previous_expiry: SessionExpiry,
+        /// The renewed expiry of the session.
+        expiry: SessionExpiry,
+    },
+    /// An existing session was stored under a new id, because its data, expiry or metadata changed.
+    Updated {
+        /// The expiry of the updated session.
+        expiry: SessionExpiry,
+    },
+    /// A session was deleted.
+    Deleted,
+    /// A session was found to be expired when it was loaded, so it was not returned.
+    ExpiredOnLoad {
+        /// The expiry of the session.
+        expiry: SessionExpiry,
+    },
+}
+
+/// Receives the [`SessionEvent`]s of a session store, see [`SessionStore::set_event_listener`].
+///
+/// This is implemented for all closures `Fn(SessionEvent, &SessionMetadata)`.
+/// Events are reported synchronously, so slow listeners should forward them e.g. through a channel.
+pub trait SessionEventListener: Send + Sync {
+    /// Handle the given event of the session with the given metadata.
+    fn on_event(&self, event: SessionEvent, metadata: &SessionMetadata);
+}
+
+impl<F: Fn(SessionEvent, &SessionMetadata) + Send + Sync> SessionEventListener for F {
+    fn on_event(&self, event: SessionEvent, metadata: &SessionMetadata) {
+        self(event, metadata)
+    }
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator: SessionCookieGenerator>
+    SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Sets a listener that is called whenever a session is created, renewed, updated or deleted,
+    /// or found to be expired on load, e.g. to emit audit logs or metrics without wrapping every call site.
+    ///
+    /// Writes are reported after they succeeded.
+    /// Sessions that are deleted in bulk, e.g. by [`invalidate_user_sessions`](SessionStore::invalidate_user_sessions),
+    /// are not reported individually.
+    pub fn set_event_listener(&mut self, listener: impl SessionEventListener + 'static) {
+        self.event_listener = Some(Hook(Arc::new(listener)));
+    }
+
+    /// Report the given event to the event listener, if any.
+    pub(crate) fn emit_event(&self, event: SessionEvent, metadata: &SessionMetadata) {
+        if let Some(listener) = &self.event_listener {
+            listener.0.on_event(event, metadata);
+        }
+    }
+
+    /// Report the write of the given session, which must have been stored successfully.
+    pub(crate) fn emit_write_event(&self, session: &Session<SessionData>) {
+        let event = match &session.state {
+            SessionState::NewChanged { expiry, .. } => SessionEvent::Created { expiry: *expiry },
+            SessionState::Changed { expiry, .. } => SessionEvent::Updated { expiry: *expiry },
+            SessionState::Deleted { .. } => SessionEvent::Deleted,
+            SessionState::NewUnchanged { .. }
+            | SessionState::Unchanged { .. }
+            | SessionState::NewDeleted
+            | SessionState::Invalid => return,
+        };
+        self.emit_event(event, &session.metadata);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{Error, Session, SessionCookieGenerator, SessionExpiry, SessionStore};
+use chrono::{DateTime, Duration, Utc};
+use std::fmt::Debug;
+use tracing::warn;
+
+/// What to do when a connector returns a session with an implausible expiry,
+/// see [`SessionStore::set_expiry_sanitization`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExpirySanitizationPolicy {
+    /// Log a warning and clamp the expiry into the plausible range.
+    /// The session is marked as changed, such that the corrected expiry is written back when it is stored.
+    Clamp,
+
+    /// Log a warning and treat the session as if it was expired.
+    #[default]
+    TreatAsExpired,
+
+    /// Return [`Error::CorruptedExpiry`].
+    Error,
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator: SessionCookieGenerator>
+    SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Returns the maximum plausible time-to-live and the policy for implausible expiries,
+    /// see [`set_expiry_sanitization`](SessionStore::set_expiry_sanitization).
+    pub fn expiry_sanitization(&self) -> Option<(Duration, ExpirySanitizationPolicy)> {
+        self.expiry_sanitization
+    }
+
+    /// Stop trusting the expiry returned by the connector blindly.
+    ///
+    /// When a loaded session expires later than `maximum_time_to_live` from now, or earlier than it was created,
+    /// its expiry is considered corrupted, e.g. by a broken migration or a manual edit of the backend,
+    /// and `policy` is applied.
+    /// This keeps corrupted rows from breaking the renewal math or from producing absurd cookie expiry headers.
+    ///
+    /// `maximum_time_to_live` should be at least the longest time-to-live that this application assigns to sessions.
+    /// Sessions that never expire are not affected.
+    pub fn set_expiry_sanitization(
+        &mut self,
+        maximum_time_to_live: Duration,
+        policy: ExpirySanitizationPolicy,
+    ) {
+        self.expiry_sanitization = Some((maximum_time_to_live, policy));
+    }
+}
+
+impl<SessionData: Debug, SessionStoreConnection, CookieGenerator>
+    SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Apply the [expiry sanitization](SessionStore::set_expiry_sanitization) to the given loaded session, if set.
+    ///
+    /// Returns false if the session should be treated as expired.
+    pub(crate) fn sanitize_expiry<SessionStoreConnectorError>(
+        &self,
+        session: &mut Session<SessionData>,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<SessionStoreConnectorError>> {
+        let Some((maximum_time_to_live, policy)) = self.expiry_sanitization else {
+            return Ok(true);
+        };
+        let SessionExpiry::DateTime(expiry) = *session.expiry() else {
+            return Ok(true);
+        };
+
+        let latest = now
+            .checked_add_signed(maximum_time_to_live)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let earliest = session
+            .metadata
+            .created_at
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        if earliest <= expiry && expiry <= latest {
+            return Ok(true);
+        }
+
+        match policy {
+            ExpirySanitizationPolicy::Clamp => {
+                warn!("Clamped the corrupted expiry {expiry} of a loaded session");
+                session.set_expiry(expiry.clamp(earliest.min(latest), latest));
+                Ok(true)
+            }
+            ExpirySanitizationPolicy::TreatAsExpired => {
+                warn!("Treated a loaded session with the corrupted expiry {expiry} as expired");
+                Ok(false)
+            }
+            ExpirySanitizationPolicy::Error => Err(Error::CorruptedExpiry { expiry }),
+        }
+    }
+}
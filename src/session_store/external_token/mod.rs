@@ -0,0 +1,109 @@
+use crate::session::SessionState;
+use crate::{
+    ConnectorOperation, CookieValue, Error, Session, SessionCookieCommand, SessionCookieGenerator,
+    SessionStore, SessionStoreConnector, WriteSessionResult,
+};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use tracing::info;
+
+/// The minimum estimated entropy of an adopted token, see [`SessionStore::adopt_external_token`].
+const MINIMUM_EXTERNAL_TOKEN_ENTROPY_BITS: f64 = 96.0;
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Create a session with the given data under a token that was created outside of this session store,
+    /// e.g. by an SSO gateway that mints the session cookie upstream.
+    ///
+    /// The token must be accepted by the cookie generator like any cookie received from a client.
+    /// Additionally, its entropy is estimated from its character frequencies, and tokens with an estimate below
+    /// 96 bits are rejected with [`Error::WeakExternalToken`], since e.g. repetitive tokens are guessable.
+    /// The token is then hashed according to the [`HashingPolicy`](crate::HashingPolicy) like a generated cookie,
+    /// and the session is created with the usual metadata and expiry.
+    /// Like any other session, it receives a generated cookie once it is changed.
+    ///
+    /// Returns [`WriteSessionResult::SessionIdExists`] if a session with this token already exists,
+    /// in which case nothing is changed.
+    /// In [read-only mode](crate::SessionStoreConfig::read_only), valid tokens are not adopted,
+    /// and [`SessionCookieCommand::DoNothing`] is returned.
+    /// Every adoption is logged at info level, such that adopted sessions can be audited.
+    pub async fn adopt_external_token(
+        &self,
+        token: &CookieValue,
+        data: SessionData,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<WriteSessionResult<SessionCookieCommand>, Error<SessionStoreConnection::Error>>
+    {
+        let token_str = token.expose();
+        if token_str.len() != CookieGenerator::COOKIE_LENGTH {
+            return Err(Error::WrongCookieLength {
+                expected: CookieGenerator::COOKIE_LENGTH,
+                actual: token_str.len(),
+            });
+        }
+        if !self.cookie_generator.is_valid_cookie(token_str) {
+            return Err(Error::InvalidCookieValue);
+        }
+        if estimated_entropy_bits(token_str) < MINIMUM_EXTERNAL_TOKEN_ENTROPY_BITS {
+            return Err(Error::WeakExternalToken);
+        }
+        if self.suppress_write_in_read_only_mode(format_args!("adopting an external session token"))
+        {
+            return Ok(WriteSessionResult::Ok(SessionCookieCommand::DoNothing));
+        }
+
+        let mut session = Session::new_with_data(data);
+        let now = self.now(connection).await?;
+        self.prepare_write(&mut session, now);
+        let SessionState::NewChanged { expiry, data } = &session.state else {
+            unreachable!("a new session with data is in state NewChanged");
+        };
+
+        let id = self.session_id_from_cookie_value(token);
+        match self
+            .intercept(
+                ConnectorOperation::CreateSession,
+                connection.create_session(&id, expiry, &session.metadata, data),
+            )
+            .await?
+        {
+            WriteSessionResult::Ok(()) => {
+                info!("Adopted an externally created session token");
+                self.after_write(&session, now);
+                Ok(WriteSessionResult::Ok(self.set_cookie_command(
+                    token.clone(),
+                    *expiry,
+                    now,
+                )))
+            }
+            WriteSessionResult::SessionIdExists => {
+                info!("Refused to adopt an externally created session token that already exists");
+                Ok(WriteSessionResult::SessionIdExists)
+            }
+        }
+    }
+}
+
+/// Estimate the entropy of the given token in bits from the frequencies of its characters.
+///
+/// This is only a plausibility check that rejects tokens with few distinct characters,
+/// it cannot detect tokens that are derived from guessable inputs.
+fn estimated_entropy_bits(token: &str) -> f64 {
+    let mut counts = HashMap::new();
+    for character in token.chars() {
+        *counts.entry(character).or_insert(0usize) += 1;
+    }
+    let length = token.chars().count() as f64;
+    let entropy_per_character: f64 = counts
+        .values()
+        .map(|&count| {
+            let probability = count as f64 / length;
+            -probability * probability.log2()
+        })
+        .sum();
+    entropy_per_character * length
+}
@@ -0,0 +1,107 @@
+use crate::session::{saturating_add, SessionState};
+use crate::{
+    ConnectorOperation, Error, Session, SessionCookieGenerator, SessionId, SessionStore,
+    SessionStoreConnector,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::fmt::Debug;
+use std::future::Future;
+
+/// The state of an idempotency key, as returned by [`IdempotencyStoreConnector::begin_idempotent_request`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum IdempotencyRecord {
+    /// The key was not used before, and is now marked as in progress.
+    New,
+    /// A request with the key is currently in progress.
+    InProgress,
+    /// A request with the key was completed with the given result.
+    Completed(Vec<u8>),
+}
+
+/// An extension of [`SessionStoreConnector`] for backends that can record idempotency keys of sessions.
+///
+/// Implementations must uphold the following in addition to the requirements of [`SessionStoreConnector`]:
+///  * [`delete_session`](SessionStoreConnector::delete_session) and
+///    [`create_tombstone`](SessionStoreConnector::create_tombstone) must also delete all idempotency keys of the session.
+///  * [`update_session`](SessionStoreConnector::update_session) must keep the idempotency keys of a session, even though its id changes.
+#[async_trait]
+pub trait IdempotencyStoreConnector<SessionData>: SessionStoreConnector<SessionData> {
+    /// Look up the idempotency `key` of the session identified by `session_id`, ignoring records that are expired at `now`.
+    /// If there is no such record, create one that is in progress and expires at `expiry`,
+    /// and return [`IdempotencyRecord::New`].
+    ///
+    /// Returns [`Error::UpdatedSessionDoesNotExist`] if the session does not exist.
+    /// This must be atomic, such that a key can never be new twice.
+    async fn begin_idempotent_request(
+        &mut self,
+        session_id: &SessionId,
+        key: &str,
+        now: DateTime<Utc>,
+        expiry: DateTime<Utc>,
+    ) -> Result<IdempotencyRecord, Error<Self::Error>>;
+
+    /// Mark the idempotency `key` of the session identified by `session_id` as completed with the given `result`.
+    ///
+    /// If the session or the key do not exist anymore, this does nothing.
+    async fn complete_idempotent_request(
+        &mut self,
+        session_id: &SessionId,
+        key: &str,
+        result: &[u8],
+    ) -> Result<(), Error<Self::Error>>;
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: IdempotencyStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Run `f` at most once for the given idempotency `key` within the given session, and return its result.
+    /// If the key was used before, the recorded result is returned instead of running `f` again.
+    ///
+    /// The key is remembered for `time_to_live`, and deleted together with the session.
+    /// If a request with the same key is still in progress, [`Error::IdempotentRequestInProgress`] is returned.
+    /// The session must have been loaded from the session store, otherwise [`Error::SessionNotStored`] is returned.
+    pub async fn run_idempotent<HandlerFuture: Future<Output = Vec<u8>>>(
+        &self,
+        session: &Session<SessionData>,
+        key: &str,
+        time_to_live: Duration,
+        connection: &mut SessionStoreConnection,
+        f: impl FnOnce() -> HandlerFuture,
+    ) -> Result<Vec<u8>, Error<SessionStoreConnection::Error>> {
+        let (SessionState::Unchanged { current_id, .. } | SessionState::Changed { current_id, .. }) =
+            &session.state
+        else {
+            return Err(Error::SessionNotStored);
+        };
+
+        let now = self.now(connection).await?;
+        match self
+            .intercept(
+                ConnectorOperation::BeginIdempotentRequest,
+                connection.begin_idempotent_request(
+                    current_id,
+                    key,
+                    now,
+                    saturating_add(now, time_to_live),
+                ),
+            )
+            .await?
+        {
+            IdempotencyRecord::New => {
+                let result = f().await;
+                self.intercept(
+                    ConnectorOperation::CompleteIdempotentRequest,
+                    connection.complete_idempotent_request(current_id, key, &result),
+                )
+                .await?;
+                Ok(result)
+            }
+            IdempotencyRecord::InProgress => Err(Error::IdempotentRequestInProgress),
+            IdempotencyRecord::Completed(result) => Ok(result),
+        }
+    }
+}
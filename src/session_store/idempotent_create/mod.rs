@@ -0,0 +1,95 @@
+use crate::session::SessionState;
+use crate::{
+    ConnectorOperation, Error, Session, SessionCookieCommand, SessionCookieGenerator,
+    SessionExpiry, SessionId, SessionMetadata, SessionStore, SessionStoreConnector,
+    WriteSessionResult,
+};
+use async_trait::async_trait;
+use std::fmt::Debug;
+
+/// An extension of [`SessionStoreConnector`] for backends that support idempotent session creation.
+///
+/// Each creation carries a token id, which is derived from an idempotency token chosen by the application,
+/// e.g. the request id of a request that middleware may retry.
+/// Like session ids, tokens are only passed to the connector in hashed form.
+#[async_trait]
+pub trait IdempotentCreateStoreConnector<SessionData>: SessionStoreConnector<SessionData> {
+    /// Create a session like [`create_session`](SessionStoreConnector::create_session),
+    /// and remember that it was created with the given `token_id`.
+    ///
+    /// If a session was created earlier with the same `token_id` and still exists under the id it was created with,
+    /// then that session is moved to `current_id` and overwritten instead of creating a second session.
+    /// This must be atomic, such that concurrent retries of the same creation result in a single session.
+    ///
+    /// Returns [`WriteSessionResult::SessionIdExists`] if a session or tombstone with `current_id` already exists.
+    async fn create_session_with_token(
+        &mut self,
+        token_id: &SessionId,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>>;
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: IdempotentCreateStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Store a session like [`store_session`](SessionStore::store_session),
+    /// but create new sessions idempotently with respect to the given `token`.
+    ///
+    /// If the same request is retried, e.g. by middleware after a timeout, then the retry passes the same `token`
+    /// and converges on the session stored by the first attempt, instead of creating a duplicate session.
+    /// The session gets a new cookie on every attempt, hence only the cookie of the last attempt stays valid.
+    ///
+    /// Sessions that are not new are stored like with [`store_session`](SessionStore::store_session).
+    /// In [read-only mode](crate::SessionStoreConfig::read_only), nothing is written and
+    /// [`SessionCookieCommand::DoNothing`] is returned.
+    pub async fn store_session_idempotently(
+        &self,
+        mut session: Session<SessionData>,
+        token: &str,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<SessionCookieCommand, Error<SessionStoreConnection::Error>> {
+        if !matches!(&session.state, SessionState::NewChanged { .. }) {
+            return self.store_session(session, connection).await;
+        }
+        if self.is_suppressed(&session)
+            || self.suppress_write_in_read_only_mode(format_args!("storing session idempotently"))
+        {
+            return Ok(SessionCookieCommand::DoNothing);
+        }
+
+        let now = self.now(connection).await?;
+        self.prepare_write(&mut session, now);
+        let SessionState::NewChanged { expiry, data } = &session.state else {
+            unreachable!("preparing a write does not change the session state");
+        };
+        let token_id = SessionId::from_creation_token(self.key_prefix(), token);
+
+        let mut retries = self.write_retries(connection);
+        loop {
+            retries.check_limit()?;
+            let (cookie_value, id) = self.generate_session_id(None, connection)?;
+            let result = self
+                .intercept(
+                    ConnectorOperation::CreateSessionWithToken,
+                    connection.create_session_with_token(
+                        &token_id,
+                        &id,
+                        expiry,
+                        &session.metadata,
+                        data,
+                    ),
+                )
+                .await;
+            if let Some(()) = self.retry_write(&mut retries, result, connection).await? {
+                self.after_write(&session, now);
+                return Ok(self.set_cookie_command(cookie_value, *expiry, now));
+            }
+        }
+    }
+}
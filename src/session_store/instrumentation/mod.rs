@@ -0,0 +1,68 @@
+use crate::session::SessionState;
+use metrics::{counter, describe_counter, describe_histogram, histogram, Unit};
+use std::time::Duration;
+
+const LOADS: &str = "typed_session_loads_total";
+const LOAD_MISSES: &str = "typed_session_load_misses_total";
+const CREATIONS: &str = "typed_session_creations_total";
+const UPDATES: &str = "typed_session_updates_total";
+const DELETIONS: &str = "typed_session_deletions_total";
+const ID_COLLISIONS: &str = "typed_session_id_collisions_total";
+const STORE_DURATION: &str = "typed_session_store_duration_seconds";
+
+/// Register descriptions of the metrics emitted by session stores with the installed
+/// [`metrics`](https://crates.io/crates/metrics) recorder, available under the feature flag `metrics`.
+///
+/// Calling this is optional, it only allows exporters to show help texts and units.
+/// Call it after installing the recorder.
+pub fn describe_session_metrics() {
+    describe_counter!(LOADS, "Sessions that were attempted to be loaded.");
+    describe_counter!(
+        LOAD_MISSES,
+        "Sessions that were attempted to be loaded, but did not exist or were rejected."
+    );
+    describe_counter!(CREATIONS, "Sessions that were created.");
+    describe_counter!(UPDATES, "Sessions that were updated.");
+    describe_counter!(DELETIONS, "Sessions that were deleted.");
+    describe_counter!(
+        ID_COLLISIONS,
+        "Writes that were retried because the generated session id already existed."
+    );
+    describe_histogram!(
+        STORE_DURATION,
+        Unit::Seconds,
+        "Time to write a session to the session store connector, including retries."
+    );
+}
+
+/// Record the load of a session, which was found if `hit` is true.
+pub(crate) fn record_load(hit: bool) {
+    counter!(LOADS).increment(1);
+    if !hit {
+        counter!(LOAD_MISSES).increment(1);
+    }
+}
+
+/// Record the successful write of a session in the given state.
+pub(crate) fn record_write<SessionData>(state: &SessionState<SessionData>) {
+    let name = match state {
+        SessionState::NewChanged { .. } => CREATIONS,
+        SessionState::Changed { .. } => UPDATES,
+        SessionState::Deleted { .. } => DELETIONS,
+        SessionState::NewUnchanged { .. }
+        | SessionState::Unchanged { .. }
+        | SessionState::NewDeleted
+        | SessionState::Invalid => return,
+    };
+    counter!(name).increment(1);
+}
+
+/// Record the duration of a successful [`store_session`](crate::SessionStore::store_session).
+pub(crate) fn record_store_duration(duration: Duration) {
+    histogram!(STORE_DURATION).record(duration.as_secs_f64());
+}
+
+/// Record a collision of a generated session id.
+pub(crate) fn record_id_collision() {
+    counter!(ID_COLLISIONS).increment(1);
+}
@@ -0,0 +1,161 @@
+use crate::session_store::Hook;
+use crate::{Error, SessionCookieGenerator, SessionStore};
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+
+/// An operation of a session store connector, reported to an [`Interceptor`].
+///
+/// Each variant corresponds to the connector method of the same name.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum ConnectorOperation {
+    CreateSession,
+    ReadSession,
+    ReadSessions,
+    UpdateSession,
+    DeleteSession,
+    DeleteSessions,
+    CreateTombstone,
+    IsTombstone,
+    IsSessionValid,
+    Now,
+    PreloadSessions,
+    CountSessions,
+    Clear,
+    ClearNamespace,
+    ListSessionIds,
+    RewriteSession,
+    CreateChildSession,
+    CreateSessionWithToken,
+    DeleteSessionsOfUser,
+    ReadSessionsOfUser,
+    PutAttachment,
+    GetAttachment,
+    DeleteAttachment,
+    CreateNonce,
+    ConsumeNonce,
+    BeginIdempotentRequest,
+    CompleteIdempotentRequest,
+    LockSession,
+    UnlockSession,
+}
+
+impl ConnectorOperation {
+    /// Returns the name of the connector method that performs this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CreateSession => "create_session",
+            Self::ReadSession => "read_session",
+            Self::ReadSessions => "read_sessions",
+            Self::UpdateSession => "update_session",
+            Self::DeleteSession => "delete_session",
+            Self::DeleteSessions => "delete_sessions",
+            Self::CreateTombstone => "create_tombstone",
+            Self::IsTombstone => "is_tombstone",
+            Self::IsSessionValid => "is_session_valid",
+            Self::Now => "now",
+            Self::PreloadSessions => "preload_sessions",
+            Self::CountSessions => "count_sessions",
+            Self::Clear => "clear",
+            Self::ClearNamespace => "clear_namespace",
+            Self::ListSessionIds => "list_session_ids",
+            Self::RewriteSession => "rewrite_session",
+            Self::CreateChildSession => "create_child_session",
+            Self::CreateSessionWithToken => "create_session_with_token",
+            Self::DeleteSessionsOfUser => "delete_sessions_of_user",
+            Self::ReadSessionsOfUser => "read_sessions_of_user",
+            Self::PutAttachment => "put_attachment",
+            Self::GetAttachment => "get_attachment",
+            Self::DeleteAttachment => "delete_attachment",
+            Self::CreateNonce => "create_nonce",
+            Self::ConsumeNonce => "consume_nonce",
+            Self::BeginIdempotentRequest => "begin_idempotent_request",
+            Self::CompleteIdempotentRequest => "complete_idempotent_request",
+            Self::LockSession => "lock_session",
+            Self::UnlockSession => "unlock_session",
+        }
+    }
+}
+
+impl Display for ConnectorOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The outcome of a connector operation, reported to [`Interceptor::after`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OperationOutcome {
+    /// The connector returned successfully.
+    Succeeded,
+    /// The connector returned an error.
+    Failed,
+}
+
+/// Observes and possibly rejects each connector operation of a session store,
+/// see [`SessionStore::set_interceptor`].
+///
+/// This allows injecting cross-cutting concerns like authorisation checks, quota accounting or custom logging
+/// without writing a wrapper connector.
+/// Unlike a wrapper connector, an interceptor only sees the operations issued by the session store,
+/// but not those that a connector issues internally, e.g. to the connectors it wraps.
+pub trait Interceptor: Send + Sync {
+    /// Called before the given operation is passed to the connector.
+    /// Returning an error rejects the operation with [`Error::Intercepted`], without calling the connector.
+    ///
+    /// The default implementation accepts all operations.
+    fn before(&self, operation: ConnectorOperation) -> Result<(), String> {
+        let _ = operation;
+        Ok(())
+    }
+
+    /// Called after the connector returned from the given operation.
+    /// This is not called for rejected operations.
+    ///
+    /// The default implementation does nothing.
+    fn after(&self, operation: ConnectorOperation, outcome: OperationOutcome) {
+        let _ = (operation, outcome);
+    }
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator: SessionCookieGenerator>
+    SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Sets an interceptor that is called before and after each connector operation issued by this session store.
+    pub fn set_interceptor(&mut self, interceptor: impl Interceptor + 'static) {
+        self.interceptor = Some(Hook(Arc::new(interceptor)));
+    }
+
+    /// Perform the given connector call, reporting it to the interceptor, if any.
+    /// Under the feature flag `tracing-spans`, the call is wrapped in a span named after the operation.
+    ///
+    /// Since futures are lazy, the connector is not called before the interceptor accepted the operation.
+    pub(crate) async fn intercept<Output, ConnectorError>(
+        &self,
+        operation: ConnectorOperation,
+        call: impl Future<Output = Result<Output, Error<ConnectorError>>>,
+    ) -> Result<Output, Error<ConnectorError>> {
+        #[cfg(feature = "tracing-spans")]
+        let call = tracing::Instrument::instrument(
+            call,
+            tracing::debug_span!("connector", operation = operation.name()),
+        );
+        let Some(interceptor) = &self.interceptor else {
+            return call.await;
+        };
+        interceptor
+            .0
+            .before(operation)
+            .map_err(|reason| Error::Intercepted { operation, reason })?;
+        let result = call.await;
+        let outcome = if result.is_ok() {
+            OperationOutcome::Succeeded
+        } else {
+            OperationOutcome::Failed
+        };
+        interceptor.0.after(operation, outcome);
+        result
+    }
+}
@@ -0,0 +1,111 @@
+use crate::session::saturating_add;
+use crate::{
+    ConnectorOperation, CookieValue, Error, Session, SessionCookieCommand, SessionCookieGenerator,
+    SessionId, SessionStore, SessionStoreConnector,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::fmt::Debug;
+use std::future::Future;
+
+/// An extension of [`SessionStoreConnector`] for backends that support cooperative locking of sessions.
+///
+/// Locks are advisory: they only serialize requests that use [`SessionStore::with_session_locked`],
+/// while all other operations ignore them.
+#[async_trait]
+pub trait LockingSessionStoreConnector<SessionData>: SessionStoreConnector<SessionData> {
+    /// Lock the session with the given `id` until `expiry`, unless it is already locked at time `now`.
+    /// Returns true if the lock was acquired.
+    ///
+    /// The session does not need to exist.
+    /// This must be atomic, such that a lock can never be acquired twice.
+    async fn lock_session(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+        expiry: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>>;
+
+    /// Release the lock on the session with the given `id` that was acquired with the given `expiry`.
+    ///
+    /// If the lock has a different expiry, then it expired and was acquired by someone else in the meantime,
+    /// and it must not be released.
+    async fn unlock_session(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>>;
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: LockingSessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Load the session identified by `cookie_value` while holding a lock on it, and pass it to `f`.
+    /// The session returned by `f`, if any, is stored before the lock is released.
+    ///
+    /// This serializes all handlers that use this method for the same session, which can be preferable to
+    /// handling [`Error::UpdatedSessionDoesNotExist`] for specific critical endpoints.
+    /// If the session is already locked, then [`Error::SessionLocked`] is returned and `f` is not called.
+    ///
+    /// The lock is held for at most `lock_time_to_live`, such that a crashed handler does not lock the session forever.
+    pub async fn with_session_locked<Output, Handler, HandlerFuture>(
+        &self,
+        cookie_value: &CookieValue,
+        lock_time_to_live: Duration,
+        connection: &mut SessionStoreConnection,
+        f: Handler,
+    ) -> Result<(Output, SessionCookieCommand), Error<SessionStoreConnection::Error>>
+    where
+        Handler: FnOnce(Option<Session<SessionData>>) -> HandlerFuture,
+        HandlerFuture: Future<Output = (Output, Option<Session<SessionData>>)>,
+    {
+        self.check_cookie_value(cookie_value)?;
+
+        let id = self.session_id_from_cookie_value(cookie_value);
+        let now = self.now(connection).await?;
+        let expiry = saturating_add(now, lock_time_to_live);
+        if !self
+            .intercept(
+                ConnectorOperation::LockSession,
+                connection.lock_session(&id, now, expiry),
+            )
+            .await?
+        {
+            return Err(Error::SessionLocked);
+        }
+
+        let result = self.run_locked(cookie_value, connection, f).await;
+        let unlock_result = self
+            .intercept(
+                ConnectorOperation::UnlockSession,
+                connection.unlock_session(&id, expiry),
+            )
+            .await;
+        let result = result?;
+        unlock_result?;
+        Ok(result)
+    }
+
+    async fn run_locked<Output, Handler, HandlerFuture>(
+        &self,
+        cookie_value: &CookieValue,
+        connection: &mut SessionStoreConnection,
+        f: Handler,
+    ) -> Result<(Output, SessionCookieCommand), Error<SessionStoreConnection::Error>>
+    where
+        Handler: FnOnce(Option<Session<SessionData>>) -> HandlerFuture,
+        HandlerFuture: Future<Output = (Output, Option<Session<SessionData>>)>,
+    {
+        let session = self.load_session(cookie_value, connection).await?;
+        let (output, session) = f(session).await;
+        let command = if let Some(session) = session {
+            self.store_session(session, connection).await?
+        } else {
+            SessionCookieCommand::DoNothing
+        };
+        Ok((output, command))
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{CookieSettings, DefaultSessionCookieGenerator, SessionCookieGenerator, SessionStore};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+/// A set of session stores with different session data types that share one backend.
+///
+/// Each session data type is served by a [`SessionChannel`], which has its own cookie name and key prefix.
+/// The key prefix separates the sessions of the channels in the backend, see [`SessionStore::set_key_prefix`].
+/// This allows to keep unrelated concerns, like authentication and user preferences, in separate session data types,
+/// instead of combining them into a single large session data type.
+///
+/// Since all channels share the same backend, `SessionStoreConnection` must implement
+/// [`SessionStoreConnector`](crate::SessionStoreConnector) for the session data types of all channels.
+///
+/// Build it with [`MultiSessionStoreBuilder`].
+pub struct MultiSessionStore<
+    SessionStoreConnection,
+    CookieGenerator = DefaultSessionCookieGenerator,
+> {
+    channels: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    cookie_names: Vec<String>,
+    connection: PhantomData<fn() -> (SessionStoreConnection, CookieGenerator)>,
+}
+
+/// A builder for [`MultiSessionStore`].
+pub struct MultiSessionStoreBuilder<
+    SessionStoreConnection,
+    CookieGenerator = DefaultSessionCookieGenerator,
+> {
+    store: MultiSessionStore<SessionStoreConnection, CookieGenerator>,
+    key_prefixes: Vec<String>,
+}
+
+/// The session store of a single session data type in a [`MultiSessionStore`], together with its cookie name.
+#[derive(Debug)]
+pub struct SessionChannel<
+    SessionData,
+    SessionStoreConnection,
+    CookieGenerator = DefaultSessionCookieGenerator,
+> {
+    cookie_name: String,
+    store: SessionStore<SessionData, SessionStoreConnection, CookieGenerator>,
+}
+
+impl<SessionStoreConnection, CookieGenerator>
+    MultiSessionStoreBuilder<SessionStoreConnection, CookieGenerator>
+{
+    /// Create a builder without any channels.
+    pub fn new() -> Self {
+        Self {
+            store: MultiSessionStore {
+                channels: Default::default(),
+                cookie_names: Default::default(),
+                connection: PhantomData,
+            },
+            key_prefixes: Default::default(),
+        }
+    }
+}
+
+impl<
+        SessionStoreConnection: Send + Sync + 'static,
+        CookieGenerator: SessionCookieGenerator + Send + Sync + 'static,
+    > MultiSessionStoreBuilder<SessionStoreConnection, CookieGenerator>
+{
+    /// Add a channel for the session data type `SessionData`.
+    ///
+    /// The session cookie of the channel should be sent to clients under `cookie_name`.
+    /// The key prefix of `store` is set to `key_prefix`, see [`SessionStore::set_key_prefix`],
+    /// and the name in its [`CookieSettings`] is set to `cookie_name`.
+    ///
+    /// **Panics** if there already is a channel with the same session data type, cookie name or key prefix,
+    /// or if the hashing policy of `store` is [`HashingPolicy::Never`](crate::HashingPolicy::Never).
+    pub fn channel<SessionData: Send + Sync + 'static>(
+        mut self,
+        cookie_name: impl Into<String>,
+        key_prefix: impl Into<String>,
+        mut store: SessionStore<SessionData, SessionStoreConnection, CookieGenerator>,
+    ) -> Self {
+        let cookie_name = cookie_name.into();
+        let key_prefix = key_prefix.into();
+        assert!(
+            !self.store.cookie_names.contains(&cookie_name),
+            "duplicate cookie name {cookie_name:?}"
+        );
+        assert!(
+            !self.key_prefixes.contains(&key_prefix),
+            "duplicate key prefix {key_prefix:?}"
+        );
+
+        if let Err(error) = store.set_key_prefix(Some(key_prefix.clone())) {
+            panic!("{error}");
+        }
+        store.set_cookie_settings(CookieSettings {
+            name: cookie_name.clone(),
+            ..store.cookie_settings().clone()
+        });
+        let channel = SessionChannel {
+            cookie_name: cookie_name.clone(),
+            store,
+        };
+        let previous = self
+            .store
+            .channels
+            .insert(TypeId::of::<SessionData>(), Box::new(channel));
+        assert!(
+            previous.is_none(),
+            "duplicate session data type {}",
+            std::any::type_name::<SessionData>()
+        );
+
+        self.store.cookie_names.push(cookie_name);
+        self.key_prefixes.push(key_prefix);
+        self
+    }
+
+    /// Build the [`MultiSessionStore`].
+    pub fn build(self) -> MultiSessionStore<SessionStoreConnection, CookieGenerator> {
+        self.store
+    }
+}
+
+impl<SessionStoreConnection, CookieGenerator> Default
+    for MultiSessionStoreBuilder<SessionStoreConnection, CookieGenerator>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<SessionStoreConnection: 'static, CookieGenerator: 'static>
+    MultiSessionStore<SessionStoreConnection, CookieGenerator>
+{
+    /// Returns the channel of the session data type `SessionData`, if there is one.
+    pub fn channel<SessionData: 'static>(
+        &self,
+    ) -> Option<&SessionChannel<SessionData, SessionStoreConnection, CookieGenerator>> {
+        self.channels
+            .get(&TypeId::of::<SessionData>())
+            .and_then(|channel| channel.downcast_ref())
+    }
+}
+
+impl<SessionStoreConnection, CookieGenerator>
+    MultiSessionStore<SessionStoreConnection, CookieGenerator>
+{
+    /// The cookie names of all channels, in the order they were added.
+    pub fn cookie_names(&self) -> impl Iterator<Item = &str> {
+        self.cookie_names.iter().map(String::as_str)
+    }
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator>
+    SessionChannel<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// The name of the cookie of this channel.
+    pub fn cookie_name(&self) -> &str {
+        &self.cookie_name
+    }
+
+    /// The session store of this channel.
+    pub fn store(&self) -> &SessionStore<SessionData, SessionStoreConnection, CookieGenerator> {
+        &self.store
+    }
+}
+
+impl<SessionStoreConnection, CookieGenerator> Debug
+    for MultiSessionStore<SessionStoreConnection, CookieGenerator>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultiSessionStore")
+            .field("cookie_names", &self.cookie_names)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<SessionStoreConnection, CookieGenerator> Debug
+    for MultiSessionStoreBuilder<SessionStoreConnection, CookieGenerator>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultiSessionStoreBuilder")
+            .field("store", &self.store)
+            .field("key_prefixes", &self.key_prefixes)
+            .finish()
+    }
+}
@@ -0,0 +1,128 @@
+use crate::session::{saturating_add, SessionState};
+use crate::{
+    ConnectorOperation, CookieValue, Error, Session, SessionCookieGenerator, SessionId,
+    SessionStore, SessionStoreConnector, WriteSessionResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::fmt::Debug;
+
+/// An extension of [`SessionStoreConnector`] for backends that support session-bound nonces.
+///
+/// A nonce is a single-use token that belongs to a session and a purpose, e.g. the continuation of an email confirmation.
+/// Like session ids, nonces are only passed to the connector in hashed form.
+///
+/// Implementations must uphold the following in addition to the requirements of [`SessionStoreConnector`]:
+///  * [`delete_session`](SessionStoreConnector::delete_session) and
+///    [`create_tombstone`](SessionStoreConnector::create_tombstone) must also delete all nonces of the session.
+///  * [`update_session`](SessionStoreConnector::update_session) must keep the nonces of a session, even though its id changes.
+#[async_trait]
+pub trait NonceStoreConnector<SessionData>: SessionStoreConnector<SessionData> {
+    /// Create a nonce with the given `nonce_id` and `purpose` that belongs to the session identified by `session_id`
+    /// and expires at `expiry`.
+    ///
+    /// Returns [`WriteSessionResult::SessionIdExists`] if the session already has a nonce with the given `nonce_id`,
+    /// and [`Error::UpdatedSessionDoesNotExist`] if the session does not exist.
+    async fn create_nonce(
+        &mut self,
+        session_id: &SessionId,
+        nonce_id: &SessionId,
+        purpose: &str,
+        expiry: DateTime<Utc>,
+    ) -> Result<WriteSessionResult, Error<Self::Error>>;
+
+    /// Delete the nonce with the given `nonce_id` if it belongs to the session identified by `session_id`,
+    /// has the given `purpose` and is not expired at `now`.
+    ///
+    /// Returns true if the nonce was deleted.
+    /// This must be atomic, such that a nonce can never be consumed twice.
+    async fn consume_nonce(
+        &mut self,
+        session_id: &SessionId,
+        nonce_id: &SessionId,
+        purpose: &str,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>>;
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: NonceStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Issue a single-use nonce for the given `purpose` that belongs to the given session and is valid for `time_to_live`.
+    ///
+    /// The nonce has the same format and entropy as a session cookie.
+    /// It is deleted together with the session.
+    ///
+    /// Returns `None` if the session was not loaded from the session store or was marked for deletion,
+    /// since in this case nonces cannot be bound to it.
+    /// Returns `None` as well if the session store is in [read-only mode](crate::SessionStoreConfig::read_only).
+    pub async fn issue_nonce(
+        &self,
+        session: &Session<SessionData>,
+        purpose: &str,
+        time_to_live: Duration,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<Option<CookieValue>, Error<SessionStoreConnection::Error>> {
+        let (SessionState::Unchanged { current_id, .. } | SessionState::Changed { current_id, .. }) =
+            &session.state
+        else {
+            return Ok(None);
+        };
+        if self.suppress_write_in_read_only_mode(format_args!("issuing nonce for {purpose}")) {
+            return Ok(None);
+        }
+        let expiry = saturating_add(self.now(connection).await?, time_to_live);
+
+        let mut retries = self.write_retries(connection);
+        loop {
+            retries.check_limit()?;
+            let nonce = self.generate_cookie_value()?;
+            let nonce_id = self.session_id_from_cookie_value(&nonce);
+            let result = self
+                .intercept(
+                    ConnectorOperation::CreateNonce,
+                    connection.create_nonce(current_id, &nonce_id, purpose, expiry),
+                )
+                .await;
+            if let Some(()) = self.retry_write(&mut retries, result, connection).await? {
+                return Ok(Some(nonce));
+            }
+        }
+    }
+
+    /// Consume a nonce issued with [`issue_nonce`](SessionStore::issue_nonce) for the given session and `purpose`.
+    ///
+    /// Returns true if the nonce was valid, in which case it is now deleted.
+    /// Returns false if the nonce does not exist, has expired, was already consumed,
+    /// or belongs to a different session or purpose.
+    /// In [read-only mode](crate::SessionStoreConfig::read_only), nonces cannot be consumed, hence false is returned.
+    pub async fn consume_nonce(
+        &self,
+        session: &Session<SessionData>,
+        purpose: &str,
+        nonce: &CookieValue,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<bool, Error<SessionStoreConnection::Error>> {
+        let (SessionState::Unchanged { current_id, .. } | SessionState::Changed { current_id, .. }) =
+            &session.state
+        else {
+            return Ok(false);
+        };
+        if !self.cookie_generator.is_valid_cookie(nonce.expose())
+            || self.suppress_write_in_read_only_mode(format_args!("consuming nonce for {purpose}"))
+        {
+            return Ok(false);
+        }
+
+        let nonce_id = self.session_id_from_cookie_value(nonce);
+        let now = self.now(connection).await?;
+        self.intercept(
+            ConnectorOperation::ConsumeNonce,
+            connection.consume_nonce(current_id, &nonce_id, purpose, now),
+        )
+        .await
+    }
+}
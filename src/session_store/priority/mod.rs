@@ -0,0 +1,33 @@
+use crate::session_store::Hook;
+use crate::{SessionCookieGenerator, SessionPriority, SessionStore};
+use std::sync::Arc;
+
+/// Derives the [`SessionPriority`] of a session from its data, see [`SessionStore::set_priority_classifier`].
+pub trait SessionPriorityClassifier<SessionData>: Send + Sync {
+    /// Returns the priority of a session with the given data.
+    fn classify(&self, data: &SessionData) -> SessionPriority;
+}
+
+impl<SessionData, F: Fn(&SessionData) -> SessionPriority + Send + Sync>
+    SessionPriorityClassifier<SessionData> for F
+{
+    fn classify(&self, data: &SessionData) -> SessionPriority {
+        self(data)
+    }
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator: SessionCookieGenerator>
+    SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Sets a classifier that derives the priority of each session from its data whenever the session is written.
+    /// The priority is stored in [`SessionMetadata::priority`](crate::SessionMetadata::priority).
+    ///
+    /// Bounded connectors use the priority to decide which sessions to evict first under pressure,
+    /// e.g. anonymous sessions before authenticated ones, see [`MemoryStore::set_capacity`](crate::MemoryStore::set_capacity).
+    pub fn set_priority_classifier(
+        &mut self,
+        classifier: impl SessionPriorityClassifier<SessionData> + 'static,
+    ) {
+        self.priority_classifier = Some(Hook(Arc::new(classifier)));
+    }
+}
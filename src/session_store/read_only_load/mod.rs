@@ -0,0 +1,133 @@
+use crate::{
+    AssuranceLevel, ChannelBindingPolicy, ConnectorOperation, CookieValue, DeletionMode, Error,
+    Session, SessionCookieGenerator, SessionExpiry, SessionMetadata, SessionStore,
+    SessionStoreConnector,
+};
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+use tracing::warn;
+
+/// An immutable view of a session, loaded with [`SessionStore::load_session_read_only`].
+///
+/// Unlike a [`Session`], it cannot be modified, renewed or stored,
+/// so it never causes a write to the backend or an update of the session cookie.
+#[derive(Debug, Clone)]
+pub struct SessionRef<SessionData> {
+    session: Session<SessionData>,
+}
+
+impl<SessionData: Debug> SessionRef<SessionData> {
+    /// Returns the data of the session.
+    pub fn data(&self) -> &SessionData {
+        self.session.data()
+    }
+
+    /// Returns the expiry of the session as stored in the backend.
+    pub fn expiry(&self) -> &SessionExpiry {
+        self.session.expiry()
+    }
+
+    /// Returns the metadata of the session.
+    pub fn metadata(&self) -> &SessionMetadata {
+        self.session.metadata()
+    }
+
+    /// Returns the user the session belongs to, if any.
+    pub fn user_id(&self) -> Option<&str> {
+        self.session.user_id()
+    }
+
+    /// Returns the assurance level of the session at time `now`, or `None` if it has none or it has decayed.
+    pub fn assurance_level(&self, now: DateTime<Utc>) -> Option<AssuranceLevel> {
+        self.session.assurance_level(now)
+    }
+
+    /// Returns true if the assurance level of the session at time `now` is at least `level`.
+    pub fn has_assurance(&self, level: AssuranceLevel, now: DateTime<Utc>) -> bool {
+        self.session.has_assurance(level, now)
+    }
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Load a session like [`load_session`](SessionStore::load_session), but without the intent to write it.
+    ///
+    /// Like `load_session`, this does not return expired sessions, sessions whose schema is discarded,
+    /// and sessions rejected by the [`ChannelBindingPolicy`].
+    /// However, the session is neither renewed nor decayed, and neither the pre-expiry hook nor the anomaly scorer
+    /// are invoked, since these may change the session.
+    /// The returned [`SessionRef`] cannot be stored, so this never causes a write or a cookie update,
+    /// even under [`SessionRenewalStrategy::AutomaticRenewal`](crate::SessionRenewalStrategy::AutomaticRenewal).
+    ///
+    /// This is useful for high-traffic endpoints like health checks or static pages,
+    /// where writing to the store on every request is wasteful.
+    pub async fn load_session_read_only(
+        &self,
+        cookie_value: &CookieValue,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<Option<SessionRef<SessionData>>, Error<SessionStoreConnection::Error>> {
+        self.check_cookie_value(cookie_value)?;
+
+        let session_id = self.session_id_from_cookie_value(cookie_value);
+        let Some(mut session) = self
+            .intercept(
+                ConnectorOperation::ReadSession,
+                connection.read_session(session_id.clone()),
+            )
+            .await?
+        else {
+            if matches!(
+                self.config.get().deletion_mode,
+                DeletionMode::Tombstone { .. }
+            ) {
+                let now = self.now(connection).await?;
+                if self
+                    .intercept(
+                        ConnectorOperation::IsTombstone,
+                        connection.is_tombstone(&session_id, now),
+                    )
+                    .await?
+                {
+                    warn!("A client attempted to load a deleted session, which may indicate a replay of a stolen session cookie");
+                    self.score_retired_id();
+                }
+            }
+            return Ok(None);
+        };
+
+        if !session.matches_channel(None) {
+            match self.channel_binding_policy {
+                // A read-only load cannot rotate the session.
+                ChannelBindingPolicy::Reject | ChannelBindingPolicy::Rotate => {
+                    warn!("Rejected a session that was loaded over a different channel than it is bound to, which may indicate a stolen session cookie");
+                    return Ok(None);
+                }
+                ChannelBindingPolicy::Flag => {
+                    warn!("A session was loaded over a different channel than it is bound to, which may indicate a stolen session cookie");
+                }
+                ChannelBindingPolicy::Ignore => {}
+            }
+        }
+        if let Some(schema_check) = &self.schema_check {
+            if schema_check.0.discard(session.metadata.schema_fingerprint) {
+                return Ok(None);
+            }
+        }
+
+        let enforces_expiry = connection.enforces_expiry();
+        if !enforces_expiry || self.expiry_sanitization.is_some() {
+            let now = self.now(connection).await?;
+            if !self.sanitize_expiry(&mut session, now)?
+                || (!enforces_expiry && session.is_expired(now))
+            {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(SessionRef { session }))
+    }
+}
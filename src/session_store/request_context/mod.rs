@@ -0,0 +1,79 @@
+use crate::{
+    CookieValue, Error, Session, SessionCookieCommand, SessionCookieGenerator, SessionStore,
+    SessionStoreConnector,
+};
+use std::fmt::Debug;
+use tracing::{debug_span, Instrument};
+
+/// Information about the request on whose behalf the session store operates.
+///
+/// It is passed to the connector with [`SessionStoreConnector::set_request_context`],
+/// and recorded in a tracing span around the operation.
+/// This allows to correlate e.g. slow-query logs of the backend with specific requests.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct RequestContext {
+    /// An identifier of the request, e.g. the value of an `X-Request-Id` header.
+    pub request_id: Option<String>,
+    /// An identifier of the client, e.g. its IP address.
+    pub client_key: Option<String>,
+    /// The maximum number of connector operations for this request, enforced by a [`BudgetedStore`](crate::BudgetedStore).
+    pub operation_budget: Option<u32>,
+}
+
+impl RequestContext {
+    /// Create a request context with the given request id, no client key and no operation budget.
+    pub fn new(request_id: impl Into<String>) -> Self {
+        Self {
+            request_id: Some(request_id.into()),
+            client_key: None,
+            operation_budget: None,
+        }
+    }
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Like [`load_session`](SessionStore::load_session), but on behalf of the request described by `context`.
+    pub async fn load_session_with_context(
+        &self,
+        cookie_value: &CookieValue,
+        context: &RequestContext,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<Option<Session<SessionData>>, Error<SessionStoreConnection::Error>> {
+        connection.set_request_context(Some(context));
+        let result = self
+            .load_session(cookie_value, connection)
+            .instrument(debug_span!(
+                "load_session",
+                request_id = context.request_id.as_deref(),
+                client_key = context.client_key.as_deref(),
+            ))
+            .await;
+        connection.set_request_context(None);
+        result
+    }
+
+    /// Like [`store_session`](SessionStore::store_session), but on behalf of the request described by `context`.
+    pub async fn store_session_with_context(
+        &self,
+        session: Session<SessionData>,
+        context: &RequestContext,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<SessionCookieCommand, Error<SessionStoreConnection::Error>> {
+        connection.set_request_context(Some(context));
+        let result = self
+            .store_session(session, connection)
+            .instrument(debug_span!(
+                "store_session",
+                request_id = context.request_id.as_deref(),
+                client_key = context.client_key.as_deref(),
+            ))
+            .await;
+        connection.set_request_context(None);
+        result
+    }
+}
@@ -0,0 +1,220 @@
+#[cfg(feature = "metrics")]
+use crate::session_store::instrumentation;
+use crate::session_store::Hook;
+use crate::{
+    Error, SessionCookieGenerator, SessionStore, SessionStoreConnector, Sleeper, WriteSessionResult,
+};
+use rand::Rng;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Why a write of the session store failed, see [`RetryPolicy`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum RetryReason {
+    /// The generated session id already exists.
+    /// Retrying generates a new id, so no delay is required.
+    IdCollision,
+    /// The connector returned an error that it considers
+    /// [transient](crate::SessionStoreConnector::is_transient_error), e.g. a network timeout.
+    TransientError,
+}
+
+/// The decision of a [`RetryPolicy`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum RetryDecision {
+    /// Retry the write after the given delay.
+    Retry {
+        /// The delay before the next attempt.
+        delay: Duration,
+    },
+    /// Give up and return the error.
+    GiveUp,
+}
+
+/// Decides whether and when the session store retries a failed write, see [`SessionStore::set_retry_policy`].
+pub trait RetryPolicy: Send + Sync {
+    /// Decide whether to retry a write that failed for the given reason,
+    /// where `failures` counts the failures of the write for this reason, starting with 1.
+    fn retry(&self, reason: RetryReason, failures: u32) -> RetryDecision;
+}
+
+/// A [`RetryPolicy`] that retries id collisions immediately, and transient errors with exponential backoff.
+///
+/// The delay before the `n`-th retry after a transient error is `initial_delay * 2^(n-1)`, capped at `maximum_delay`.
+/// With `jitter`, the delay is instead drawn uniformly from zero to this value,
+/// such that clients that failed at the same time do not retry at the same time.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ExponentialBackoff {
+    /// The delay before the first retry after a transient error.
+    pub initial_delay: Duration,
+    /// The maximum delay before a retry after a transient error.
+    pub maximum_delay: Duration,
+    /// Whether to randomise the delays.
+    pub jitter: bool,
+    /// The maximum number of retries after id collisions.
+    /// The cap of the connector, see [`SessionStoreConnector::maximum_retries_on_id_collision`](crate::SessionStoreConnector::maximum_retries_on_id_collision),
+    /// applies in addition.
+    pub maximum_retries_on_id_collision: u32,
+    /// The maximum number of retries after transient errors.
+    pub maximum_retries_on_transient_error: u32,
+}
+
+impl Default for ExponentialBackoff {
+    /// Up to three retries after transient errors, starting with 50 milliseconds up to one second with jitter,
+    /// and up to ten retries after id collisions.
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(50),
+            maximum_delay: Duration::from_secs(1),
+            jitter: true,
+            maximum_retries_on_id_collision: 10,
+            maximum_retries_on_transient_error: 3,
+        }
+    }
+}
+
+impl RetryPolicy for ExponentialBackoff {
+    fn retry(&self, reason: RetryReason, failures: u32) -> RetryDecision {
+        match reason {
+            RetryReason::IdCollision if failures < self.maximum_retries_on_id_collision => {
+                RetryDecision::Retry {
+                    delay: Duration::ZERO,
+                }
+            }
+            RetryReason::TransientError if failures <= self.maximum_retries_on_transient_error => {
+                let delay = self
+                    .initial_delay
+                    .checked_mul(1 << (failures - 1).min(31))
+                    .unwrap_or(self.maximum_delay)
+                    .min(self.maximum_delay);
+                let delay = if self.jitter {
+                    rand::thread_rng().gen_range(Duration::ZERO..=delay)
+                } else {
+                    delay
+                };
+                RetryDecision::Retry { delay }
+            }
+            RetryReason::IdCollision | RetryReason::TransientError => RetryDecision::GiveUp,
+        }
+    }
+}
+
+impl<SessionData, SessionStoreConnection, CookieGenerator: SessionCookieGenerator>
+    SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Sets the policy that decides whether [`store_session`](SessionStore::store_session) retries failed writes.
+    ///
+    /// The policy applies to all writes that generate a new id, i.e. also to storing child sessions,
+    /// issuing nonces and storing sessions idempotently.
+    /// Since this crate does not depend on an async runtime, delays are awaited with the given [`Sleeper`].
+    ///
+    /// Without a retry policy, id collisions are retried immediately
+    /// up to the [maximum number of retries](SessionStore::set_maximum_retries_on_id_collision),
+    /// and errors of the connector are returned immediately.
+    /// Note that a transient error may hide a successful write, in which case retrying an update
+    /// fails with [`Error::UpdatedSessionDoesNotExist`](crate::Error::UpdatedSessionDoesNotExist).
+    pub fn set_retry_policy(
+        &mut self,
+        retry_policy: impl RetryPolicy + 'static,
+        sleeper: impl Sleeper + 'static,
+    ) {
+        self.retry_policy = Some((Hook(Arc::new(retry_policy)), Hook(Arc::new(sleeper))));
+    }
+
+    /// Decide whether to retry a write that failed for the given reason, and wait for the delay if so.
+    pub(crate) async fn retry(&self, reason: RetryReason, failures: u32) -> bool {
+        let Some((retry_policy, sleeper)) = &self.retry_policy else {
+            return reason == RetryReason::IdCollision;
+        };
+        match retry_policy.0.retry(reason, failures) {
+            RetryDecision::Retry { delay } => {
+                if !delay.is_zero() {
+                    sleeper.0.sleep(delay).await;
+                }
+                true
+            }
+            RetryDecision::GiveUp => false,
+        }
+    }
+}
+
+/// The failures of a single write that generates a new session id on each attempt, see [`SessionStore::retry_write`].
+pub(crate) struct WriteRetries {
+    maximum_retries_on_collision: Option<u32>,
+    collisions: u32,
+    transient_errors: u32,
+}
+
+impl WriteRetries {
+    /// Returns an error if no further attempt is allowed because of too many id collisions.
+    pub(crate) fn check_limit<ConnectorError>(&self) -> Result<(), Error<ConnectorError>> {
+        match self.maximum_retries_on_collision {
+            Some(maximum) if self.collisions >= maximum => {
+                Err(Error::MaximumSessionIdGenerationTriesReached { maximum })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Start counting the failures of a write with the given connection.
+    pub(crate) fn write_retries(&self, connection: &SessionStoreConnection) -> WriteRetries {
+        WriteRetries {
+            maximum_retries_on_collision: self
+                .effective_maximum_retries_on_id_collision(connection),
+            collisions: 0,
+            transient_errors: 0,
+        }
+    }
+
+    /// Handle the result of an attempt of a write according to the [retry policy](SessionStore::set_retry_policy).
+    ///
+    /// Returns the output of a successful write, `None` if the write should be attempted again with a new id,
+    /// and an error if the write failed for good.
+    pub(crate) async fn retry_write<Output>(
+        &self,
+        retries: &mut WriteRetries,
+        result: Result<WriteSessionResult<Output>, Error<SessionStoreConnection::Error>>,
+        connection: &SessionStoreConnection,
+    ) -> Result<Option<Output>, Error<SessionStoreConnection::Error>> {
+        match result {
+            Ok(WriteSessionResult::Ok(output)) => Ok(Some(output)),
+            Ok(WriteSessionResult::SessionIdExists) => {
+                #[cfg(feature = "metrics")]
+                instrumentation::record_id_collision();
+                retries.collisions += 1;
+                if self
+                    .retry(RetryReason::IdCollision, retries.collisions)
+                    .await
+                {
+                    Ok(None)
+                } else {
+                    Err(Error::MaximumSessionIdGenerationTriesReached {
+                        maximum: retries.collisions,
+                    })
+                }
+            }
+            Err(Error::SessionStoreConnector(error)) if connection.is_transient_error(&error) => {
+                retries.transient_errors += 1;
+                if self
+                    .retry(RetryReason::TransientError, retries.transient_errors)
+                    .await
+                {
+                    warn!("Retrying a write after a transient error: {error:?}");
+                    Ok(None)
+                } else {
+                    Err(Error::SessionStoreConnector(error))
+                }
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
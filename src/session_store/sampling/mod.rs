@@ -0,0 +1,101 @@
+use crate::session::SessionState;
+use crate::{Session, SessionExpiry};
+use rand::Rng;
+
+/// The kind of a sampled session write.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SessionWriteKind {
+    /// A new session was created.
+    Create,
+    /// An existing session was updated.
+    Update,
+    /// A session was deleted.
+    Delete,
+}
+
+/// A session write that was sampled by the session store, see [`SessionStore::set_session_sampler`](crate::SessionStore::set_session_sampler).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SessionWriteSample<SessionData> {
+    /// The kind of the write.
+    pub kind: SessionWriteKind,
+    /// The expiry of the written session, or `None` if the session was deleted.
+    pub expiry: Option<SessionExpiry>,
+    /// The redacted data of the written session, or `None` if the session was deleted.
+    pub data: Option<SessionData>,
+}
+
+/// A sink that receives sampled session writes, e.g. to analyse session sizes and churn in production.
+///
+/// This is implemented for all closures `Fn(SessionWriteSample<SessionData>)`.
+/// Recording happens synchronously while storing the session, so slow sinks should forward samples e.g. through a channel.
+pub trait SessionSampleSink<SessionData>: Send + Sync {
+    /// Record a sampled session write.
+    fn record(&self, sample: SessionWriteSample<SessionData>);
+}
+
+impl<SessionData, F: Fn(SessionWriteSample<SessionData>) + Send + Sync>
+    SessionSampleSink<SessionData> for F
+{
+    fn record(&self, sample: SessionWriteSample<SessionData>) {
+        self(sample)
+    }
+}
+
+type SessionDataRedactor<SessionData> = dyn Fn(&SessionData) -> SessionData + Send + Sync;
+
+pub(crate) struct SessionSampler<SessionData> {
+    fraction: f64,
+    redactor: Box<SessionDataRedactor<SessionData>>,
+    sink: Box<dyn SessionSampleSink<SessionData>>,
+}
+
+impl<SessionData> SessionSampler<SessionData> {
+    pub(crate) fn new(
+        fraction: f64,
+        redactor: impl Fn(&SessionData) -> SessionData + Send + Sync + 'static,
+        sink: impl SessionSampleSink<SessionData> + 'static,
+    ) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "the sampled fraction must be between 0 and 1, but is {fraction}"
+        );
+        Self {
+            fraction,
+            redactor: Box::new(redactor),
+            sink: Box::new(sink),
+        }
+    }
+
+    /// Randomly record the write of the given session, which must have been stored successfully.
+    pub(crate) fn sample<const COOKIE_LENGTH: usize>(
+        &self,
+        session: &Session<SessionData, COOKIE_LENGTH>,
+    ) {
+        if !rand::thread_rng().gen_bool(self.fraction) {
+            return;
+        }
+
+        let sample = match &session.state {
+            SessionState::NewChanged { expiry, data } => SessionWriteSample {
+                kind: SessionWriteKind::Create,
+                expiry: Some(*expiry),
+                data: Some((self.redactor)(data)),
+            },
+            SessionState::Changed { expiry, data, .. } => SessionWriteSample {
+                kind: SessionWriteKind::Update,
+                expiry: Some(*expiry),
+                data: Some((self.redactor)(data)),
+            },
+            SessionState::Deleted { .. } => SessionWriteSample {
+                kind: SessionWriteKind::Delete,
+                expiry: None,
+                data: None,
+            },
+            SessionState::NewUnchanged { .. }
+            | SessionState::Unchanged { .. }
+            | SessionState::NewDeleted
+            | SessionState::Invalid => return,
+        };
+        self.sink.record(sample);
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// What to do when a loaded session was written with a different schema version of the session data,
+/// see [`SessionStore::set_schema_version`](crate::SessionStore::set_schema_version).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SchemaMismatchPolicy {
+    /// Log a warning and treat the session as if it did not exist.
+    #[default]
+    Discard,
+
+    /// Log a warning, but return the session anyways.
+    /// This allows to monitor mismatches, e.g. during a rolling deployment.
+    Flag,
+
+    /// Return the session without logging.
+    Ignore,
+}
+
+/// The schema fingerprint of a session store, together with its mismatch policy and counter.
+#[derive(Debug)]
+pub(crate) struct SchemaCheck {
+    fingerprint: [u8; blake3::OUT_LEN],
+    policy: SchemaMismatchPolicy,
+    mismatches: AtomicU64,
+}
+
+impl SchemaCheck {
+    pub(crate) fn new(version: &str, policy: SchemaMismatchPolicy) -> Self {
+        Self {
+            fingerprint: blake3::hash(version.as_bytes()).into(),
+            policy,
+            mismatches: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn fingerprint(&self) -> [u8; blake3::OUT_LEN] {
+        self.fingerprint
+    }
+
+    pub(crate) fn mismatches(&self) -> u64 {
+        self.mismatches.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if a session with the given fingerprint should be discarded.
+    /// Sessions without fingerprint were written before fingerprinting was enabled, and are accepted.
+    pub(crate) fn discard(&self, fingerprint: Option<[u8; blake3::OUT_LEN]>) -> bool {
+        match fingerprint {
+            Some(fingerprint) if fingerprint != self.fingerprint => {
+                self.mismatches.fetch_add(1, Ordering::Relaxed);
+                match self.policy {
+                    SchemaMismatchPolicy::Discard => {
+                        tracing::warn!("Discarded a session that was written with a different schema version of the session data");
+                        true
+                    }
+                    SchemaMismatchPolicy::Flag => {
+                        tracing::warn!("Loaded a session that was written with a different schema version of the session data");
+                        false
+                    }
+                    SchemaMismatchPolicy::Ignore => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
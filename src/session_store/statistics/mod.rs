@@ -0,0 +1,177 @@
+use crate::session::SessionState;
+use crate::{Session, SessionExpiry};
+use chrono::{DateTime, Utc};
+use std::sync::Mutex;
+
+/// The number of buckets of a [`Histogram`], one for zero and one for each power of two up to `2^63`.
+const HISTOGRAM_BUCKETS: usize = 65;
+
+/// A histogram with exponentially growing buckets, suitable to estimate percentiles of values that span many orders of magnitude.
+///
+/// Bucket `0` counts the value zero, and bucket `i > 0` counts values in `[2^(i-1), 2^i)`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Histogram {
+    buckets: [u64; HISTOGRAM_BUCKETS],
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; HISTOGRAM_BUCKETS],
+        }
+    }
+}
+
+impl Histogram {
+    /// Record the given value.
+    pub fn record(&mut self, value: u64) {
+        let bucket = (u64::BITS - value.leading_zeros()) as usize;
+        self.buckets[bucket] += 1;
+    }
+
+    /// The number of recorded values.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns a histogram with the count of each bucket replaced by `f` applied to it.
+    pub(crate) fn map_counts(&self, f: impl FnMut(u64) -> u64) -> Self {
+        Self {
+            buckets: self.buckets.map(f),
+        }
+    }
+
+    /// Returns an upper bound for the given percentile of the recorded values, or `None` if no values were recorded.
+    /// The returned bound is at most twice as large as the actual percentile.
+    ///
+    /// **Panics** if `percentile` is not between `0.0` and `100.0`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use typed_session::Histogram;
+    /// let mut histogram = Histogram::default();
+    /// assert_eq!(histogram.percentile(50.0), None);
+    /// for value in 1..=100 {
+    ///     histogram.record(value);
+    /// }
+    /// assert_eq!(histogram.percentile(50.0), Some(63));
+    /// assert_eq!(histogram.percentile(100.0), Some(127));
+    /// ```
+    pub fn percentile(&self, percentile: f64) -> Option<u64> {
+        assert!(
+            (0.0..=100.0).contains(&percentile),
+            "the percentile must be between 0 and 100, but is {percentile}"
+        );
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+
+        let rank = ((percentile / 100.0 * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, bucket_count) in self.buckets.iter().enumerate() {
+            seen += bucket_count;
+            if seen >= rank {
+                return Some(if bucket == 0 {
+                    0
+                } else {
+                    u64::MAX >> (u64::BITS as usize - bucket)
+                });
+            }
+        }
+        unreachable!("the rank is at most the count")
+    }
+}
+
+/// Statistics about the sessions handled by a session store, see [`SessionStore::runtime_statistics`](crate::SessionStore::runtime_statistics).
+///
+/// Ages are only known for sessions that were created after [`SessionMetadata::created_at`](crate::SessionMetadata::created_at)
+/// was introduced, i.e. older sessions are not recorded.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct RuntimeStatistics {
+    /// The age in seconds of sessions when they were deleted.
+    pub session_age_at_deletion: Histogram,
+    /// The age in seconds of sessions when they expired.
+    /// Only expired sessions that are attempted to be loaded are recorded.
+    pub session_age_at_expiry: Histogram,
+    /// The size of the session data when it was written, as determined by the payload size function.
+    pub payload_size_at_write: Histogram,
+}
+
+type PayloadSize<SessionData> = dyn Fn(&SessionData) -> usize + Send + Sync;
+
+pub(crate) struct RuntimeStatisticsRecorder<SessionData> {
+    payload_size: Box<PayloadSize<SessionData>>,
+    statistics: Mutex<RuntimeStatistics>,
+}
+
+impl<SessionData> RuntimeStatisticsRecorder<SessionData> {
+    pub(crate) fn new(
+        payload_size: impl Fn(&SessionData) -> usize + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            payload_size: Box::new(payload_size),
+            statistics: Default::default(),
+        }
+    }
+
+    pub(crate) fn statistics(&self) -> RuntimeStatistics {
+        self.statistics.lock().unwrap().clone()
+    }
+
+    /// Record the write of the given session, which must have been stored successfully.
+    pub(crate) fn record_write<const COOKIE_LENGTH: usize>(
+        &self,
+        session: &Session<SessionData, COOKIE_LENGTH>,
+        now: DateTime<Utc>,
+    ) {
+        match &session.state {
+            SessionState::NewChanged { data, .. } | SessionState::Changed { data, .. } => {
+                let payload_size = (self.payload_size)(data);
+                self.statistics
+                    .lock()
+                    .unwrap()
+                    .payload_size_at_write
+                    .record(payload_size.try_into().unwrap_or(u64::MAX));
+            }
+            SessionState::Deleted { .. } => {
+                if let Some(age) = age_in_seconds(session.metadata.created_at, now) {
+                    self.statistics
+                        .lock()
+                        .unwrap()
+                        .session_age_at_deletion
+                        .record(age);
+                }
+            }
+            SessionState::NewUnchanged { .. }
+            | SessionState::Unchanged { .. }
+            | SessionState::NewDeleted
+            | SessionState::Invalid => {}
+        }
+    }
+
+    /// Record that the given session was found to be expired.
+    pub(crate) fn record_expiry<const COOKIE_LENGTH: usize>(
+        &self,
+        session: &Session<SessionData, COOKIE_LENGTH>,
+    ) {
+        if let SessionState::Unchanged {
+            expiry: SessionExpiry::DateTime(expiry),
+            ..
+        } = &session.state
+        {
+            if let Some(age) = age_in_seconds(session.metadata.created_at, *expiry) {
+                self.statistics
+                    .lock()
+                    .unwrap()
+                    .session_age_at_expiry
+                    .record(age);
+            }
+        }
+    }
+}
+
+fn age_in_seconds(created_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Option<u64> {
+    created_at.map(|created_at| (now - created_at).num_seconds().max(0) as u64)
+}
@@ -0,0 +1,107 @@
+use crate::{
+    Error, Session, SessionCookieCommand, SessionCookieGenerator, SessionStore,
+    SessionStoreConnector,
+};
+use std::fmt::Debug;
+
+/// A request-scoped session whose writes are coalesced into a single write,
+/// see [`SessionStore::commit_transaction`].
+///
+/// Handlers and middleware layers that would each call [`SessionStore::store_session`] instead
+/// [`stage`](SessionTransaction::stage) their version of the session, or modify it in place with
+/// [`session_mut`](SessionTransaction::session_mut).
+/// Since a [`Session`] tracks its own changes, the staged session carries all changes made so far,
+/// and committing it causes at most one connector write and one id rotation, and hence at most one new cookie.
+///
+/// The transaction holds no reference to the session store, so it can be stored e.g. in the extensions of a request.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "memory-store")] {
+/// # use typed_session::{MemoryStore, Session, SessionCookieCommand, SessionRenewalStrategy, SessionStore, SessionTransaction};
+/// # async_std::task::block_on(async {
+/// let store: SessionStore<u32, _> = SessionStore::new(SessionRenewalStrategy::Ignore);
+/// let mut connection = MemoryStore::new();
+///
+/// let mut transaction = SessionTransaction::new(Session::new_with_data(0));
+/// // An authentication layer changes the session...
+/// *transaction.session_mut().data_mut() += 1;
+/// // ...and so does the handler.
+/// let mut session = transaction.session().clone();
+/// *session.data_mut() += 1;
+/// transaction.stage(session);
+///
+/// let command = store.commit_transaction(transaction, &mut connection).await?;
+/// assert!(matches!(command, SessionCookieCommand::Set { .. }));
+/// assert_eq!(connection.len(), 1);
+/// # Ok::<(), typed_session::Error<std::convert::Infallible>>(()) }).unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct SessionTransaction<SessionData> {
+    session: Session<SessionData>,
+    staged_writes: u32,
+}
+
+impl<SessionData> SessionTransaction<SessionData> {
+    /// Begin a transaction for the given session, e.g. as returned by [`SessionStore::load_session`].
+    pub fn new(session: Session<SessionData>) -> Self {
+        Self {
+            session,
+            staged_writes: 0,
+        }
+    }
+
+    /// A reference to the session of this transaction.
+    pub fn session(&self) -> &Session<SessionData> {
+        &self.session
+    }
+
+    /// A mutable reference to the session of this transaction.
+    pub fn session_mut(&mut self) -> &mut Session<SessionData> {
+        &mut self.session
+    }
+
+    /// Replace the session of this transaction with the given version of it,
+    /// where [`SessionStore::store_session`] would be called without a transaction.
+    ///
+    /// The given session should be derived from the session of this transaction,
+    /// since changes that were only made to the replaced session are lost.
+    pub fn stage(&mut self, session: Session<SessionData>) {
+        self.session = session;
+        self.staged_writes = self.staged_writes.saturating_add(1);
+    }
+
+    /// Returns how often a session was [staged](SessionTransaction::stage) in this transaction.
+    pub fn staged_writes(&self) -> u32 {
+        self.staged_writes
+    }
+
+    /// End the transaction without storing it, returning its session.
+    pub fn into_session(self) -> Session<SessionData> {
+        self.session
+    }
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Store the session of the given transaction with a single call to [`store_session`](SessionStore::store_session),
+    /// no matter how often it was staged.
+    pub async fn commit_transaction(
+        &self,
+        transaction: SessionTransaction<SessionData>,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<SessionCookieCommand, Error<SessionStoreConnection::Error>> {
+        tracing::debug!(
+            "Committing a session transaction with {} staged writes",
+            transaction.staged_writes
+        );
+        self.store_session(transaction.session, connection).await
+    }
+}
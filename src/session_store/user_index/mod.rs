@@ -0,0 +1,132 @@
+use crate::session::saturating_add;
+use crate::{
+    ConnectorOperation, DeletionMode, Error, Session, SessionCookieGenerator, SessionStore,
+    SessionStoreConnector,
+};
+use async_trait::async_trait;
+use std::fmt::Debug;
+
+/// An extension of [`SessionStoreConnector`] for backends that index sessions by their user,
+/// as set with [`Session::set_user_id`](crate::Session::set_user_id).
+///
+/// Implementations must keep the index up to date on all operations of [`SessionStoreConnector`].
+#[async_trait]
+pub trait UserIndexedSessionStoreConnector<SessionData>:
+    SessionStoreConnector<SessionData>
+{
+    /// Delete all sessions that belong to the user identified by `user_id`, including their child sessions.
+    ///
+    /// Returns the number of deleted sessions, including child sessions.
+    async fn delete_sessions_of_user(&mut self, user_id: &str)
+        -> Result<usize, Error<Self::Error>>;
+
+    /// Read all sessions that belong to the user identified by `user_id`, in no particular order.
+    /// Expired sessions may be returned, and tombstones must not be returned.
+    ///
+    /// The default implementation returns [`Error::UnsupportedOperation`].
+    async fn read_sessions_of_user(
+        &mut self,
+        user_id: &str,
+    ) -> Result<Vec<Session<SessionData>>, Error<Self::Error>> {
+        let _ = user_id;
+        Err(Error::UnsupportedOperation {
+            operation: "read_sessions_of_user",
+        })
+    }
+}
+
+/// The result of [`SessionStore::erase_user_data`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ErasureReport {
+    /// The number of deleted sessions, including child sessions.
+    pub deleted_sessions: usize,
+}
+
+impl<
+        SessionData: Debug,
+        SessionStoreConnection: UserIndexedSessionStoreConnector<SessionData> + Send,
+        CookieGenerator: SessionCookieGenerator,
+    > SessionStore<SessionData, SessionStoreConnection, CookieGenerator>
+{
+    /// Delete all sessions of the user identified by `user_id`, e.g. to comply with a request for erasure of personal data.
+    ///
+    /// Sessions are deleted without leaving tombstones, regardless of the [`DeletionMode`](crate::DeletionMode).
+    pub async fn erase_user_data(
+        &self,
+        user_id: &str,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<ErasureReport, Error<SessionStoreConnection::Error>> {
+        let deleted_sessions = self
+            .intercept(
+                ConnectorOperation::DeleteSessionsOfUser,
+                connection.delete_sessions_of_user(user_id),
+            )
+            .await?;
+        Ok(ErasureReport { deleted_sessions })
+    }
+
+    /// Returns all unexpired sessions of the user identified by `user_id`, e.g. to show a list of logged-in devices.
+    ///
+    /// The sessions are not renewed. They can be deleted and stored like loaded sessions.
+    pub async fn user_sessions(
+        &self,
+        user_id: &str,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<Vec<Session<SessionData>>, Error<SessionStoreConnection::Error>> {
+        let mut sessions = self
+            .intercept(
+                ConnectorOperation::ReadSessionsOfUser,
+                connection.read_sessions_of_user(user_id),
+            )
+            .await?;
+        let now = self.now(connection).await?;
+        sessions.retain(|session| !session.is_expired(now));
+        Ok(sessions)
+    }
+
+    /// Delete all sessions of the user identified by `user_id` according to the [`DeletionMode`],
+    /// e.g. to log the user out everywhere after a password change.
+    ///
+    /// Unlike [`erase_user_data`](SessionStore::erase_user_data), this leaves tombstones if configured,
+    /// such that stolen cookies of the revoked sessions are detected when they are replayed.
+    /// Child sessions are deleted together with their parents.
+    ///
+    /// Returns the number of invalidated sessions, excluding child sessions.
+    pub async fn invalidate_user_sessions(
+        &self,
+        user_id: &str,
+        connection: &mut SessionStoreConnection,
+    ) -> Result<usize, Error<SessionStoreConnection::Error>> {
+        let sessions = self
+            .intercept(
+                ConnectorOperation::ReadSessionsOfUser,
+                connection.read_sessions_of_user(user_id),
+            )
+            .await?;
+        let ids: Vec<_> = sessions
+            .iter()
+            .filter_map(|session| session.current_id().cloned())
+            .collect();
+
+        match self.config.get().deletion_mode {
+            DeletionMode::Hard => {
+                self.intercept(
+                    ConnectorOperation::DeleteSessions,
+                    connection.delete_sessions(&ids),
+                )
+                .await?
+            }
+            DeletionMode::Tombstone { time_to_live } => {
+                let expiry = saturating_add(self.now(connection).await?, time_to_live);
+                for id in &ids {
+                    self.intercept(
+                        ConnectorOperation::CreateTombstone,
+                        connection.create_tombstone(id, expiry),
+                    )
+                    .await?;
+                }
+            }
+        }
+        Ok(ids.len())
+    }
+}
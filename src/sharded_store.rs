@@ -0,0 +1,78 @@
+use crate::routed_store::{RoutedStore, Routing};
+use crate::SessionId;
+
+/// A session store connector that spreads sessions over multiple connectors, e.g. multiple Redis instances.
+///
+/// The shard of a session is determined from a stable hash of its id with jump consistent hashing,
+/// so it depends only on the number of shards.
+/// When a shard is added, about a fraction `1 / shards` of the sessions move to the new shard,
+/// and since they are not copied, they are lost. All other sessions keep their shard.
+///
+/// Updates are only performed within a single shard, so the atomicity guarantees of
+/// [`SessionStoreConnector::update_session`](crate::SessionStoreConnector::update_session) are preserved.
+/// To achieve this, new ids that would belong to a different shard than the previous id are not
+/// [accepted](crate::SessionStoreConnector::accepts_session_id), such that the session store generates a different id.
+/// This requires on average as many tries as there are shards, which do not count as id collisions.
+pub type ShardedStore<SessionStoreConnection> = RoutedStore<ShardRouting, SessionStoreConnection>;
+
+/// The [`Routing`] of the [`ShardedStore`].
+#[derive(Debug, Clone, Copy)]
+pub struct ShardRouting {
+    shards: usize,
+}
+
+impl Routing for ShardRouting {
+    const NAME: &'static str = "ShardedStore";
+
+    fn route(&self, id: &SessionId) -> Option<usize> {
+        let hash = blake3::hash(id.as_ref());
+        let key = u64::from_le_bytes(hash.as_bytes()[..8].try_into().unwrap());
+        Some(jump_consistent_hash(key, self.shards))
+    }
+}
+
+impl<SessionStoreConnection> ShardedStore<SessionStoreConnection> {
+    /// Spread sessions over the given shards.
+    ///
+    /// The mapping from sessions to shards depends on the order of the shards,
+    /// so new shards must be appended at the end.
+    ///
+    /// **Panics** if there are no shards.
+    pub fn new(shards: impl IntoIterator<Item = SessionStoreConnection>) -> Self {
+        let shards: Vec<_> = shards.into_iter().collect();
+        assert!(!shards.is_empty(), "there must be at least one shard");
+        Self {
+            routing: ShardRouting {
+                shards: shards.len(),
+            },
+            connections: shards,
+        }
+    }
+
+    /// Returns the index of the shard the session with the given id belongs to.
+    pub fn shard_of(&self, id: &SessionId) -> usize {
+        self.routing.route(id).expect("shards are always available")
+    }
+
+    /// Returns the shards.
+    pub fn shards(&self) -> &[SessionStoreConnection] {
+        &self.connections
+    }
+
+    /// Returns the shards, consuming this wrapper.
+    pub fn into_inner(self) -> Vec<SessionStoreConnection> {
+        self.connections
+    }
+}
+
+/// Map the given key to one of `buckets` buckets, see [Lamping and Veach, 2014](https://arxiv.org/abs/1406.2294).
+fn jump_consistent_hash(mut key: u64, buckets: usize) -> usize {
+    let mut bucket = 0;
+    let mut next = 0;
+    while next < buckets {
+        bucket = next;
+        key = key.wrapping_mul(2862933555777941757).wrapping_add(1);
+        next = ((bucket as f64 + 1.0) * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as usize;
+    }
+    bucket
+}
@@ -0,0 +1,81 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// Waits for a duration on some async runtime.
+///
+/// This crate does not depend on a specific async runtime, so all features that need to wait,
+/// like the backoff of a [retry policy](crate::SessionStore::set_retry_policy), take a sleeper.
+/// Implementations are available for tokio as `TokioSleeper` under the feature flag `tokio-sleeper`,
+/// for async-std as `AsyncStdSleeper` under the feature flag `async-std-sleeper`,
+/// and for the browser as `WasmSleeper` under the feature flag `wasm-sleeper`.
+///
+/// Other runtimes can be used with a closure, since this is implemented for functions that return a boxed future.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::Sleeper;
+/// # use std::time::Duration;
+/// let sleeper = |duration| -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> {
+///     Box::pin(async_std::task::sleep(duration))
+/// };
+/// # async_std::task::block_on(async {
+/// sleeper.sleep(Duration::from_millis(1)).await;
+/// # });
+/// ```
+pub trait Sleeper: Send + Sync {
+    /// Returns a future that completes after the given duration.
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+}
+
+impl<F> Sleeper for F
+where
+    F: Fn(Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync,
+{
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self(duration)
+    }
+}
+
+/// A [`Sleeper`] for the tokio runtime, available under the feature flag `tokio-sleeper`.
+#[cfg(feature = "tokio-sleeper")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioSleeper;
+
+#[cfg(feature = "tokio-sleeper")]
+impl Sleeper for TokioSleeper {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A [`Sleeper`] for the async-std runtime, available under the feature flag `async-std-sleeper`.
+#[cfg(feature = "async-std-sleeper")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsyncStdSleeper;
+
+#[cfg(feature = "async-std-sleeper")]
+impl Sleeper for AsyncStdSleeper {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+/// A [`Sleeper`] for WebAssembly in the browser, based on `setTimeout`,
+/// available under the feature flag `wasm-sleeper`.
+///
+/// The returned futures must be polled on the thread that created them, which is always the case in the browser.
+#[cfg(feature = "wasm-sleeper")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WasmSleeper;
+
+#[cfg(feature = "wasm-sleeper")]
+impl Sleeper for WasmSleeper {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        // Browsers run WebAssembly on a single thread, so the timer is never sent to another thread.
+        Box::pin(send_wrapper::SendWrapper::new(gloo_timers::future::sleep(
+            duration,
+        )))
+    }
+}
@@ -0,0 +1,34 @@
+use std::future::Future;
+use std::pin::Pin;
+
+/// Runs futures in the background on some async runtime.
+///
+/// Like with [`Sleeper`](crate::Sleeper), this keeps the crate independent of a specific async runtime.
+/// Features that work in the background, like the
+/// [stale-while-revalidate mode](crate::CachedStore::set_stale_while_revalidate) of the cached store, take a spawner.
+/// This is implemented for functions that take a boxed future, so e.g. `tokio::spawn` can be passed in a closure.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::Spawner;
+/// # use std::future::Future;
+/// # use std::pin::Pin;
+/// let spawner = |future: Pin<Box<dyn Future<Output = ()> + Send>>| {
+///     async_std::task::spawn(future);
+/// };
+/// spawner.spawn(Box::pin(async {}));
+/// ```
+pub trait Spawner: Send + Sync {
+    /// Runs the given future to completion in the background.
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>);
+}
+
+impl<F> Spawner for F
+where
+    F: Fn(Pin<Box<dyn Future<Output = ()> + Send>>) + Send + Sync,
+{
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>) {
+        self(future)
+    }
+}
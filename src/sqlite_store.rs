@@ -0,0 +1,427 @@
+use crate::{
+    ConnectorDescription, Error, Session, SessionExpiry, SessionId, SessionMetadata,
+    SessionStoreConnector, WriteSessionResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+/// A session store connector for [SQLite](https://www.sqlite.org) using [sqlx](https://crates.io/crates/sqlx),
+/// available under the feature flag `sqlite-store`.
+///
+/// This makes the crate usable for small self-hosted applications without external infrastructure.
+/// Sessions are stored in the following tables, which can be created with [`migrate`](SqliteStore::migrate):
+///
+/// ```sql
+/// CREATE TABLE typed_sessions (
+///     id BLOB PRIMARY KEY,       -- the hashed session id
+///     expiry INTEGER,            -- milliseconds since the epoch, NULL if the session never expires
+///     metadata TEXT NOT NULL,    -- JSON
+///     data TEXT NOT NULL         -- JSON
+/// );
+/// CREATE TABLE typed_sessions_tombstones (
+///     id BLOB PRIMARY KEY,
+///     expiry INTEGER NOT NULL
+/// );
+/// ```
+///
+/// The table names can be changed with [`set_table_name`](SqliteStore::set_table_name).
+/// Expired sessions are not deleted automatically, see [`delete_expired_sessions`](SessionStoreConnector::delete_expired_sessions)
+/// and [`SessionCleaner`](crate::SessionCleaner).
+/// Child sessions are not supported.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use typed_session::{CookieValue, SessionRenewalStrategy, SessionStore, SqliteStore};
+/// # async fn example(cookie_value: CookieValue) -> Result<(), Box<dyn std::error::Error>> {
+/// let pool = sqlx::SqlitePool::connect("sqlite://sessions.db?mode=rwc").await?;
+/// let mut connection: SqliteStore<String> = SqliteStore::new(pool);
+/// connection.migrate().await?;
+/// let store: SessionStore<String, _> = SessionStore::new(SessionRenewalStrategy::Ignore);
+/// let session = store.load_session(&cookie_value, &mut connection).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SqliteStore<SessionData> {
+    pool: SqlitePool,
+    table_name: String,
+    maximum_retries_on_id_collision: Option<u32>,
+    data: PhantomData<fn() -> SessionData>,
+}
+
+/// The errors of a [`SqliteStore`].
+#[derive(Debug, thiserror::Error)]
+pub enum SqliteStoreError {
+    /// The database returned an error.
+    #[error("database error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+
+    /// A session could not be serialised or deserialised.
+    #[error("could not serialise or deserialise a session: {0}")]
+    Serialisation(#[from] serde_json::Error),
+}
+
+impl<SessionData> SqliteStore<SessionData> {
+    /// Create a new connector that stores sessions in the table `typed_sessions` of the given database.
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            table_name: "typed_sessions".to_string(),
+            maximum_retries_on_id_collision: None,
+            data: PhantomData,
+        }
+    }
+
+    /// Returns the name of the table that stores the sessions.
+    /// Tombstones are stored in a table with the suffix `_tombstones`.
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Sets the name of the table that stores the sessions.
+    /// Tombstones are stored in a table with the suffix `_tombstones`.
+    ///
+    /// The name is inserted into queries without escaping, so it must not be derived from untrusted input.
+    pub fn set_table_name(&mut self, table_name: impl Into<String>) {
+        self.table_name = table_name.into();
+    }
+
+    /// Sets the maximum retries on id collision, see [SessionStoreConnector::maximum_retries_on_id_collision] for details.
+    pub fn set_maximum_retries_on_id_collision(
+        &mut self,
+        maximum_retries_on_id_collision: Option<u32>,
+    ) {
+        self.maximum_retries_on_id_collision = maximum_retries_on_id_collision;
+    }
+
+    /// Create the tables of this connector, if they do not exist yet.
+    pub async fn migrate(&self) -> Result<(), SqliteStoreError> {
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                id BLOB PRIMARY KEY,
+                expiry INTEGER,
+                metadata TEXT NOT NULL,
+                data TEXT NOT NULL
+            )",
+            self.table_name
+        ))
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {}_tombstones (
+                id BLOB PRIMARY KEY,
+                expiry INTEGER NOT NULL
+            )",
+            self.table_name
+        ))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+fn expiry_column(expiry: &SessionExpiry) -> Option<i64> {
+    match expiry {
+        SessionExpiry::DateTime(expiry) => Some(expiry.timestamp_millis()),
+        SessionExpiry::Never => None,
+    }
+}
+
+#[async_trait]
+impl<SessionData: Serialize + DeserializeOwned + Send + Sync> SessionStoreConnector<SessionData>
+    for SqliteStore<SessionData>
+{
+    type Error = SqliteStoreError;
+
+    fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        self.maximum_retries_on_id_collision
+    }
+
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        matches!(
+            error,
+            SqliteStoreError::Sqlx(
+                sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::WorkerCrashed
+            )
+        )
+    }
+
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::new("SqliteStore")
+    }
+
+    async fn create_session(
+        &mut self,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let metadata = serde_json::to_string(metadata).map_err(SqliteStoreError::from)?;
+        let data = serde_json::to_string(data).map_err(SqliteStoreError::from)?;
+        let created = sqlx::query(&format!(
+            "INSERT INTO {0} (id, expiry, metadata, data)
+            SELECT ?1, ?2, ?3, ?4
+            WHERE NOT EXISTS (SELECT 1 FROM {0}_tombstones WHERE id = ?1)
+            ON CONFLICT (id) DO NOTHING",
+            self.table_name
+        ))
+        .bind(current_id.as_ref())
+        .bind(expiry_column(expiry))
+        .bind(metadata)
+        .bind(data)
+        .execute(&self.pool)
+        .await
+        .map_err(SqliteStoreError::from)?
+        .rows_affected();
+        if created == 1 {
+            Ok(WriteSessionResult::Ok(()))
+        } else {
+            Ok(WriteSessionResult::SessionIdExists)
+        }
+    }
+
+    async fn read_session(
+        &mut self,
+        id: SessionId,
+    ) -> Result<Option<Session<SessionData>>, Error<Self::Error>> {
+        let row: Option<(Option<i64>, String, String)> = sqlx::query_as(&format!(
+            "SELECT expiry, metadata, data FROM {} WHERE id = ?1",
+            self.table_name
+        ))
+        .bind(id.as_ref())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(SqliteStoreError::from)?;
+        let Some((expiry, metadata, data)) = row else {
+            return Ok(None);
+        };
+        let metadata = serde_json::from_str(&metadata).map_err(SqliteStoreError::from)?;
+        let data = serde_json::from_str(&data).map_err(SqliteStoreError::from)?;
+        let expiry = expiry
+            .and_then(|expiry| Utc.timestamp_millis_opt(expiry).single())
+            .map_or(SessionExpiry::Never, SessionExpiry::DateTime);
+        Ok(Some(Session::new_from_session_store(
+            id, expiry, metadata, data,
+        )))
+    }
+
+    async fn update_session(
+        &mut self,
+        current_id: &SessionId,
+        previous_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let metadata = serde_json::to_string(metadata).map_err(SqliteStoreError::from)?;
+        let data = serde_json::to_string(data).map_err(SqliteStoreError::from)?;
+        let mut transaction = self.pool.begin().await.map_err(SqliteStoreError::from)?;
+
+        let (is_tombstone,): (bool,) = sqlx::query_as(&format!(
+            "SELECT EXISTS (SELECT 1 FROM {}_tombstones WHERE id = ?1)",
+            self.table_name
+        ))
+        .bind(current_id.as_ref())
+        .fetch_one(&mut *transaction)
+        .await
+        .map_err(SqliteStoreError::from)?;
+        if is_tombstone {
+            return Ok(WriteSessionResult::SessionIdExists);
+        }
+
+        let updated = match sqlx::query(&format!(
+            "UPDATE {} SET id = ?1, expiry = ?2, metadata = ?3, data = ?4 WHERE id = ?5",
+            self.table_name
+        ))
+        .bind(current_id.as_ref())
+        .bind(expiry_column(expiry))
+        .bind(metadata)
+        .bind(data)
+        .bind(previous_id.as_ref())
+        .execute(&mut *transaction)
+        .await
+        {
+            Ok(result) => result.rows_affected(),
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation() => {
+                return Ok(WriteSessionResult::SessionIdExists);
+            }
+            Err(error) => return Err(SqliteStoreError::from(error).into()),
+        };
+        if updated == 0 {
+            return Err(Error::UpdatedSessionDoesNotExist);
+        }
+
+        transaction.commit().await.map_err(SqliteStoreError::from)?;
+        Ok(WriteSessionResult::Ok(()))
+    }
+
+    async fn delete_session(&mut self, id: &SessionId) -> Result<(), Error<Self::Error>> {
+        sqlx::query(&format!("DELETE FROM {} WHERE id = ?1", self.table_name))
+            .bind(id.as_ref())
+            .execute(&self.pool)
+            .await
+            .map_err(SqliteStoreError::from)?;
+        Ok(())
+    }
+
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        let mut transaction = self.pool.begin().await.map_err(SqliteStoreError::from)?;
+        for id in ids {
+            sqlx::query(&format!("DELETE FROM {} WHERE id = ?1", self.table_name))
+                .bind(id.as_ref())
+                .execute(&mut *transaction)
+                .await
+                .map_err(SqliteStoreError::from)?;
+        }
+        transaction.commit().await.map_err(SqliteStoreError::from)?;
+        Ok(())
+    }
+
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        let mut transaction = self.pool.begin().await.map_err(SqliteStoreError::from)?;
+        sqlx::query(&format!("DELETE FROM {} WHERE id = ?1", self.table_name))
+            .bind(id.as_ref())
+            .execute(&mut *transaction)
+            .await
+            .map_err(SqliteStoreError::from)?;
+        sqlx::query(&format!(
+            "INSERT INTO {}_tombstones (id, expiry) VALUES (?1, ?2)
+            ON CONFLICT (id) DO UPDATE SET expiry = excluded.expiry",
+            self.table_name
+        ))
+        .bind(id.as_ref())
+        .bind(expiry.timestamp_millis())
+        .execute(&mut *transaction)
+        .await
+        .map_err(SqliteStoreError::from)?;
+        transaction.commit().await.map_err(SqliteStoreError::from)?;
+        Ok(())
+    }
+
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        let (is_tombstone,): (bool,) = sqlx::query_as(&format!(
+            "SELECT EXISTS (SELECT 1 FROM {}_tombstones WHERE id = ?1 AND expiry > ?2)",
+            self.table_name
+        ))
+        .bind(id.as_ref())
+        .bind(now.timestamp_millis())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SqliteStoreError::from)?;
+        Ok(is_tombstone)
+    }
+
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        let (is_valid,): (bool,) = sqlx::query_as(&format!(
+            "SELECT EXISTS (SELECT 1 FROM {} WHERE id = ?1 AND (expiry IS NULL OR expiry >= ?2))",
+            self.table_name
+        ))
+        .bind(id.as_ref())
+        .bind(now.timestamp_millis())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SqliteStoreError::from)?;
+        Ok(is_valid)
+    }
+
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        let (count,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {}", self.table_name))
+            .fetch_one(&self.pool)
+            .await
+            .map_err(SqliteStoreError::from)?;
+        Ok(usize::try_from(count).unwrap_or(0))
+    }
+
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        let before = before.timestamp_millis();
+        let deleted = sqlx::query(&format!(
+            "DELETE FROM {} WHERE expiry <= ?1",
+            self.table_name
+        ))
+        .bind(before)
+        .execute(&self.pool)
+        .await
+        .map_err(SqliteStoreError::from)?
+        .rows_affected();
+        sqlx::query(&format!(
+            "DELETE FROM {}_tombstones WHERE expiry <= ?1",
+            self.table_name
+        ))
+        .bind(before)
+        .execute(&self.pool)
+        .await
+        .map_err(SqliteStoreError::from)?;
+        tracing::trace!("Deleted {deleted} expired sessions");
+        Ok(deleted)
+    }
+
+    async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
+        let mut transaction = self.pool.begin().await.map_err(SqliteStoreError::from)?;
+        sqlx::query(&format!("DELETE FROM {}", self.table_name))
+            .execute(&mut *transaction)
+            .await
+            .map_err(SqliteStoreError::from)?;
+        sqlx::query(&format!("DELETE FROM {}_tombstones", self.table_name))
+            .execute(&mut *transaction)
+            .await
+            .map_err(SqliteStoreError::from)?;
+        transaction.commit().await.map_err(SqliteStoreError::from)?;
+        Ok(())
+    }
+
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        sqlx::query(&format!(
+            "DELETE FROM {} WHERE json_extract(metadata, '$.namespace') = ?1",
+            self.table_name
+        ))
+        .bind(namespace)
+        .execute(&self.pool)
+        .await
+        .map_err(SqliteStoreError::from)?;
+        Ok(())
+    }
+}
+
+impl<SessionData> Debug for SqliteStore<SessionData> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqliteStore")
+            .field("table_name", &self.table_name)
+            .field(
+                "maximum_retries_on_id_collision",
+                &self.maximum_retries_on_id_collision,
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl<SessionData> Clone for SqliteStore<SessionData> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            table_name: self.table_name.clone(),
+            maximum_retries_on_id_collision: self.maximum_retries_on_id_collision,
+            data: PhantomData,
+        }
+    }
+}
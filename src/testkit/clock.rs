@@ -0,0 +1,61 @@
+use crate::Clock;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex};
+
+/// A [`Clock`] that only advances when told to, available under the feature flag `testkit`.
+///
+/// Clones share their time, such that a test can keep a clone to advance the clock of a session store.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{MemoryStore, Session, SessionRenewalStrategy, SessionStore, SessionCookieCommand};
+/// # use typed_session::testkit::MockClock;
+/// # use chrono::{Duration, Utc};
+/// # async_std::task::block_on(async {
+/// let clock = MockClock::new(Utc::now());
+/// let mut connection = MemoryStore::new();
+/// let mut store: SessionStore<i32, _> = SessionStore::new(SessionRenewalStrategy::Ignore);
+/// store.set_clock(clock.clone());
+/// let mut session = Session::new_with_data(1);
+/// session.expire_in(clock.now(), std::time::Duration::from_secs(60));
+/// let SessionCookieCommand::Set { cookie_value, .. } =
+///     store.store_session(session, &mut connection).await.unwrap() else { panic!() };
+/// clock.advance(Duration::minutes(2));
+/// assert!(store.load_session(&cookie_value, &mut connection).await.unwrap().is_none());
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl MockClock {
+    /// Create a clock that stands still at `now`.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    /// Returns the current time of this clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+
+    /// Sets the current time of this clock.
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Advance this clock by the given duration.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        MockClock::now(self)
+    }
+}
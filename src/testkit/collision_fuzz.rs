@@ -0,0 +1,161 @@
+use crate::testkit::ContractViolation;
+use crate::{
+    CookieValue, Error, SessionCookieGenerator, SessionExpiry, SessionId, SessionMetadata,
+    SessionStoreConnector, WriteSessionResult,
+};
+use rand::seq::IteratorRandom;
+use rand::Rng;
+use std::collections::HashMap;
+use tracing::error;
+
+/// A cookie generator that draws from a small pool of cookies, such that generated session ids collide often,
+/// available under the feature flag `testkit`.
+///
+/// This is insecure and only meant to exercise the id collision handling of connectors,
+/// e.g. with [`fuzz_id_collisions`] or by passing it to
+/// [`SessionStore::new_with_cookie_generator`](crate::SessionStore::new_with_cookie_generator).
+#[derive(Debug, Clone, Copy)]
+pub struct CollidingCookieGenerator {
+    distinct_cookies: usize,
+}
+
+impl CollidingCookieGenerator {
+    /// Create a generator that generates at most `distinct_cookies` different cookies.
+    ///
+    /// **Panics** if `distinct_cookies` is zero.
+    pub fn new(distinct_cookies: usize) -> Self {
+        assert!(distinct_cookies > 0, "distinct_cookies must be positive");
+        Self { distinct_cookies }
+    }
+
+    /// Returns the number of different cookies this generator generates.
+    pub fn distinct_cookies(&self) -> usize {
+        self.distinct_cookies
+    }
+}
+
+impl SessionCookieGenerator for CollidingCookieGenerator {
+    const COOKIE_LENGTH: usize = 32;
+
+    fn generate_cookie(&self) -> String {
+        let index = rand::thread_rng().gen_range(0..self.distinct_cookies);
+        format!("{index:0width$}", width = Self::COOKIE_LENGTH)
+    }
+}
+
+/// The result of [`fuzz_id_collisions`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CollisionFuzzReport {
+    /// The number of attempted creations.
+    pub creations: usize,
+    /// The number of attempted updates.
+    pub updates: usize,
+    /// The number of writes that collided with an existing session, and were hence expected to fail
+    /// with [`WriteSessionResult::SessionIdExists`].
+    pub collisions: usize,
+    /// The violations of the contract of [`SessionStoreConnector`] that were detected.
+    pub violations: Vec<ContractViolation>,
+}
+
+/// Run a randomised sequence of `rounds` creations, updates and deletions against the given connector,
+/// with session ids from the given colliding generator, available under the feature flag `testkit`.
+///
+/// Whenever a write targets the id of an existing session, the connector must return
+/// [`WriteSessionResult::SessionIdExists`] and leave the existing session untouched.
+/// Overwriting it instead is a subtle bug of hand-written connectors,
+/// since with secure cookie generators, collisions practically never happen in tests.
+/// Such bugs are reported as [`ContractViolation::ReusedSessionId`] or [`ContractViolation::OverwrittenSession`].
+///
+/// The connector should be empty, and it must not be used concurrently.
+/// All sessions that were created by the fuzzer are deleted at the end.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::MemoryStore;
+/// # use typed_session::testkit::{fuzz_id_collisions, CollidingCookieGenerator};
+/// # async_std::task::block_on(async {
+/// let mut connection = MemoryStore::new();
+/// let report = fuzz_id_collisions(&mut connection, &CollidingCookieGenerator::new(16), 200).await?;
+/// assert!(report.collisions > 0);
+/// assert!(report.violations.is_empty());
+/// assert!(connection.is_empty());
+/// # Ok::<(), typed_session::Error<std::convert::Infallible>>(()) }).unwrap();
+/// ```
+pub async fn fuzz_id_collisions<SessionStoreConnection: SessionStoreConnector<u64> + Send>(
+    connection: &mut SessionStoreConnection,
+    generator: &CollidingCookieGenerator,
+    rounds: usize,
+) -> Result<CollisionFuzzReport, Error<SessionStoreConnection::Error>> {
+    let mut report = CollisionFuzzReport::default();
+    // The data of each session that should exist.
+    let mut live: HashMap<SessionId, u64> = HashMap::new();
+    let metadata = SessionMetadata::default();
+
+    for round in 0..rounds {
+        let data = round as u64;
+        let id = SessionId::from_cookie_value(&CookieValue::new(generator.generate_cookie()));
+        let previous = live.keys().choose(&mut rand::thread_rng()).cloned();
+
+        match (previous, rand::thread_rng().gen_range(0..3)) {
+            (Some(previous_id), 0) => {
+                connection.delete_session(&previous_id).await?;
+                live.remove(&previous_id);
+            }
+            (Some(previous_id), 1) if previous_id != id => {
+                report.updates += 1;
+                let result = connection
+                    .update_session(&id, &previous_id, &SessionExpiry::Never, &metadata, &data)
+                    .await?;
+                if check_write(connection, &mut report, &live, &id, result).await? {
+                    live.remove(&previous_id);
+                    live.insert(id, data);
+                }
+            }
+            _ => {
+                report.creations += 1;
+                let result = connection
+                    .create_session(&id, &SessionExpiry::Never, &metadata, &data)
+                    .await?;
+                if check_write(connection, &mut report, &live, &id, result).await? {
+                    live.insert(id, data);
+                }
+            }
+        }
+    }
+
+    let ids: Vec<_> = live.into_keys().collect();
+    connection.delete_sessions(&ids).await?;
+    Ok(report)
+}
+
+/// Check the result of a write of the session with the given id, and return true if the write succeeded.
+async fn check_write<SessionStoreConnection: SessionStoreConnector<u64>>(
+    connection: &mut SessionStoreConnection,
+    report: &mut CollisionFuzzReport,
+    live: &HashMap<SessionId, u64>,
+    id: &SessionId,
+    result: WriteSessionResult,
+) -> Result<bool, Error<SessionStoreConnection::Error>> {
+    let Some(existing) = live.get(id) else {
+        return Ok(matches!(result, WriteSessionResult::Ok(())));
+    };
+    report.collisions += 1;
+
+    let (violation, written) = match result {
+        WriteSessionResult::Ok(()) => (ContractViolation::ReusedSessionId { id: id.clone() }, true),
+        WriteSessionResult::SessionIdExists => {
+            let session = connection.read_session(id.clone()).await?;
+            if session.is_some_and(|session| session.data() == existing) {
+                return Ok(false);
+            }
+            (
+                ContractViolation::OverwrittenSession { id: id.clone() },
+                false,
+            )
+        }
+    };
+    error!("Session store connector violated its contract: {violation:?}");
+    report.violations.push(violation);
+    Ok(written)
+}
@@ -0,0 +1,350 @@
+use crate::{
+    ConnectorDescription, Error, RequestContext, Session, SessionExpiry, SessionId,
+    SessionMetadata, SessionStoreConnector, WriteSessionResult,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tracing::error;
+
+/// A violation of the contract of [`SessionStoreConnector`], detected by a [`ContractCheckingStore`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ContractViolation {
+    /// An update succeeded, although the session with the previous id was already updated or deleted before.
+    /// This means that a session has two successors, see [`SessionStoreConnector::update_session`].
+    UpdatedRetiredSession {
+        /// The previous id of the update.
+        previous_id: SessionId,
+    },
+    /// A create or update succeeded with the id of a session that exists.
+    ReusedSessionId {
+        /// The reused id.
+        id: SessionId,
+    },
+    /// A create or update succeeded with the id of a tombstone that is not expired,
+    /// see [`SessionStoreConnector::create_tombstone`].
+    ReusedTombstoneId {
+        /// The reused id.
+        id: SessionId,
+    },
+    /// A read returned a session that was updated or deleted before.
+    ReadRetiredSession {
+        /// The id of the read session.
+        id: SessionId,
+    },
+    /// A write failed with [`WriteSessionResult::SessionIdExists`], but changed the existing session anyway,
+    /// see [`fuzz_id_collisions`](crate::testkit::fuzz_id_collisions).
+    OverwrittenSession {
+        /// The id of the overwritten session.
+        id: SessionId,
+    },
+}
+
+#[derive(Debug, Default)]
+struct Model {
+    /// The ids of the sessions that exist, with their namespace.
+    live: HashMap<SessionId, Option<String>>,
+    /// The ids of the sessions that were updated or deleted.
+    retired: HashSet<SessionId>,
+    tombstones: HashMap<SessionId, DateTime<Utc>>,
+    violations: Vec<ContractViolation>,
+}
+
+/// A session store connector that checks whether the connector it wraps upholds the contract of
+/// [`SessionStoreConnector`], available under the feature flag `testkit`.
+///
+/// All operations are shadowed in a model of the expected state of the backend.
+/// When the wrapped connector behaves in a way that the model forbids, e.g. by allowing two successful updates
+/// of the same previous id, the violation is logged and recorded, see [`violations`](ContractCheckingStore::violations).
+/// By default, a violation additionally panics, such that tests fail at the violating operation.
+///
+/// Clones share their model, such that concurrent operations through multiple clones are checked against each other.
+/// The model only knows about operations done through this wrapper, and it never forgets updated or deleted ids.
+/// Hence, this is meant for tests, not for production.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::{MemoryStore, Session, SessionRenewalStrategy, SessionStore};
+/// # use typed_session::testkit::ContractCheckingStore;
+/// # async_std::task::block_on(async {
+/// let mut connection = ContractCheckingStore::new(MemoryStore::new());
+/// let store: SessionStore<i32, _> = SessionStore::new(SessionRenewalStrategy::Ignore);
+/// store.store_session(Session::new_with_data(1), &mut connection).await.unwrap();
+/// assert!(connection.violations().is_empty());
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct ContractCheckingStore<SessionStoreConnection> {
+    inner: SessionStoreConnection,
+    model: Arc<Mutex<Model>>,
+    panic_on_violation: bool,
+}
+
+impl<SessionStoreConnection> ContractCheckingStore<SessionStoreConnection> {
+    /// Wrap the given connector.
+    pub fn new(inner: SessionStoreConnection) -> Self {
+        Self {
+            inner,
+            model: Default::default(),
+            panic_on_violation: true,
+        }
+    }
+
+    /// Returns the wrapped connector.
+    pub fn inner(&self) -> &SessionStoreConnection {
+        &self.inner
+    }
+
+    /// Returns the wrapped connector, consuming this wrapper.
+    pub fn into_inner(self) -> SessionStoreConnection {
+        self.inner
+    }
+
+    /// Returns true if a violation panics, see [`set_panic_on_violation`](ContractCheckingStore::set_panic_on_violation).
+    pub fn panic_on_violation(&self) -> bool {
+        self.panic_on_violation
+    }
+
+    /// If true, which is the default, a violation panics after it was recorded.
+    /// Otherwise, violations are only logged and recorded.
+    pub fn set_panic_on_violation(&mut self, panic_on_violation: bool) {
+        self.panic_on_violation = panic_on_violation;
+    }
+
+    /// Returns all violations recorded so far by this wrapper and its clones.
+    pub fn violations(&self) -> Vec<ContractViolation> {
+        self.model.lock().unwrap().violations.clone()
+    }
+
+    /// Record the given violations, and panic if configured.
+    fn report(&self, violations: Vec<ContractViolation>) {
+        if violations.is_empty() {
+            return;
+        }
+        for violation in &violations {
+            error!("Session store connector violated its contract: {violation:?}");
+        }
+        // Release the lock before panicking, such that the model stays usable.
+        self.model
+            .lock()
+            .unwrap()
+            .violations
+            .extend(violations.iter().cloned());
+        if self.panic_on_violation {
+            panic!("session store connector violated its contract: {violations:?}");
+        }
+    }
+
+    /// Record that a session with the given `id` was successfully written.
+    fn record_write(&self, id: &SessionId, metadata: &SessionMetadata) {
+        let mut violations = Vec::new();
+        {
+            let mut model = self.model.lock().unwrap();
+            if model.live.contains_key(id) {
+                violations.push(ContractViolation::ReusedSessionId { id: id.clone() });
+            }
+            if model
+                .tombstones
+                .get(id)
+                .is_some_and(|expiry| *expiry > Utc::now())
+            {
+                violations.push(ContractViolation::ReusedTombstoneId { id: id.clone() });
+            }
+            model.retired.remove(id);
+            model.live.insert(id.clone(), metadata.namespace.clone());
+        }
+        self.report(violations);
+    }
+
+    fn is_retired(&self, id: &SessionId) -> bool {
+        self.model.lock().unwrap().retired.contains(id)
+    }
+
+    fn retire(&self, id: &SessionId) {
+        let mut model = self.model.lock().unwrap();
+        model.live.remove(id);
+        model.retired.insert(id.clone());
+    }
+}
+
+#[async_trait]
+impl<
+        SessionData: Send + Sync,
+        SessionStoreConnection: SessionStoreConnector<SessionData> + Send,
+    > SessionStoreConnector<SessionData> for ContractCheckingStore<SessionStoreConnection>
+{
+    type Error = SessionStoreConnection::Error;
+
+    fn maximum_retries_on_id_collision(&self) -> Option<u32> {
+        self.inner.maximum_retries_on_id_collision()
+    }
+
+    fn is_transient_error(&self, error: &Self::Error) -> bool {
+        self.inner.is_transient_error(error)
+    }
+
+    fn describe(&self) -> ConnectorDescription {
+        ConnectorDescription::wrapping("ContractCheckingStore", [self.inner.describe()])
+    }
+
+    fn enforces_expiry(&self) -> bool {
+        self.inner.enforces_expiry()
+    }
+
+    fn accepts_session_id(&self, new_id: &SessionId, previous_id: Option<&SessionId>) -> bool {
+        self.inner.accepts_session_id(new_id, previous_id)
+    }
+
+    async fn create_session(
+        &mut self,
+        current_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let result = self
+            .inner
+            .create_session(current_id, expiry, metadata, data)
+            .await?;
+        if let WriteSessionResult::Ok(()) = result {
+            self.record_write(current_id, metadata);
+        }
+        Ok(result)
+    }
+
+    async fn read_session(
+        &mut self,
+        id: SessionId,
+    ) -> Result<Option<Session<SessionData>>, Error<Self::Error>> {
+        let session = self.inner.read_session(id.clone()).await?;
+        if session.is_some() && self.is_retired(&id) {
+            self.report(vec![ContractViolation::ReadRetiredSession { id }]);
+        }
+        Ok(session)
+    }
+
+    async fn update_session(
+        &mut self,
+        current_id: &SessionId,
+        previous_id: &SessionId,
+        expiry: &SessionExpiry,
+        metadata: &SessionMetadata,
+        data: &SessionData,
+    ) -> Result<WriteSessionResult, Error<Self::Error>> {
+        let result = self
+            .inner
+            .update_session(current_id, previous_id, expiry, metadata, data)
+            .await?;
+        if let WriteSessionResult::Ok(()) = result {
+            if self.is_retired(previous_id) {
+                self.report(vec![ContractViolation::UpdatedRetiredSession {
+                    previous_id: previous_id.clone(),
+                }]);
+            }
+            self.retire(previous_id);
+            self.record_write(current_id, metadata);
+        }
+        Ok(result)
+    }
+
+    async fn delete_session(&mut self, id: &SessionId) -> Result<(), Error<Self::Error>> {
+        self.inner.delete_session(id).await?;
+        self.retire(id);
+        Ok(())
+    }
+
+    async fn delete_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.inner.delete_sessions(ids).await?;
+        for id in ids {
+            self.retire(id);
+        }
+        Ok(())
+    }
+
+    async fn create_tombstone(
+        &mut self,
+        id: &SessionId,
+        expiry: DateTime<Utc>,
+    ) -> Result<(), Error<Self::Error>> {
+        self.inner.create_tombstone(id, expiry).await?;
+        self.retire(id);
+        self.model
+            .lock()
+            .unwrap()
+            .tombstones
+            .insert(id.clone(), expiry);
+        Ok(())
+    }
+
+    async fn is_tombstone(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.inner.is_tombstone(id, now).await
+    }
+
+    async fn is_session_valid(
+        &mut self,
+        id: &SessionId,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Error<Self::Error>> {
+        self.inner.is_session_valid(id, now).await
+    }
+
+    async fn now(&mut self) -> Result<Option<DateTime<Utc>>, Error<Self::Error>> {
+        self.inner.now().await
+    }
+
+    fn set_request_context(&mut self, context: Option<&RequestContext>) {
+        self.inner.set_request_context(context);
+    }
+
+    async fn preload_sessions(&mut self, ids: &[SessionId]) -> Result<(), Error<Self::Error>> {
+        self.inner.preload_sessions(ids).await
+    }
+
+    async fn count_sessions(&mut self) -> Result<usize, Error<Self::Error>> {
+        self.inner.count_sessions().await
+    }
+
+    async fn delete_expired_sessions(
+        &mut self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, Error<Self::Error>> {
+        self.inner.delete_expired_sessions(before).await
+    }
+
+    async fn clear(&mut self) -> Result<(), Error<Self::Error>> {
+        self.inner.clear().await?;
+        let mut model = self.model.lock().unwrap();
+        let Model {
+            live,
+            retired,
+            tombstones,
+            ..
+        } = &mut *model;
+        retired.extend(live.drain().map(|(id, _)| id));
+        tombstones.clear();
+        Ok(())
+    }
+
+    async fn clear_namespace(&mut self, namespace: &str) -> Result<(), Error<Self::Error>> {
+        self.inner.clear_namespace(namespace).await?;
+        let mut model = self.model.lock().unwrap();
+        let ids: Vec<_> = model
+            .live
+            .iter()
+            .filter(|(_, session_namespace)| session_namespace.as_deref() == Some(namespace))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in ids {
+            model.live.remove(&id);
+            model.retired.insert(id);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,12 @@
+//! Utilities for testing session store connectors and for evaluating the configuration of a session store
+//! before deploying it.
+
+mod clock;
+mod collision_fuzz;
+mod contract_checking_store;
+mod renewal;
+
+pub use clock::MockClock;
+pub use collision_fuzz::{fuzz_id_collisions, CollidingCookieGenerator, CollisionFuzzReport};
+pub use contract_checking_store::{ContractCheckingStore, ContractViolation};
+pub use renewal::{simulate_renewal, RenewalEvent, RenewalSummary};
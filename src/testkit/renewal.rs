@@ -0,0 +1,138 @@
+use crate::{Session, SessionExpiry, SessionRenewalStrategy};
+use chrono::{DateTime, Duration, Utc};
+
+/// An event of a simulated session, see [`simulate_renewal`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RenewalEvent {
+    /// A new session was created, because the client had no valid session.
+    Created {
+        /// The time of the access that created the session.
+        time: DateTime<Utc>,
+        /// The expiry of the new session.
+        expiry: SessionExpiry,
+    },
+    /// The expiry of the session was renewed.
+    Renewed {
+        /// The time of the access that renewed the session.
+        time: DateTime<Utc>,
+        /// The expiry before the renewal.
+        previous_expiry: SessionExpiry,
+        /// The expiry after the renewal.
+        expiry: SessionExpiry,
+    },
+    /// The session expired.
+    Expired {
+        /// The time at which the session expired.
+        time: DateTime<Utc>,
+        /// The time between the creation of the session and its expiry.
+        lifetime: Duration,
+    },
+}
+
+/// A summary of the events of [`simulate_renewal`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct RenewalSummary {
+    /// The number of created sessions.
+    pub sessions: usize,
+    /// The number of renewals.
+    pub renewals: usize,
+    /// The lifetimes of all sessions that expired, in order of their expiry.
+    pub lifetimes: Vec<Duration>,
+}
+
+impl RenewalSummary {
+    /// Summarise the given events.
+    pub fn from_events<'a>(events: impl IntoIterator<Item = &'a RenewalEvent>) -> Self {
+        let mut summary = Self::default();
+        for event in events {
+            match event {
+                RenewalEvent::Created { .. } => summary.sessions += 1,
+                RenewalEvent::Renewed { .. } => summary.renewals += 1,
+                RenewalEvent::Expired { lifetime, .. } => summary.lifetimes.push(*lifetime),
+            }
+        }
+        summary
+    }
+}
+
+/// Replay the accesses of a single client at the given times against the given renewal strategy.
+///
+/// The client keeps its session as long as it is valid, and obtains a new session when accessing after its expiry.
+/// This does not involve a session store, and hence allows to compare renewal strategies e.g. in unit tests.
+/// The last session expires after the last access, unless it never expires.
+///
+/// **Panics** if the access times are not sorted.
+///
+/// # Example
+///
+/// ```rust
+/// # use typed_session::SessionRenewalStrategy;
+/// # use typed_session::testkit::{simulate_renewal, RenewalSummary};
+/// # use chrono::{Duration, TimeZone, Utc};
+/// let start = Utc.timestamp_opt(0, 0).unwrap();
+/// let strategy = SessionRenewalStrategy::AutomaticRenewal {
+///     time_to_live: Duration::hours(2),
+///     maximum_remaining_time_to_live_for_renewal: Duration::hours(1),
+/// };
+/// // A client that is active for four hours, and returns after a break of five hours.
+/// let accesses = (0..16).chain(36..40).map(|i| start + Duration::minutes(15) * i);
+/// let summary = RenewalSummary::from_events(&simulate_renewal(strategy, accesses));
+/// assert_eq!(summary.sessions, 2);
+/// assert_eq!(summary.renewals, 3);
+/// ```
+pub fn simulate_renewal(
+    strategy: SessionRenewalStrategy,
+    access_pattern: impl IntoIterator<Item = DateTime<Utc>>,
+) -> Vec<RenewalEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<(DateTime<Utc>, Session<()>)> = None;
+    let mut previous_time = None;
+
+    for time in access_pattern {
+        assert!(
+            previous_time.is_none_or(|previous_time| previous_time <= time),
+            "access times must be sorted"
+        );
+        previous_time = Some(time);
+
+        if let Some((created_at, session)) = &mut current {
+            if session.expiry().is_expired(time) {
+                // The client obtains a new session below.
+                push_expiry(&mut events, *created_at, session);
+            } else {
+                let previous_expiry = *session.expiry();
+                strategy.apply_to_session(session, time);
+                if *session.expiry() != previous_expiry {
+                    events.push(RenewalEvent::Renewed {
+                        time,
+                        previous_expiry,
+                        expiry: *session.expiry(),
+                    });
+                }
+                continue;
+            }
+        }
+
+        let mut session = Session::new();
+        strategy.apply_to_session(&mut session, time);
+        events.push(RenewalEvent::Created {
+            time,
+            expiry: *session.expiry(),
+        });
+        current = Some((time, session));
+    }
+
+    if let Some((created_at, session)) = &current {
+        push_expiry(&mut events, *created_at, session);
+    }
+    events
+}
+
+fn push_expiry(events: &mut Vec<RenewalEvent>, created_at: DateTime<Utc>, session: &Session<()>) {
+    if let SessionExpiry::DateTime(expiry) = *session.expiry() {
+        events.push(RenewalEvent::Expired {
+            time: expiry,
+            lifetime: expiry - created_at,
+        });
+    }
+}